@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{fs::File, io::Write as _, path::PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use mpeg_audio_header::{Header, ParseMode};
+
+/// Number of frames of the generated stream, i.e. about 26 minutes
+const FRAME_COUNT: usize = 40_000;
+
+/// Generate a VBR stream of MPEG-1 Layer III frames (44.1 kHz, Stereo)
+fn vbr_stream() -> Vec<u8> {
+    // Bitrate index and the corresponding bitrate in kbps
+    const BITRATES: [(u8, usize); 6] = [
+        (0b1001, 128),
+        (0b1010, 160),
+        (0b1011, 192),
+        (0b1100, 224),
+        (0b1101, 256),
+        (0b1110, 320),
+    ];
+    let mut data = Vec::new();
+    for frame_index in 0..FRAME_COUNT {
+        let (bitrate_bits, bitrate_kbps) = BITRATES[frame_index * 7 % BITRATES.len()];
+        let frame_size = 144 * bitrate_kbps * 1000 / 44100;
+        data.extend_from_slice(&[0xFF, 0xFB, bitrate_bits << 4, 0x00]);
+        data.resize(data.len() + frame_size - 4, 0x55);
+    }
+    data
+}
+
+fn read_vbr_stream(c: &mut Criterion) {
+    let data = vbr_stream();
+    let path: PathBuf = std::env::temp_dir().join("mpeg-audio-header-bench-vbr.mp3");
+    File::create(&path)
+        .and_then(|mut file| file.write_all(&data))
+        .unwrap();
+
+    let mut group = c.benchmark_group("IgnoreVbrHeaders");
+    group.bench_function("read_from_path", |b| {
+        b.iter(|| Header::read_from_path(&path, ParseMode::IgnoreVbrHeaders).unwrap());
+    });
+    group.bench_function("read_from_slice", |b| {
+        b.iter(|| Header::read_from_slice(&data, ParseMode::IgnoreVbrHeaders).unwrap());
+    });
+    group.finish();
+
+    std::fs::remove_file(&path).ok();
+}
+
+criterion_group!(benches, read_vbr_stream);
+criterion_main!(benches);
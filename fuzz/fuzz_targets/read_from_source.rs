@@ -0,0 +1,32 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mpeg_audio_header::{Header, ParseMode, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let options = ParseOptions {
+        require_next_sync: true,
+        detect_multichannel: true,
+        capture_vbr_header: true,
+        min_bitrate_run_frames: Some(2),
+        parse_mllt: true,
+        detect_bit_reservoir: true,
+        vbr_header_tolerance_percent: Some(10),
+        ..Default::default()
+    };
+    for parse_mode in [
+        ParseMode::PreferVbrHeaders,
+        ParseMode::IgnoreVbrHeaders,
+        ParseMode::Reconcile,
+    ] {
+        let _ = Header::read_from_source_with_options(
+            &mut &data[..],
+            parse_mode,
+            &ParseOptions::default(),
+        );
+        let _ = Header::read_from_source_with_options(&mut &data[..], parse_mode, &options);
+    }
+});
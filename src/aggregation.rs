@@ -0,0 +1,571 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::time::Duration;
+
+use crate::{
+    duration_from_samples, frame, ChannelSemantics, Emphasis, FrameFlags, FrameHeader, Header,
+    HeaderSource, Layer, Mode, ReadPosition, Version,
+};
+
+/// Summary of all frames that share the same profile
+///
+/// A profile is the combination of version, layer, and sample rate
+/// that determines the duration of a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProfileSummary {
+    /// MPEG version
+    pub version: Version,
+
+    /// MPEG layer
+    pub layer: Layer,
+
+    /// Sample rate in Hz
+    pub sample_rate_hz: u16,
+
+    /// Number of frames
+    pub frame_count: u64,
+
+    /// Total number of samples in all frames
+    pub total_samples: u64,
+}
+
+impl ProfileSummary {
+    /// The duration of a single frame
+    #[must_use]
+    pub fn frame_duration(&self) -> Duration {
+        duration_from_samples(
+            frame::samples_per_frame(self.version, self.layer).into(),
+            self.sample_rate_hz,
+        )
+    }
+}
+
+/// Intermediate state while aggregating MPEG frame headers
+///
+/// Accumulates the properties of all audio frames that have been
+/// fed into it. Finalizing the state yields a [`Header`] with
+/// [`HeaderSource::MpegFrameHeaders`].
+///
+/// The state could be persisted between parsing sessions when
+/// the `serde` feature is enabled, e.g. for resuming the parsing
+/// of a stream that arrives in chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregationState {
+    version: ConsistentValue<Version>,
+
+    layer: ConsistentValue<Layer>,
+
+    mode: ConsistentValue<Mode>,
+
+    channel_semantics: ConsistentValue<ChannelSemantics>,
+
+    emphasis: ConsistentValue<Emphasis>,
+
+    flags: FrameFlags<ConsistentValue<bool>>,
+
+    min_channel_count: u8,
+    max_channel_count: u8,
+
+    frame_count: u64,
+    mode_frame_counts: [u64; 4],
+    mono_frame_count: u64,
+    stereo_frame_count: u64,
+    padding_frame_count: u64,
+    sum_sample_count: u64,
+
+    min_sample_rate_hz: u16,
+    max_sample_rate_hz: u16,
+    accmul_sample_rate_hz: u64,
+
+    max_frame_size: Option<u16>,
+
+    accmul_bitrate_bps: u64,
+    accmul_squared_bitrate_bps: u128,
+    bitrate_sample_count: u64,
+
+    profiles: Vec<ProfileSummary>,
+
+    first_frame: Option<FrameHeader>,
+
+    position: ReadPosition,
+}
+
+impl Default for AggregationState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A property that is only defined as long as all values are equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum ConsistentValue<T> {
+    Empty,
+    Consistent(T),
+    Inconsistent,
+}
+
+impl<T: Copy + PartialEq> ConsistentValue<T> {
+    fn aggregate(&mut self, next: T) {
+        match self {
+            Self::Empty => *self = Self::Consistent(next),
+            Self::Consistent(value) => {
+                if *value != next {
+                    *self = Self::Inconsistent;
+                }
+            }
+            Self::Inconsistent => (),
+        }
+    }
+
+    const fn value(self) -> Option<T> {
+        match self {
+            Self::Consistent(value) => Some(value),
+            Self::Empty | Self::Inconsistent => None,
+        }
+    }
+}
+
+impl FrameFlags<ConsistentValue<bool>> {
+    fn aggregate(&mut self, next: FrameFlags) {
+        self.protection.aggregate(next.protection);
+        self.padding.aggregate(next.padding);
+        self.private.aggregate(next.private);
+        self.copyright.aggregate(next.copyright);
+        self.original.aggregate(next.original);
+    }
+
+    const fn value(self) -> FrameFlags<Option<bool>> {
+        FrameFlags {
+            protection: self.protection.value(),
+            padding: self.padding.value(),
+            private: self.private.value(),
+            copyright: self.copyright.value(),
+            original: self.original.value(),
+        }
+    }
+}
+
+/// Aggregate the minimum, ignoring the initial value 0
+pub(crate) fn aggregate_min<T: Copy + Ord + Default>(min: &mut T, next: T) {
+    if *min == T::default() {
+        *min = next;
+    } else {
+        *min = (*min).min(next);
+    }
+}
+
+impl AggregationState {
+    /// Create an empty state
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            version: ConsistentValue::Empty,
+            layer: ConsistentValue::Empty,
+            mode: ConsistentValue::Empty,
+            channel_semantics: ConsistentValue::Empty,
+            emphasis: ConsistentValue::Empty,
+            flags: FrameFlags {
+                protection: ConsistentValue::Empty,
+                padding: ConsistentValue::Empty,
+                private: ConsistentValue::Empty,
+                copyright: ConsistentValue::Empty,
+                original: ConsistentValue::Empty,
+            },
+            min_channel_count: 0,
+            max_channel_count: 0,
+            frame_count: 0,
+            mode_frame_counts: [0; 4],
+            mono_frame_count: 0,
+            stereo_frame_count: 0,
+            padding_frame_count: 0,
+            sum_sample_count: 0,
+            min_sample_rate_hz: 0,
+            max_sample_rate_hz: 0,
+            accmul_sample_rate_hz: 0,
+            max_frame_size: None,
+            accmul_bitrate_bps: 0,
+            accmul_squared_bitrate_bps: 0,
+            bitrate_sample_count: 0,
+            profiles: Vec::new(),
+            first_frame: None,
+            position: ReadPosition {
+                byte_offset: 0,
+                duration: std::time::Duration::ZERO,
+            },
+        }
+    }
+
+    /// Accumulate the properties of an audio frame
+    ///
+    /// The position advances by the frame size (if known) and
+    /// by the playback duration of the frame.
+    ///
+    /// Frames without any samples are ignored. They are never decoded
+    /// by the parser, but might be constructed manually.
+    pub fn feed_frame(&mut self, frame_header: &FrameHeader) {
+        if frame_header.sample_count == 0 {
+            return;
+        }
+        if self.first_frame.is_none() {
+            self.first_frame = Some(frame_header.clone());
+        }
+        self.version.aggregate(frame_header.version);
+        self.layer.aggregate(frame_header.layer);
+        self.mode.aggregate(frame_header.mode);
+        self.channel_semantics
+            .aggregate(frame_header.effective_channels());
+        if frame_header.emphasis == Emphasis::Reserved {
+            // Never report a reserved emphasis
+            self.emphasis = ConsistentValue::Inconsistent;
+        } else {
+            self.emphasis.aggregate(frame_header.emphasis);
+        }
+
+        let frame_samples = u64::from(frame_header.sample_count);
+        self.frame_count += 1;
+        self.mode_frame_counts[frame::mode_index(frame_header.mode)] += 1;
+        if frame_header.channel_count() == 1 {
+            self.mono_frame_count += 1;
+        } else {
+            self.stereo_frame_count += 1;
+        }
+        self.flags.aggregate(frame_header.flags);
+        if frame_header.flags.padding {
+            self.padding_frame_count += 1;
+        }
+        self.sum_sample_count += frame_samples;
+
+        let channel_count = frame_header.channel_count();
+        debug_assert!(channel_count > 0);
+        aggregate_min(&mut self.min_channel_count, channel_count);
+        self.max_channel_count = self.max_channel_count.max(channel_count);
+
+        if let Some(frame_size) = frame_header.frame_size {
+            self.max_frame_size = self.max_frame_size.max(Some(frame_size));
+            self.position.byte_offset += u64::from(frame_size);
+        }
+
+        // Free bitrate = 0 bps
+        if let Some(bitrate_bps) = frame_header.bitrate_bps {
+            self.accmul_bitrate_bps += u64::from(bitrate_bps) * frame_samples;
+            self.accmul_squared_bitrate_bps +=
+                u128::from(bitrate_bps) * u128::from(bitrate_bps) * u128::from(frame_samples);
+            self.bitrate_sample_count += frame_samples;
+        }
+
+        debug_assert!(frame_header.sample_rate_hz > 0);
+        aggregate_min(&mut self.min_sample_rate_hz, frame_header.sample_rate_hz);
+        self.max_sample_rate_hz = self.max_sample_rate_hz.max(frame_header.sample_rate_hz);
+        self.accmul_sample_rate_hz += u64::from(frame_header.sample_rate_hz) * frame_samples;
+
+        if let Some(profile) = self.profiles.iter_mut().find(|profile| {
+            profile.version == frame_header.version
+                && profile.layer == frame_header.layer
+                && profile.sample_rate_hz == frame_header.sample_rate_hz
+        }) {
+            profile.frame_count += 1;
+            profile.total_samples += frame_samples;
+        } else {
+            self.profiles.push(ProfileSummary {
+                version: frame_header.version,
+                layer: frame_header.layer,
+                sample_rate_hz: frame_header.sample_rate_hz,
+                frame_count: 1,
+                total_samples: frame_samples,
+            });
+        }
+
+        self.position.duration += frame_header.frame_duration();
+    }
+
+    /// The number of audio frames that have been fed
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The total number of samples in all audio frames that have been fed
+    #[must_use]
+    pub const fn total_sample_count(&self) -> u64 {
+        self.sum_sample_count
+    }
+
+    /// The accumulated size and duration of all audio frames that have been fed
+    #[must_use]
+    pub const fn position(&self) -> &ReadPosition {
+        &self.position
+    }
+
+    /// Build the header from the aggregated properties
+    ///
+    /// Properties that are not derived from the frame headers,
+    /// e.g. [`Header::truncated_metadata`], remain empty.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn finalize(self) -> Header {
+        let Self {
+            version,
+            layer,
+            mode,
+            channel_semantics,
+            emphasis,
+            flags,
+            min_channel_count,
+            max_channel_count,
+            mode_frame_counts,
+            mono_frame_count,
+            stereo_frame_count,
+            padding_frame_count,
+            sum_sample_count: total_sample_count,
+            min_sample_rate_hz,
+            max_sample_rate_hz,
+            accmul_sample_rate_hz,
+            max_frame_size,
+            accmul_bitrate_bps,
+            accmul_squared_bitrate_bps,
+            bitrate_sample_count,
+            profiles,
+            first_frame,
+            position,
+            ..
+        } = self;
+
+        let avg_sample_rate_hz =
+            accmul_sample_rate_hz
+                .checked_div(total_sample_count)
+                .map(|avg_sample_rate_hz| {
+                    debug_assert!(avg_sample_rate_hz <= u16::MAX.into());
+                    avg_sample_rate_hz as u16
+                });
+
+        let avg_bitrate_bps =
+            accmul_bitrate_bps
+                .checked_div(total_sample_count)
+                .map(|avg_bitrate_bps| {
+                    debug_assert!(avg_bitrate_bps <= u32::MAX.into());
+                    avg_bitrate_bps as u32
+                });
+
+        // The precision of f64 is sufficient for a statistical measure
+        #[allow(clippy::cast_precision_loss)]
+        let bitrate_cv = (bitrate_sample_count > 0 && accmul_bitrate_bps > 0).then(|| {
+            let squared_sum = u128::from(accmul_bitrate_bps).checked_mul(accmul_bitrate_bps.into());
+            let scaled_sum_of_squares =
+                accmul_squared_bitrate_bps.checked_mul(bitrate_sample_count.into());
+            if squared_sum.is_some() && squared_sum == scaled_sum_of_squares {
+                // Exactly 0 for a constant bitrate without any rounding errors
+                return 0.0;
+            }
+            let sample_count = bitrate_sample_count as f64;
+            let mean = accmul_bitrate_bps as f64 / sample_count;
+            let mean_of_squares = accmul_squared_bitrate_bps as f64 / sample_count;
+            // Rounding errors might result in a slightly negative variance
+            let variance = (mean_of_squares - mean * mean).max(0.0);
+            variance.sqrt() / mean
+        });
+
+        let program_count = match channel_semantics {
+            ConsistentValue::Empty => 0,
+            ConsistentValue::Consistent(channel_semantics) => channel_semantics.program_count(),
+            ConsistentValue::Inconsistent => 1,
+        };
+
+        Header {
+            source: HeaderSource::MpegFrameHeaders,
+            version: version.value(),
+            layer: layer.value(),
+            mode: mode.value(),
+            channel_semantics: channel_semantics.value(),
+            emphasis: emphasis.value(),
+            common_flags: flags.value(),
+            min_channel_count,
+            max_channel_count,
+            program_count,
+            mode_frame_counts,
+            mono_frame_count,
+            stereo_frame_count,
+            padding_frame_count,
+            possible_multichannel: false,
+            multichannel_count: None,
+            min_sample_rate_hz,
+            max_sample_rate_hz,
+            total_sample_count,
+            samples_per_frame: version
+                .value()
+                .zip(layer.value())
+                .map(|(version, layer)| frame::samples_per_frame(version, layer)),
+            total_duration: position.duration,
+            reconciled_declared_duration: None,
+            measured_duration: None,
+            measured_frame_count: None,
+            measured_sample_count: None,
+            avg_sample_rate_hz,
+            max_frame_size,
+            avg_bitrate_bps,
+            // Round to the nearest kbps
+            avg_bitrate_kbps: avg_bitrate_bps.map(|bps| ((bps + 500) / 1000) as u16),
+            bitrate_cv,
+            uses_bit_reservoir: None,
+            bitrate_monotonic: None,
+            truncated_metadata: None,
+            truncated_frame: None,
+            alignment_errors: 0,
+            junk_byte_count: 0,
+            partial: false,
+            stopped_at: None,
+            read_error: None,
+            final_position: position,
+            stream_byte_len: None,
+            raw_vbr_header: None,
+            declared_frame_count: None,
+            declared_vbr: None,
+            vbr_header_suspect: false,
+            cbr_claim_violated: false,
+            vbr_frame_samples: None,
+            vbri_toc: None,
+            vbri_delay: None,
+            lame_info: None,
+            start_skip_samples: None,
+            end_skip_samples: None,
+            min_bitrate_runs: Vec::new(),
+            lead_in_confidence: 0,
+            first_frame_offset: None,
+            audio_end_offset: None,
+            profiles,
+            mllt_seek_points: None,
+            apev2_info: None,
+            trailing_tags: Vec::new(),
+            diagnostics: Vec::new(),
+            #[cfg(feature = "id3-lite")]
+            basic_tags: None,
+            first_frame,
+        }
+    }
+}
+
+/// Runs of consecutive frames at the minimum bitrate of the stream
+///
+/// The minimum bitrate is only known after all frames have been
+/// traversed. All runs collected so far are discarded whenever a
+/// new, lower minimum bitrate is encountered.
+#[derive(Debug)]
+pub(crate) struct MinBitrateRuns {
+    min_run_frames: u32,
+    min_bitrate_bps: u32,
+    current_run: Option<MinBitrateRun>,
+    runs: Vec<(u64, u64)>,
+}
+
+#[derive(Debug)]
+struct MinBitrateRun {
+    sample_offset: u64,
+    sample_count: u64,
+    frame_count: u32,
+}
+
+impl MinBitrateRuns {
+    pub(crate) const fn new(min_run_frames: u32) -> Self {
+        Self {
+            min_run_frames,
+            min_bitrate_bps: 0,
+            current_run: None,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Continue or finish the current run with the next audio frame
+    ///
+    /// The `sample_offset` is the number of samples preceding the frame.
+    pub(crate) fn feed_frame(&mut self, sample_offset: u64, frame_header: &FrameHeader) {
+        // Free bitrate frames interrupt any run
+        let Some(bitrate_bps) = frame_header.bitrate_bps else {
+            self.finish_current_run();
+            return;
+        };
+        if self.min_bitrate_bps == 0 || bitrate_bps < self.min_bitrate_bps {
+            self.min_bitrate_bps = bitrate_bps;
+            self.current_run = None;
+            self.runs.clear();
+        } else if bitrate_bps > self.min_bitrate_bps {
+            self.finish_current_run();
+            return;
+        }
+        let run = self.current_run.get_or_insert(MinBitrateRun {
+            sample_offset,
+            sample_count: 0,
+            frame_count: 0,
+        });
+        run.sample_count += u64::from(frame_header.sample_count);
+        run.frame_count += 1;
+    }
+
+    fn finish_current_run(&mut self) {
+        let Some(run) = self.current_run.take() else {
+            return;
+        };
+        if run.frame_count >= self.min_run_frames {
+            self.runs.push((run.sample_offset, run.sample_count));
+        }
+    }
+
+    /// Collect all runs as `(sample_offset, sample_count)`
+    pub(crate) fn finish(mut self) -> Vec<(u64, u64)> {
+        self.finish_current_run();
+        self.runs
+    }
+}
+
+/// Direction of the bitrate between consecutive frames
+///
+/// Counts how often the bitrate changes and how often the direction
+/// of these changes reverses, i.e. from increasing to decreasing or
+/// vice versa. Frames with an unchanged bitrate are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct BitrateTrend {
+    frame_count: u64,
+    last_bitrate_bps: u32,
+    last_increasing: Option<bool>,
+    change_count: u64,
+    reversal_count: u64,
+}
+
+impl BitrateTrend {
+    /// The maximum percentage of reversals among all changes
+    /// that is tolerated for a monotonic bitrate
+    const MAX_REVERSAL_PERCENT: u64 = 5;
+
+    pub(crate) fn feed_frame(&mut self, frame_header: &FrameHeader) {
+        // Free bitrate frames are ignored
+        let Some(bitrate_bps) = frame_header.bitrate_bps else {
+            return;
+        };
+        if self.frame_count > 0 && bitrate_bps != self.last_bitrate_bps {
+            let increasing = bitrate_bps > self.last_bitrate_bps;
+            if self
+                .last_increasing
+                .replace(increasing)
+                .is_some_and(|last_increasing| last_increasing != increasing)
+            {
+                self.reversal_count += 1;
+            }
+            self.change_count += 1;
+        }
+        self.last_bitrate_bps = bitrate_bps;
+        self.frame_count += 1;
+    }
+
+    /// Check if the bitrate is monotonic within the tolerance
+    ///
+    /// Returns `None` if fewer than 2 frames with a known bitrate
+    /// have been traversed.
+    pub(crate) fn finish(self) -> Option<bool> {
+        if self.frame_count < 2 {
+            return None;
+        }
+        Some(self.reversal_count * 100 <= self.change_count * Self::MAX_REVERSAL_PERCENT)
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Header and footer of APE tags
+//!
+//! Both the optional header and the footer of an APE tag are 32 bytes
+//! long and share the same layout. Only this block is decoded, all items
+//! of the tag are skipped.
+//!
+//! <https://wiki.hydrogenaud.io/index.php?title=APE_Tags_Header>
+
+/// Size of the APE tag header or footer in bytes
+pub(crate) const APEV2_HEADER_SIZE: u8 = 32;
+
+const APEV2_PREAMBLE: &[u8; 8] = b"APETAGEX";
+
+const FLAG_HAS_HEADER: u32 = 1 << 31;
+
+const FLAG_IS_HEADER: u32 = 1 << 29;
+
+/// Properties of an APE tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApeV2Info {
+    /// Version number, e.g. 2000 for version 2.0 or 1000 for version 1.0
+    pub version: u32,
+
+    /// Number of items in the tag
+    pub item_count: u32,
+
+    /// Global flags of the tag
+    pub flags: u32,
+
+    /// Size of the tag in bytes
+    ///
+    /// Includes the items and the footer, but not the header.
+    pub size: u32,
+}
+
+impl ApeV2Info {
+    /// Check if the tag contains a header in front of the items
+    #[must_use]
+    pub const fn has_header(&self) -> bool {
+        self.flags & FLAG_HAS_HEADER != 0
+    }
+
+    /// Check if decoded from the header or from the footer of the tag
+    #[must_use]
+    pub const fn is_header(&self) -> bool {
+        self.flags & FLAG_IS_HEADER != 0
+    }
+}
+
+/// Parse the header or footer of an APE tag
+///
+/// Returns `None` if the block doesn't start with the preamble.
+pub(crate) fn parse_apev2_block(block: &[u8; APEV2_HEADER_SIZE as usize]) -> Option<ApeV2Info> {
+    if &block[..APEV2_PREAMBLE.len()] != APEV2_PREAMBLE {
+        return None;
+    }
+    let read_u32 =
+        |offset: usize| u32::from_le_bytes(block[offset..offset + 4].try_into().expect("4 bytes"));
+    Some(ApeV2Info {
+        version: read_u32(8),
+        size: read_u32(12),
+        item_count: read_u32(16),
+        flags: read_u32(20),
+    })
+}
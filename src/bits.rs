@@ -0,0 +1,38 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+/// Read big-endian bit fields from a byte slice
+pub(crate) struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit_offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            bit_offset: 0,
+        }
+    }
+
+    pub(crate) fn read_bits(&mut self, num_bits: u8) -> Option<u32> {
+        debug_assert!(num_bits <= 32);
+        let mut value = 0u32;
+        for _ in 0..num_bits {
+            let byte = self.bytes.get(self.bit_offset / 8)?;
+            let bit = (byte >> (7 - self.bit_offset % 8)) & 0b1;
+            value = (value << 1) | u32::from(bit);
+            self.bit_offset += 1;
+        }
+        Some(value)
+    }
+
+    pub(crate) fn skip_bits(&mut self, num_bits: usize) -> Option<()> {
+        let bit_offset = self.bit_offset + num_bits;
+        if bit_offset > self.bytes.len() * 8 {
+            return None;
+        }
+        self.bit_offset = bit_offset;
+        Some(())
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+/// Warning about a decision of the parser that did not cause an error
+///
+/// Diagnostics never affect the outcome of parsing. They are only
+/// collected if enabled by [`ParseOptions::collect_diagnostics`](crate::ParseOptions::collect_diagnostics).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Diagnostic {
+    /// Unrecognized data after the last MPEG frame has been ignored
+    TrailingDataIgnored {
+        /// Byte offset of the first ignored byte
+        byte_offset: u64,
+
+        /// Number of ignored bytes
+        byte_len: u64,
+    },
+
+    /// Junk has been skipped before finding the sync word of a frame
+    ResyncPerformed {
+        /// Byte offset of the frame
+        byte_offset: u64,
+
+        /// Number of bytes that have been skipped, excluding metadata blocks
+        skipped_byte_count: u64,
+    },
+
+    /// XING header with reserved flags
+    ///
+    /// The header is still used, but the reserved flags might indicate
+    /// that it is corrupt.
+    SuspiciousXingFlags {
+        /// Byte offset of the frame that contains the XING header
+        byte_offset: u64,
+
+        /// All flags of the XING header
+        flags: u32,
+    },
+}
+
+impl Diagnostic {
+    /// Shift the byte offset for the second part of a stream
+    #[must_use]
+    pub(crate) const fn shifted(self, byte_offset: u64) -> Self {
+        match self {
+            Self::TrailingDataIgnored {
+                byte_offset: offset,
+                byte_len,
+            } => Self::TrailingDataIgnored {
+                byte_offset: byte_offset + offset,
+                byte_len,
+            },
+            Self::ResyncPerformed {
+                byte_offset: offset,
+                skipped_byte_count,
+            } => Self::ResyncPerformed {
+                byte_offset: byte_offset + offset,
+                skipped_byte_count,
+            },
+            Self::SuspiciousXingFlags {
+                byte_offset: offset,
+                flags,
+            } => Self::SuspiciousXingFlags {
+                byte_offset: byte_offset + offset,
+                flags,
+            },
+        }
+    }
+}
@@ -1,6 +1,11 @@
 // SPDX-FileCopyrightText: The mpeg-audio-header authors
 // SPDX-License-Identifier: MPL-2.0
 
+use std::{
+    io::{Read, Seek, SeekFrom},
+    time::Duration,
+};
+
 use thiserror::Error;
 
 use crate::ReadPosition;
@@ -14,6 +19,8 @@ pub struct PositionalError {
     pub(crate) source: Error,
 
     pub(crate) position: ReadPosition,
+
+    pub(crate) context: Option<(u64, Vec<u8>)>,
 }
 
 impl PositionalError {
@@ -28,12 +35,78 @@ impl PositionalError {
     pub const fn position(&self) -> &ReadPosition {
         &self.position
     }
+
+    /// The raw bytes around the position of the error
+    ///
+    /// Only captured from seekable sources if enabled by
+    /// [`ParseOptions::error_context_len`](crate::ParseOptions::error_context_len).
+    /// The bytes start at [`Self::context_byte_offset()`] and might be
+    /// fewer than requested near the start or the end of the stream.
+    #[must_use]
+    pub fn context(&self) -> Option<&[u8]> {
+        self.context.as_ref().map(|(_, context)| context.as_slice())
+    }
+
+    /// The byte offset of the first byte of [`Self::context()`]
+    #[must_use]
+    pub fn context_byte_offset(&self) -> Option<u64> {
+        self.context.as_ref().map(|(byte_offset, _)| *byte_offset)
+    }
 }
 
 impl PositionalError {
     pub(crate) fn is_unexpected_eof(&self) -> bool {
         self.source.is_unexpected_eof()
     }
+
+    /// Capture up to `len` bytes before and after the position of the error
+    ///
+    /// The `start_offset` is the position of the source where parsing
+    /// has started. The context remains empty if the bytes could not
+    /// be read.
+    pub(crate) fn capture_context(
+        &mut self,
+        source: &mut (impl Read + Seek),
+        start_offset: u64,
+        len: u16,
+    ) {
+        let context_byte_offset = self.position.byte_offset.saturating_sub(len.into());
+        let max_len = self.position.byte_offset - context_byte_offset + u64::from(len);
+        let mut context = Vec::new();
+        if source
+            .seek(SeekFrom::Start(start_offset + context_byte_offset))
+            .and_then(|_| source.take(max_len).read_to_end(&mut context))
+            .is_ok()
+        {
+            self.context = Some((context_byte_offset, context));
+        }
+    }
+}
+
+/// I/O error that has been tolerated
+///
+/// See also: [`ParseOptions::tolerate_io_errors`](crate::ParseOptions::tolerate_io_errors)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadError {
+    /// Byte offset at which reading failed
+    pub byte_offset: u64,
+
+    /// Kind of the I/O error
+    pub kind: std::io::ErrorKind,
+
+    /// Message of the I/O error
+    pub message: String,
+}
+
+impl ReadError {
+    /// Shift the byte offset for the second part of a stream
+    #[must_use]
+    pub(crate) fn shifted(self, byte_offset: u64) -> Self {
+        Self {
+            byte_offset: byte_offset + self.byte_offset,
+            ..self
+        }
+    }
 }
 
 /// Error type
@@ -46,6 +119,28 @@ pub enum Error {
 
     #[error("frame error: {0}")]
     FrameError(String),
+
+    /// Reading would exceed the maximum number of bytes
+    ///
+    /// See also: [`ParseOptions::max_bytes`](crate::ParseOptions::max_bytes)
+    #[error("limit of {0} bytes exceeded")]
+    LimitExceeded(u64),
+
+    /// The stream is wrapped in an unsupported container format
+    #[error("unsupported format: {0}")]
+    UnsupportedFormat(String),
+
+    /// The duration differs from the expected duration
+    ///
+    /// See also: [`Header::read_and_verify_duration()`](crate::Header::read_and_verify_duration)
+    #[error("duration mismatch: expected {expected:?}, actual {actual:?}")]
+    DurationMismatch {
+        /// Expected duration
+        expected: Duration,
+
+        /// Actual duration
+        actual: Duration,
+    },
 }
 
 impl Error {
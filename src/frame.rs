@@ -3,17 +3,33 @@
 
 use std::{io::Read, time::Duration};
 
-use crate::{reader::Reader, PositionalError, PositionalResult};
+use crate::{
+    apev2::{self, APEV2_HEADER_SIZE},
+    duration_from_samples, id3v2,
+    reader::Reader,
+    ParseOptions, PositionalError, PositionalResult,
+};
 
 pub(crate) const FRAME_HEADER_SIZE: u8 = 4;
 pub(crate) const XING_HEADER_MIN_SIZE: u8 = 8;
-pub(crate) const XING_VBRI_HEADER_MIN_SIZE: u8 = 22; // 4 + 8 + 22 = 30 (= start of TOC entries)
+pub(crate) const XING_VBRI_HEADER_MIN_SIZE: u8 = 18; // 8 + 18 = 26 (= start of TOC entries)
+pub(crate) const XING_FRAMES_FIELD_SIZE: u8 = 4;
+pub(crate) const XING_BYTES_FIELD_SIZE: u8 = 4;
+pub(crate) const VBRI_HEADER_OFFSET: u8 = 32; // after the frame header
+
+// XING header flags
+pub(crate) const XING_FLAG_FRAMES: u32 = 0b0001;
+pub(crate) const XING_FLAG_BYTES: u32 = 0b0010;
+pub(crate) const XING_FLAG_TOC: u32 = 0b0100;
+pub(crate) const XING_FLAG_QUALITY: u32 = 0b1000;
+/// All flags that are defined for XING headers
+pub(crate) const XING_FLAGS_MASK: u32 =
+    XING_FLAG_FRAMES | XING_FLAG_BYTES | XING_FLAG_TOC | XING_FLAG_QUALITY;
 
 // Tag frame/header sizes (including FRAME_HEADER_SIZE)
 const ID3V1_FRAME_SIZE: u8 = 128;
 const ID3V2_HEADER_SIZE: u8 = 10;
 const ID3V2_FOOTER_SIZE: u8 = 10;
-const APEV2_HEADER_SIZE: u8 = 32;
 
 const HEADER_WORD_SYNC_MASK: u32 = 0xFFE0_0000;
 
@@ -21,23 +37,62 @@ fn is_header_word_synced(header_word: u32) -> bool {
     (header_word & HEADER_WORD_SYNC_MASK) == HEADER_WORD_SYNC_MASK
 }
 
-fn maybe_valid_header_word(header_word: u32) -> bool {
+/// Check if a synced header word might be a valid frame header
+///
+/// Frames with a reserved emphasis are only accepted if
+/// `allow_reserved_emphasis` is set.
+fn maybe_valid_header_word(header_word: u32, allow_reserved_emphasis: bool) -> bool {
     if version_from_header_word(header_word).is_none()
         || layer_from_header_word(header_word).is_none()
         || !is_valid_bitrate_bits(bitrate_bits_from_header_word(header_word))
-        || !is_valid_sample_rate_bits(sample_rate_bits_from_header_word(header_word))
+        || sample_rate_hz_from_header_word(header_word).is_none()
     {
         return false;
     }
-    // Emphasis
-    if header_word & 0b11 == 0b10 {
+    if !allow_reserved_emphasis && emphasis_from_header_word(header_word) == Emphasis::Reserved {
+        return false;
+    }
+    if version_from_header_word(header_word) == Some(Version::Mpeg1)
+        && layer_from_header_word(header_word) == Some(Layer::Layer2)
+        && !is_allowed_mpeg1_layer2_bitrate_mode(
+            bitrate_bits_from_header_word(header_word),
+            mode_from_header_word(header_word),
+        )
+    {
         return false;
     }
     true
 }
 
+/// Check if a synced header word might be a valid frame header of the expected profile
+///
+/// See also: [`ParseOptions::expected_profile`]
+fn maybe_expected_header_word(header_word: u32, options: &ParseOptions) -> bool {
+    maybe_valid_header_word(header_word, options.allow_reserved_emphasis)
+        && options
+            .expected_profile
+            .is_none_or(|expected_profile| expected_profile.matches_header_word(header_word))
+}
+
+/// MPEG-1 Layer II only permits certain combinations of bitrate and mode
+///
+/// See ISO/IEC 11172-3, 2.4.2.3: The low bitrates 32, 48, 56, and 80 kbps
+/// are only allowed for single channel frames and the high bitrates 224,
+/// 256, 320, and 384 kbps only for two channel frames. All other bitrates,
+/// including the free format, are allowed for all modes.
+fn is_allowed_mpeg1_layer2_bitrate_mode(bitrate_bits: u8, mode: Mode) -> bool {
+    match bitrate_bits {
+        // 32, 48, 56, and 80 kbps
+        0b0001 | 0b0010 | 0b0011 | 0b0101 => mode == Mode::Mono,
+        // 224, 256, 320, and 384 kbps
+        0b1011..=0b1110 => mode != Mode::Mono,
+        _ => true,
+    }
+}
+
 /// MPEG Version
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Version {
     /// MPEG-1
     Mpeg1 = 0,
@@ -65,6 +120,7 @@ fn version_from_header_word(header_word: u32) -> Option<Version> {
 
 /// MPEG Audio Layer
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Layer {
     /// Layer I
     Layer1 = 0,
@@ -92,6 +148,7 @@ fn layer_from_header_word(header_word: u32) -> Option<Layer> {
 
 /// Channel Mode
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     /// Stereo
     Stereo = 0,
@@ -106,7 +163,38 @@ pub enum Mode {
     Mono = 3,
 }
 
-const fn mode_index(mode: Mode) -> usize {
+/// Emphasis
+///
+/// The emphasis only affects decoding and neither the size
+/// nor the layout of frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Emphasis {
+    /// None
+    None = 0,
+
+    /// 50/15 ms
+    Ms50_15 = 1,
+
+    /// Reserved, only accepted if enabled by
+    /// [`ParseOptions::allow_reserved_emphasis`]
+    Reserved = 2,
+
+    /// CCITT J.17
+    CcittJ17 = 3,
+}
+
+fn emphasis_from_header_word(header_word: u32) -> Emphasis {
+    match header_word & 0b11 {
+        0b00 => Emphasis::None,
+        0b01 => Emphasis::Ms50_15,
+        0b10 => Emphasis::Reserved,
+        0b11 => Emphasis::CcittJ17,
+        _ => unreachable!("exhaustive match on emphasis bits not recognized by compiler"),
+    }
+}
+
+pub(crate) const fn mode_index(mode: Mode) -> usize {
     mode as usize
 }
 
@@ -120,6 +208,44 @@ fn mode_from_header_word(header_word: u32) -> Mode {
     }
 }
 
+/// Semantics of the channels in a frame
+///
+/// Distinguishes dual channel frames that carry two independent
+/// programs from ordinary stereo frames. The channels of those
+/// programs should not be mixed down into a single channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelSemantics {
+    /// A single channel
+    Mono,
+
+    /// Two channels of a single program, either stereo or joint stereo
+    Stereo,
+
+    /// Two independent programs with a single channel each
+    DualProgram,
+}
+
+impl ChannelSemantics {
+    /// Number of encoded channels
+    #[must_use]
+    pub const fn channel_count(self) -> u8 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo | Self::DualProgram => 2,
+        }
+    }
+
+    /// Number of independent programs
+    #[must_use]
+    pub const fn program_count(self) -> u8 {
+        match self {
+            Self::Mono | Self::Stereo => 1,
+            Self::DualProgram => 2,
+        }
+    }
+}
+
 static BIT_RATES_KBPS: [[[u32; 15]; 3]; 3] = [
     [
         [
@@ -201,13 +327,110 @@ fn sample_rate_bits_from_header_word(header_word: u32) -> u8 {
     ((header_word >> 10) & u32::from(SAMPLE_RATE_BITS_MASK)) as u8
 }
 
-fn is_valid_sample_rate_bits(sample_rate_bits: u8) -> bool {
-    sample_rate_bits & SAMPLE_RATE_BITS_MASK < SAMPLE_RATE_BITS_MASK
+/// Look up the sample rate of a header word
+///
+/// Returns `None` for an invalid version, for the reserved sample rate
+/// bits, and for any sample rate of 0 that would result in a division
+/// by zero when calculating the frame size or duration.
+fn sample_rate_hz_from_header_word(header_word: u32) -> Option<u16> {
+    let version = version_from_header_word(header_word)?;
+    SAMPLE_RATES_HZ[version_index(version)]
+        .get(usize::from(sample_rate_bits_from_header_word(header_word)))
+        .copied()
+        .filter(|sample_rate_hz| *sample_rate_hz > 0)
+}
+
+pub(crate) fn min_sample_rate_hz(version: Version) -> u16 {
+    SAMPLE_RATES_HZ[version_index(version)]
+        .into_iter()
+        .min()
+        .expect("sample rates")
 }
 
-fn sample_rate_hz_from_bits(version: Version, sample_rate_bits: u8) -> u16 {
-    debug_assert!(is_valid_sample_rate_bits(sample_rate_bits));
-    SAMPLE_RATES_HZ[version_index(version)][sample_rate_bits as usize]
+/// Sample rate
+///
+/// The standard sample rates of all MPEG versions and any other value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SampleRate {
+    /// 44.1 kHz (MPEG-1)
+    Hz44100,
+
+    /// 48 kHz (MPEG-1)
+    Hz48000,
+
+    /// 32 kHz (MPEG-1)
+    Hz32000,
+
+    /// 22.05 kHz (MPEG-2)
+    Hz22050,
+
+    /// 24 kHz (MPEG-2)
+    Hz24000,
+
+    /// 16 kHz (MPEG-2)
+    Hz16000,
+
+    /// 11.025 kHz (MPEG 2.5)
+    Hz11025,
+
+    /// 12 kHz (MPEG 2.5)
+    Hz12000,
+
+    /// 8 kHz (MPEG 2.5)
+    Hz8000,
+
+    /// Non-standard sample rate in Hz
+    Other(u16),
+}
+
+impl SampleRate {
+    /// Classify a sample rate in Hz
+    #[must_use]
+    pub const fn from_hz(sample_rate_hz: u16) -> Self {
+        match sample_rate_hz {
+            44100 => Self::Hz44100,
+            48000 => Self::Hz48000,
+            32000 => Self::Hz32000,
+            22050 => Self::Hz22050,
+            24000 => Self::Hz24000,
+            16000 => Self::Hz16000,
+            11025 => Self::Hz11025,
+            12000 => Self::Hz12000,
+            8000 => Self::Hz8000,
+            _ => Self::Other(sample_rate_hz),
+        }
+    }
+
+    /// The sample rate in Hz
+    #[must_use]
+    pub const fn hz(self) -> u16 {
+        match self {
+            Self::Hz44100 => 44100,
+            Self::Hz48000 => 48000,
+            Self::Hz32000 => 32000,
+            Self::Hz22050 => 22050,
+            Self::Hz24000 => 24000,
+            Self::Hz16000 => 16000,
+            Self::Hz11025 => 11025,
+            Self::Hz12000 => 12000,
+            Self::Hz8000 => 8000,
+            Self::Other(sample_rate_hz) => sample_rate_hz,
+        }
+    }
+
+    /// The MPEG version that defines this standard sample rate
+    ///
+    /// Returns `None` for non-standard sample rates.
+    #[must_use]
+    pub const fn version(self) -> Option<Version> {
+        match self {
+            Self::Hz44100 | Self::Hz48000 | Self::Hz32000 => Some(Version::Mpeg1),
+            Self::Hz22050 | Self::Hz24000 | Self::Hz16000 => Some(Version::Mpeg2),
+            Self::Hz11025 | Self::Hz12000 | Self::Hz8000 => Some(Version::Mpeg25),
+            Self::Other(_) => None,
+        }
+    }
 }
 
 const SAMPLE_COUNT: [[u16; 3]; 3] = [
@@ -216,10 +439,113 @@ const SAMPLE_COUNT: [[u16; 3]; 3] = [
     [384, 1152, 576],  // Mpeg25
 ];
 
-const fn sample_count(version: Version, layer: Layer) -> u16 {
+/// Number of samples per channel in a single frame
+///
+/// Depends only on the version and layer, see also
+/// [`FrameHeader::sample_count`] and [`Header::samples_per_frame`](crate::Header::samples_per_frame).
+///
+/// # Examples
+///
+/// ```
+/// use mpeg_audio_header::{samples_per_frame, Layer, Version};
+///
+/// assert_eq!(384, samples_per_frame(Version::Mpeg1, Layer::Layer1));
+/// assert_eq!(1152, samples_per_frame(Version::Mpeg1, Layer::Layer3));
+/// assert_eq!(576, samples_per_frame(Version::Mpeg2, Layer::Layer3));
+/// ```
+#[must_use]
+pub const fn samples_per_frame(version: Version, layer: Layer) -> u16 {
     SAMPLE_COUNT[version_index(version)][layer_index(layer)]
 }
 
+fn frame_size(
+    layer: Layer,
+    sample_count: u16,
+    bitrate_bps: u32,
+    sample_rate_hz: u16,
+    padding: bool,
+) -> u16 {
+    if bitrate_bps == 0 {
+        // Free bitrate: The frame size is unknown, even if padded
+        return 0;
+    }
+    let padding = u32::from(padding);
+    let frame_size = if layer == Layer::Layer1 {
+        (12 * bitrate_bps / u32::from(sample_rate_hz) + padding) * 4
+    } else {
+        u32::from(sample_count) * (bitrate_bps / 8) / u32::from(sample_rate_hz) + padding
+    };
+    debug_assert!(frame_size <= u16::MAX.into());
+    frame_size as u16
+}
+
+/// Maximum frame size at the highest bitrate with padding
+pub(crate) fn max_frame_size(version: Version, layer: Layer, sample_rate_hz: u16) -> Option<u16> {
+    max_frame_size_for(version, layer, sample_rate_hz, true)
+}
+
+/// Theoretical maximum frame size in bytes
+///
+/// The size of a frame at the highest valid bitrate for the given
+/// version and layer. Sufficient for sizing buffers from the profile
+/// of the first frame without scanning the stream, e.g. on embedded
+/// devices.
+///
+/// Returns `None` if the sample rate is not valid for the version.
+///
+/// See also: [`Header::recommended_input_buffer_size()`](crate::Header::recommended_input_buffer_size)
+///
+/// # Examples
+///
+/// ```
+/// use mpeg_audio_header::{max_frame_size_for, Layer, Version};
+///
+/// // 320 kbps at 32 kHz
+/// assert_eq!(Some(1441), max_frame_size_for(Version::Mpeg1, Layer::Layer3, 32000, true));
+/// // 32 kHz is not supported by MPEG-2
+/// assert_eq!(None, max_frame_size_for(Version::Mpeg2, Layer::Layer3, 32000, true));
+/// ```
+#[must_use]
+pub fn max_frame_size_for(
+    version: Version,
+    layer: Layer,
+    sample_rate_hz: u16,
+    padding: bool,
+) -> Option<u16> {
+    if !SAMPLE_RATES_HZ[version_index(version)].contains(&sample_rate_hz) {
+        return None;
+    }
+    let max_bitrate_bits = BITRATE_BITS_MASK - 1;
+    let max_bitrate_bps = bitrate_bps_from_bits(version, layer, max_bitrate_bits);
+    Some(frame_size(
+        layer,
+        samples_per_frame(version, layer),
+        max_bitrate_bps,
+        sample_rate_hz,
+        padding,
+    ))
+}
+
+/// Minimum and maximum bitrate in bps, excluding the free format
+pub(crate) fn bitrate_bps_range(version: Version, layer: Layer) -> (u32, u32) {
+    (
+        bitrate_bps_from_bits(version, layer, 1),
+        bitrate_bps_from_bits(version, layer, BITRATE_BITS_MASK - 1),
+    )
+}
+
+/// Maximum number of bytes in the bit reservoir of Layer III streams
+///
+/// Determined by the maximum value of `main_data_begin`, which has 9 bits
+/// for MPEG-1 and 8 bits for MPEG-2/2.5.
+pub(crate) const fn max_bit_reservoir_size(version: Version, layer: Layer) -> u16 {
+    match (version, layer) {
+        (_, Layer::Layer1 | Layer::Layer2) => 0,
+        (Version::Mpeg1, Layer::Layer3) => 511,
+        (Version::Mpeg2 | Version::Mpeg25, Layer::Layer3) => 255,
+    }
+}
+
 const SIDE_INFORMATION_SIZES: [[u16; 4]; 3] = [
     [32, 32, 32, 17], // Mpeg1
     [17, 17, 17, 9],  // Mpeg2
@@ -230,15 +556,118 @@ const fn side_information_size(version: Version, mode: Mode) -> u16 {
     SIDE_INFORMATION_SIZES[version_index(version)][mode_index(mode)]
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct FrameHeader {
-    pub(crate) version: Version,
-    pub(crate) layer: Layer,
-    pub(crate) mode: Mode,
-    pub(crate) sample_count: u16,
-    pub(crate) sample_rate_hz: u16,
-    pub(crate) bitrate_bps: Option<u32>,
-    pub(crate) frame_size: Option<u16>,
+/// Single bit flags of a frame header
+///
+/// The flags of a single frame are `bool`. The aggregated flags of
+/// multiple frames are `Option<bool>` and `None` if inconsistent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameFlags<T = bool> {
+    /// Frame header is followed by a 16-bit CRC
+    pub protection: T,
+
+    /// Frame contains an additional padding slot
+    pub padding: T,
+
+    /// Private bit for application specific use
+    pub private: T,
+
+    /// Content is copyrighted
+    pub copyright: T,
+
+    /// Original media, i.e. not a copy
+    pub original: T,
+}
+
+impl FrameFlags {
+    fn from_header_word(header_word: u32) -> Self {
+        Self {
+            // The protection bit is inverted
+            protection: (header_word >> 16) & 0b1 == 0,
+            padding: (header_word >> 9) & 0b1 != 0,
+            private: (header_word >> 8) & 0b1 != 0,
+            copyright: (header_word >> 3) & 0b1 != 0,
+            original: (header_word >> 2) & 0b1 != 0,
+        }
+    }
+}
+
+/// Decoded MPEG audio frame header
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameHeader {
+    /// MPEG version
+    pub version: Version,
+
+    /// MPEG layer
+    pub layer: Layer,
+
+    /// MPEG mode
+    pub mode: Mode,
+
+    /// Mode extension
+    ///
+    /// Only used for [`Mode::JointStereo`].
+    pub mode_extension: u8,
+
+    /// Single bit flags
+    pub flags: FrameFlags,
+
+    /// Emphasis
+    pub emphasis: Emphasis,
+
+    /// Number of samples per channel in this frame
+    pub sample_count: u16,
+
+    /// Sample rate in Hz
+    pub sample_rate_hz: u16,
+
+    /// Bitrate in bits/sec
+    ///
+    /// `None` for free format frames.
+    pub bitrate_bps: Option<u32>,
+
+    /// Size of the frame in bytes, including the 4-byte frame header
+    ///
+    /// Also includes the optional 16-bit CRC and the padding slot if
+    /// [`FrameFlags::padding`] is set, i.e. 4 bytes for Layer I and 1 byte
+    /// for Layer II/III. The next frame starts exactly this number of bytes
+    /// after the start of this frame. The size of consecutive frames in
+    /// constant bitrate streams only differs by the padding slot.
+    ///
+    /// `None` if unknown, i.e. for free format frames.
+    pub frame_size: Option<u16>,
+}
+
+/// Version, layer, and sample rate of frames
+///
+/// See also: [`ParseOptions::expected_profile`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatProfile {
+    /// MPEG version
+    pub version: Version,
+
+    /// MPEG layer
+    pub layer: Layer,
+
+    /// Sample rate in Hz
+    pub sample_rate_hz: u16,
+}
+
+impl FormatProfile {
+    /// Check if a frame header matches this profile
+    #[must_use]
+    pub fn matches(self, frame_header: &FrameHeader) -> bool {
+        frame_header.version == self.version
+            && frame_header.layer == self.layer
+            && frame_header.sample_rate_hz == self.sample_rate_hz
+    }
+
+    fn matches_header_word(self, header_word: u32) -> bool {
+        version_from_header_word(header_word) == Some(self.version)
+            && layer_from_header_word(header_word) == Some(self.layer)
+            && sample_rate_hz_from_header_word(header_word) == Some(self.sample_rate_hz)
+    }
 }
 
 impl FrameHeader {
@@ -253,151 +682,648 @@ impl FrameHeader {
     }
 }
 
-fn try_read_next_header_word<R: Read>(reader: &mut Reader<'_, R>) -> PositionalResult<Option<u32>> {
-    let mut next_byte_buf = [0u8; 1];
+fn try_read_next_header_word<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+) -> PositionalResult<Option<u32>> {
     let mut initial_byte_offset = reader.position().byte_offset;
     let mut frame_header_word = 0u32;
     loop {
         while !is_header_word_synced(frame_header_word) {
-            if reader.position().byte_offset - initial_byte_offset >= u64::from(FRAME_HEADER_SIZE)
-                && skip_metadata(reader, frame_header_word.to_be_bytes())?
+            if !options.assume_no_metadata
+                && reader.position().byte_offset - initial_byte_offset
+                    >= u64::from(FRAME_HEADER_SIZE)
             {
-                if reader.position().duration == Duration::ZERO {
-                    // Restart the loop after skipping leading metadata frames before the MPEG frames
-                    initial_byte_offset = reader.position().byte_offset;
-                    frame_header_word = 0u32;
-                    continue;
+                let frame_header_bytes = frame_header_word.to_be_bytes();
+                if options.stop_at_metadata && is_metadata_start(frame_header_bytes) {
+                    // Leave the metadata block unconsumed
+                    reader.unread(&frame_header_bytes);
+                    reader.mark_stopped_at_metadata();
+                    return Ok(None);
+                }
+                if skip_metadata(reader, options, frame_header_bytes)? {
+                    if reader.position().duration == Duration::ZERO
+                        || reader.continues_after_metadata()
+                    {
+                        // Restart the loop after skipping leading metadata frames before the MPEG frames
+                        initial_byte_offset = reader.position().byte_offset;
+                        frame_header_word = 0u32;
+                        continue;
+                    }
+                    // Ignore all additional data after the first trailing metadata frame
+                    return Ok(None);
                 }
-                // Ignore all additional data after the first trailing metadata frame
-                return Ok(None);
             }
-            if !reader.try_read_exact_until_eof(&mut next_byte_buf)? {
+            if frame_header_word == 0 {
+                // Skip padding with null bytes, e.g. thousands of bytes
+                // written by broken muxers before the first frame
+                reader.skip_zero_bytes()?;
+            }
+            if !try_shift_next_bytes(reader, options, &mut frame_header_word)? {
                 return Ok(None);
             }
-            frame_header_word = (frame_header_word << 8) | u32::from(next_byte_buf[0]);
         }
 
-        if maybe_valid_header_word(frame_header_word) {
-            break;
+        if maybe_expected_header_word(frame_header_word, options) {
+            if !options.require_next_sync || check_next_sync(reader, options, frame_header_word)? {
+                break;
+            }
+            // Resume the search right after the first byte of the rejected frame header
+            reader.unread(&frame_header_word.to_be_bytes()[1..]);
+            initial_byte_offset = reader.position().byte_offset;
+            frame_header_word = 0u32;
+            continue;
         }
 
         // Start next round
-        if !reader.try_read_exact_until_eof(&mut next_byte_buf)? {
+        if !try_shift_next_bytes(reader, options, &mut frame_header_word)? {
             return Ok(None);
         }
     }
 
     debug_assert!(is_header_word_synced(frame_header_word));
-    debug_assert!(maybe_valid_header_word(frame_header_word));
+    debug_assert!(maybe_expected_header_word(frame_header_word, options));
     Ok(Some(frame_header_word))
 }
 
+/// Number of bytes until the end of the next aligned frame header
+fn sync_step<R: Read>(reader: &Reader<'_, R>, options: &ParseOptions) -> u8 {
+    let Some(sync_alignment) = options.sync_alignment else {
+        return 1;
+    };
+    let alignment = u64::from(sync_alignment.get());
+    let byte_offset = reader.position().byte_offset;
+    let header_size = u64::from(FRAME_HEADER_SIZE);
+    let next_header_end = if byte_offset < header_size {
+        header_size
+    } else {
+        (byte_offset - header_size) / alignment * alignment + alignment + header_size
+    };
+    debug_assert!(next_header_end > byte_offset);
+    debug_assert!(next_header_end - byte_offset <= alignment.max(header_size));
+    (next_header_end - byte_offset) as u8
+}
+
+/// Read the next bytes into the frame header word
+///
+/// Advances to the next aligned frame header. Returns `false` on EOF.
+fn try_shift_next_bytes<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+    frame_header_word: &mut u32,
+) -> PositionalResult<bool> {
+    let mut next_bytes_buf = [0u8; u8::MAX as usize];
+    let next_bytes = &mut next_bytes_buf[..sync_step(reader, options).into()];
+    if !reader.try_read_exact_until_eof(next_bytes)? {
+        return Ok(false);
+    }
+    for next_byte in next_bytes {
+        *frame_header_word = (*frame_header_word << 8) | u32::from(*next_byte);
+    }
+    Ok(true)
+}
+
+fn is_metadata_start(bytes: [u8; FRAME_HEADER_SIZE as usize]) -> bool {
+    matches!(&bytes[..3], b"ID3" | b"TAG") || &bytes == b"APET"
+}
+
+/// Peek behind the end of the frame
+///
+/// The frame is accepted if it is followed by either another sync word,
+/// a metadata block, or EOF. All bytes that have been read ahead are
+/// pushed back into the reader.
+fn check_next_sync<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+    header_word: u32,
+) -> PositionalResult<bool> {
+    let Some(frame_size) = FrameHeader::decode(header_word).frame_size else {
+        // Unable to locate the next frame
+        return Ok(true);
+    };
+    let mut lookahead = vec![0; usize::from(frame_size)];
+    let num_bytes_read = reader.try_read_until_eof(&mut lookahead)?;
+    reader.unread(&lookahead[..num_bytes_read]);
+    if num_bytes_read < lookahead.len() {
+        // EOF
+        return Ok(true);
+    }
+    let next_bytes: [u8; FRAME_HEADER_SIZE as usize] = lookahead[lookahead.len() - 4..]
+        .try_into()
+        .expect("4 bytes");
+    Ok(is_valid_next_start(options, next_bytes))
+}
+
+/// Check if the bytes after a frame start either another frame or a metadata block
+fn is_valid_next_start(
+    options: &ParseOptions,
+    next_bytes: [u8; FRAME_HEADER_SIZE as usize],
+) -> bool {
+    let next_header_word = u32::from_be_bytes(next_bytes);
+    (is_header_word_synced(next_header_word)
+        && maybe_expected_header_word(next_header_word, options))
+        || (!options.assume_no_metadata && is_metadata_start(next_bytes))
+}
+
+/// Peek at the bytes right after the end of a frame
+///
+/// Returns `true` if they start either another frame, a metadata
+/// block, or if EOF has been reached. All bytes that have been
+/// read ahead are pushed back into the reader.
+pub(crate) fn is_aligned_with_next_frame<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+) -> PositionalResult<bool> {
+    let mut next_bytes = [0; FRAME_HEADER_SIZE as usize];
+    let num_bytes_read = reader.try_read_until_eof(&mut next_bytes)?;
+    reader.unread(&next_bytes[..num_bytes_read]);
+    if num_bytes_read < next_bytes.len() {
+        // EOF
+        return Ok(true);
+    }
+    Ok(is_valid_next_start(options, next_bytes))
+}
+
+/// Peek at the header of the frame that starts at the current position
+///
+/// Returns `None` if the next bytes don't start a frame. All bytes
+/// that have been read ahead are pushed back into the reader.
+pub(crate) fn peek_next_frame_header<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+) -> PositionalResult<Option<FrameHeader>> {
+    let mut next_bytes = [0; FRAME_HEADER_SIZE as usize];
+    let num_bytes_read = reader.try_read_until_eof(&mut next_bytes)?;
+    reader.unread(&next_bytes[..num_bytes_read]);
+    if num_bytes_read < next_bytes.len() {
+        // EOF
+        return Ok(None);
+    }
+    let header_word = u32::from_be_bytes(next_bytes);
+    if !is_header_word_synced(header_word) || !maybe_expected_header_word(header_word, options) {
+        return Ok(None);
+    }
+    Ok(Some(FrameHeader::decode(header_word)))
+}
+
+/// Check if a buffer plausibly starts with MPEG audio
+///
+/// Skips a leading ID3 tag and checks if the following bytes
+/// form a valid MPEG frame header. If the buffer is long enough
+/// the header of the next frame is validated as well.
+///
+/// Intended for content type sniffing before parsing the whole stream.
+///
+/// # Examples
+///
+/// ```
+/// use mpeg_audio_header::looks_like_mpeg_audio;
+///
+/// assert!(looks_like_mpeg_audio(&[0xFF, 0xFB, 0x90, 0x00]));
+/// assert!(!looks_like_mpeg_audio(b"RIFF"));
+/// ```
+#[must_use]
+pub fn looks_like_mpeg_audio(prefix: &[u8]) -> bool {
+    let mut offset = 0;
+    if prefix.len() >= usize::from(ID3V2_HEADER_SIZE) && &prefix[..3] == b"ID3" {
+        let flags = prefix[5];
+        let footer_size = if flags & 0b0001_0000 == 0 {
+            0
+        } else {
+            usize::from(ID3V2_FOOTER_SIZE)
+        };
+        // 32/28-bit synchronization safe integer
+        let tag_size = usize::from(prefix[9])
+            | (usize::from(prefix[8]) << 7)
+            | (usize::from(prefix[7]) << 14)
+            | (usize::from(prefix[6]) << 21);
+        offset = usize::from(ID3V2_HEADER_SIZE) + tag_size + footer_size;
+    }
+    let Some(header_word) = header_word_at(prefix, offset) else {
+        return false;
+    };
+    if !is_header_word_synced(header_word) || !maybe_valid_header_word(header_word, false) {
+        return false;
+    }
+    let Some(frame_size) = FrameHeader::decode(header_word).frame_size else {
+        return true;
+    };
+    // Confirm the next frame if available
+    header_word_at(prefix, offset + usize::from(frame_size)).is_none_or(|next_header_word| {
+        is_header_word_synced(next_header_word) && maybe_valid_header_word(next_header_word, false)
+    })
+}
+
+fn header_word_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let header_bytes = bytes.get(offset..offset + usize::from(FRAME_HEADER_SIZE))?;
+    Some(u32::from_be_bytes([
+        header_bytes[0],
+        header_bytes[1],
+        header_bytes[2],
+        header_bytes[3],
+    ]))
+}
+
+/// Count the consecutive frames that follow a frame
+///
+/// Reads ahead at most `max_count` frames, starting with the body
+/// of the frame with the given header word that has already been
+/// consumed. All bytes that have been read ahead are pushed back
+/// into the reader.
+pub(crate) fn count_following_frames<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+    header_word: u32,
+    max_count: u32,
+) -> PositionalResult<u32> {
+    let mut lookahead = Vec::new();
+    let mut frame_header = FrameHeader::decode(header_word);
+    let mut count = 0;
+    while count < max_count {
+        let Some(frame_size) = frame_header.frame_size else {
+            // Unable to locate the next frame
+            break;
+        };
+        // The frame body followed by the next frame header
+        let start = lookahead.len();
+        lookahead.resize(start + usize::from(frame_size), 0);
+        let num_bytes_read = reader.try_read_until_eof(&mut lookahead[start..])?;
+        lookahead.truncate(start + num_bytes_read);
+        if num_bytes_read < usize::from(frame_size) {
+            // EOF
+            break;
+        }
+        let next_header_word = u32::from_be_bytes([
+            lookahead[lookahead.len() - 4],
+            lookahead[lookahead.len() - 3],
+            lookahead[lookahead.len() - 2],
+            lookahead[lookahead.len() - 1],
+        ]);
+        if !is_header_word_synced(next_header_word)
+            || !maybe_expected_header_word(next_header_word, options)
+        {
+            break;
+        }
+        count += 1;
+        frame_header = FrameHeader::decode(next_header_word);
+    }
+    reader.unread(&lookahead);
+    Ok(count)
+}
+
+/// Check if the body of a frame contains a XING/VBRI header
+///
+/// The body starts right after the frame header. XING headers follow
+/// the side information and VBRI headers are located at a fixed offset.
+pub(crate) fn is_vbr_header_frame(frame_header: &FrameHeader, frame_body: &[u8]) -> bool {
+    let xing_offset = usize::from(frame_header.side_information_size());
+    let vbri_offset = usize::from(VBRI_HEADER_OFFSET);
+    matches!(
+        frame_body.get(xing_offset..xing_offset + 4),
+        Some(b"Xing" | b"Info")
+    ) || frame_body.get(vbri_offset..vbri_offset + 4) == Some(b"VBRI")
+}
+
+/// Outcome of a single step while searching for the next frame
+pub(crate) enum SyncStep {
+    /// The header of the next frame and the header word
+    Frame(FrameHeader, u32),
+
+    /// A sync word has been rejected, because it is not followed by
+    /// enough consecutive frames
+    ///
+    /// Searching continues at the next byte.
+    LeadInRejected,
+
+    /// A metadata block has been skipped
+    MetadataSkipped,
+
+    /// No more frames, e.g. at the end of the stream or the audio data
+    Finished,
+}
+
+/// Search for the next frame
+///
+/// Skips metadata blocks and checks the lead-in of the first frame
+/// as requested by [`ParseOptions::min_lead_in_frames`] until it has
+/// been accepted. Fails with an unexpected EOF error if unrecognized
+/// data extends until the end of the stream.
+pub(crate) fn sync_next_frame<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+    lead_in_accepted: bool,
+) -> PositionalResult<SyncStep> {
+    match FrameHeader::try_read(reader, options)? {
+        Ok(Some((frame_header, header_word))) => {
+            if !lead_in_accepted {
+                let min_following_frames = options.min_lead_in_frames.saturating_sub(1);
+                if count_following_frames(reader, options, header_word, min_following_frames)?
+                    < min_following_frames
+                {
+                    reader.unread(&header_word.to_be_bytes()[1..]);
+                    return Ok(SyncStep::LeadInRejected);
+                }
+            }
+            Ok(SyncStep::Frame(frame_header, header_word))
+        }
+        Ok(None) => Ok(SyncStep::Finished),
+        Err((frame_header_bytes, header_err)) => {
+            if skip_metadata(reader, options, frame_header_bytes)? {
+                Ok(SyncStep::MetadataSkipped)
+            } else {
+                Err(header_err)
+            }
+        }
+    }
+}
+
+/// Skip over a metadata block (ID3v1/ID3v2/APEv2)
+///
+/// Returns `true` if the block has been recognized, even if it has
+/// been truncated by the end of the stream. Truncated blocks are
+/// recorded in the reader. Never recognizes any block if
+/// [`ParseOptions::assume_no_metadata`] is set.
 pub(crate) fn skip_metadata<R: Read>(
     reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
     frame_header_bytes: [u8; FRAME_HEADER_SIZE as usize],
 ) -> PositionalResult<bool> {
-    match &frame_header_bytes[..3] {
+    if options.assume_no_metadata {
+        return Ok(false);
+    }
+    let block_offset = reader.position().byte_offset - u64::from(FRAME_HEADER_SIZE);
+    let complete = match &frame_header_bytes[..3] {
         b"ID3" => {
             // ID3v2 frame
             let mut id3v2 = [0; (ID3V2_HEADER_SIZE - FRAME_HEADER_SIZE) as usize];
-            if !reader.try_read_exact_until_eof(&mut id3v2)? {
-                // EOF
-                return Ok(true);
-            }
-            let flags = id3v2[1];
-            let footer_size = if flags & 0b0001_0000 == 0 {
-                0
+            if reader.try_read_exact_until_eof(&mut id3v2)? {
+                let flags = id3v2[1];
+                let footer_size = if flags & 0b0001_0000 == 0 {
+                    0
+                } else {
+                    u32::from(ID3V2_FOOTER_SIZE)
+                };
+                // 32/28-bit synchronization safe integer
+                let tag_size = u32::from(id3v2[5])
+                    | (u32::from(id3v2[4]) << 7)
+                    | (u32::from(id3v2[3]) << 14)
+                    | (u32::from(id3v2[2]) << 21);
+                if id3v2::needs_parsing(options) {
+                    let major_version = frame_header_bytes[3];
+                    let (complete, parsed) =
+                        id3v2::skip_frames(reader, options, major_version, flags, tag_size)?;
+                    if let Some(mllt_seek_points) = parsed.mllt_seek_points {
+                        reader.set_mllt_seek_points(mllt_seek_points);
+                    }
+                    #[cfg(feature = "id3-lite")]
+                    if let Some(basic_tags) = parsed.basic_tags {
+                        reader.set_basic_tags(basic_tags);
+                    }
+                    complete && reader.try_skip_exact_until_eof(footer_size.into())?
+                } else {
+                    reader.try_skip_exact_until_eof((tag_size + footer_size).into())?
+                }
             } else {
-                u32::from(ID3V2_FOOTER_SIZE)
-            };
-            // 32/28-bit synchronization safe integer
-            let tag_size = u32::from(id3v2[5])
-                | (u32::from(id3v2[4]) << 7)
-                | (u32::from(id3v2[3]) << 14)
-                | (u32::from(id3v2[2]) << 21);
-            reader.try_skip_exact_until_eof((tag_size + footer_size).into())?;
-            Ok(true)
+                false
+            }
         }
         b"TAG" => {
             // ID3v1 frame
-            reader.try_skip_exact_until_eof((ID3V1_FRAME_SIZE - FRAME_HEADER_SIZE).into())?;
-            Ok(true)
+            reader.try_skip_exact_until_eof((ID3V1_FRAME_SIZE - FRAME_HEADER_SIZE).into())?
         }
         b"APE" if frame_header_bytes[3] == b'T' => {
-            // APEv2 frame
-            let mut ape_header = [0; (APEV2_HEADER_SIZE - FRAME_HEADER_SIZE) as usize];
-            if !reader.try_read_exact_until_eof(&mut ape_header)? {
-                // EOF
-                return Ok(true);
-            }
-            if &ape_header[..4] == b"AGEX" {
-                let tag_size = u32::from_le_bytes(ape_header[8..12].try_into().expect("4 bytes"));
-                reader.try_skip_exact_until_eof(tag_size.into())?;
+            // APEv2 header or footer
+            let mut ape_block = [0; APEV2_HEADER_SIZE as usize];
+            ape_block[..FRAME_HEADER_SIZE as usize].copy_from_slice(&frame_header_bytes);
+            if reader.try_read_exact_until_eof(&mut ape_block[FRAME_HEADER_SIZE as usize..])? {
+                if let Some(apev2_info) = apev2::parse_apev2_block(&ape_block) {
+                    reader.set_apev2_info(apev2_info);
+                    if apev2_info.is_header() {
+                        // Skip the items and the footer
+                        reader.try_skip_exact_until_eof(apev2_info.size.into())?
+                    } else {
+                        // The footer terminates the tag
+                        true
+                    }
+                } else {
+                    true
+                }
+            } else {
+                false
             }
-            Ok(true)
         }
-        _ => Ok(false),
+        _ => return Ok(false),
+    };
+    if !complete {
+        reader.mark_truncated_metadata(block_offset);
     }
+    reader.add_metadata_bytes(reader.position().byte_offset - block_offset);
+    Ok(true)
 }
 
 pub(crate) type UnrecognizedFrameHeaderError = ([u8; FRAME_HEADER_SIZE as usize], PositionalError);
 
+/// The decoded frame header together with the raw header word
 pub(crate) type TryReadFrameHeaderOutcome =
-    std::result::Result<Option<FrameHeader>, UnrecognizedFrameHeaderError>;
+    std::result::Result<Option<(FrameHeader, u32)>, UnrecognizedFrameHeaderError>;
 
 impl FrameHeader {
-    pub(crate) const fn channel_count(&self) -> u8 {
+    /// Number of channels
+    #[must_use]
+    pub const fn channel_count(&self) -> u8 {
         match self.mode {
             Mode::Stereo | Mode::JointStereo | Mode::DualChannel => 2,
             Mode::Mono => 1,
         }
     }
 
+    /// Effective channels for downmixing
+    ///
+    /// The number of channels is available from [`ChannelSemantics::channel_count()`]
+    /// and equals [`Self::channel_count()`]. Additionally dual channel frames are
+    /// flagged as two independent programs that must not be summed up.
+    #[must_use]
+    pub const fn effective_channels(&self) -> ChannelSemantics {
+        match self.mode {
+            Mode::Stereo | Mode::JointStereo => ChannelSemantics::Stereo,
+            Mode::DualChannel => ChannelSemantics::DualProgram,
+            Mode::Mono => ChannelSemantics::Mono,
+        }
+    }
+
+    /// The playback duration of the samples in this frame
+    ///
+    /// Truncated to whole nanoseconds.
+    #[must_use]
+    pub fn frame_duration(&self) -> Duration {
+        duration_from_samples(self.sample_count.into(), self.sample_rate_hz)
+    }
+
+    /// Encode the 4-byte header word of this frame
+    ///
+    /// The inverse of decoding a frame header, e.g. for rewriting the
+    /// flags of frames. The bitrate must either be one of the bitrates
+    /// of the version and layer or `None` for a free bitrate, and the
+    /// sample rate must be one of the sample rates of the version.
+    /// Otherwise `None` is returned. The derived fields `sample_count`
+    /// and `frame_size` are ignored and the mode extension is truncated
+    /// to 2 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mpeg_audio_header::{Header, ParseMode};
+    ///
+    /// // MPEG-1 Layer III, 128 kbps, 44.1 kHz, Stereo
+    /// let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+    /// frame.resize(417, 0);
+    /// let header = Header::read_from_source(&mut frame.as_slice(), ParseMode::IgnoreVbrHeaders).unwrap();
+    /// let mut frame_header = header.first_frame.unwrap();
+    /// assert_eq!(Some(0xFFFB_9000), frame_header.to_header_word());
+    ///
+    /// frame_header.flags.copyright = true;
+    /// assert_eq!(Some(0xFFFB_9008), frame_header.to_header_word());
+    /// ```
+    #[must_use]
+    pub fn to_header_word(&self) -> Option<u32> {
+        let version_bits = match self.version {
+            Version::Mpeg1 => 0b11,
+            Version::Mpeg2 => 0b10,
+            Version::Mpeg25 => 0b00,
+        };
+        let layer_bits = match self.layer {
+            Layer::Layer1 => 0b11,
+            Layer::Layer2 => 0b10,
+            Layer::Layer3 => 0b01,
+        };
+        let bitrates_kbps = &BIT_RATES_KBPS[version_index(self.version)][layer_index(self.layer)];
+        let bitrate_bits = match self.bitrate_bps {
+            // Free bitrate
+            None => 0,
+            Some(bitrate_bps) => {
+                bitrates_kbps
+                    .iter()
+                    .skip(1)
+                    .position(|bitrate_kbps| 1000 * bitrate_kbps == bitrate_bps)?
+                    + 1
+            }
+        };
+        let sample_rate_bits = SAMPLE_RATES_HZ[version_index(self.version)]
+            .iter()
+            .position(|sample_rate_hz| *sample_rate_hz == self.sample_rate_hz)?;
+        let FrameFlags {
+            protection,
+            padding,
+            private,
+            copyright,
+            original,
+        } = self.flags;
+        Some(
+            HEADER_WORD_SYNC_MASK
+                | (version_bits << 19)
+                | (layer_bits << 17)
+                // The protection bit is inverted
+                | (u32::from(!protection) << 16)
+                | ((bitrate_bits as u32) << 12)
+                | ((sample_rate_bits as u32) << 10)
+                | (u32::from(padding) << 9)
+                | (u32::from(private) << 8)
+                | ((mode_index(self.mode) as u32) << 6)
+                | ((u32::from(self.mode_extension) & 0b11) << 4)
+                | (u32::from(copyright) << 3)
+                | (u32::from(original) << 2)
+                | self.emphasis as u32,
+        )
+    }
+
+    /// Size of the side information of Layer III frames
+    ///
+    /// Layer I/II frames have no side information, but the XING header
+    /// is located at the same offset for all layers.
     pub(crate) fn side_information_size(&self) -> u16 {
         side_information_size(self.version, self.mode)
     }
 
-    #[allow(clippy::panic_in_result_fn)] // version/layer/mode unreachable!()
     pub(crate) fn try_read<R: Read>(
         reader: &mut Reader<'_, R>,
+        options: &ParseOptions,
     ) -> PositionalResult<TryReadFrameHeaderOutcome> {
-        let Some(header_word) = try_read_next_header_word(reader)? else {
+        let Some(header_word) = try_read_next_header_word(reader, options)? else {
             return Ok(Ok(None));
         };
+        Ok(Ok(Some((Self::decode(header_word), header_word))))
+    }
+
+    /// Decode a header word that has already been validated
+    fn decode(header_word: u32) -> Self {
+        debug_assert!(is_header_word_synced(header_word));
+        debug_assert!(maybe_valid_header_word(header_word, true));
 
         let version = version_from_header_word(header_word).expect("valid version");
 
         let sample_rate_hz =
-            sample_rate_hz_from_bits(version, sample_rate_bits_from_header_word(header_word));
-        debug_assert!(sample_rate_hz > 0);
+            sample_rate_hz_from_header_word(header_word).expect("valid sample rate");
 
         let layer = layer_from_header_word(header_word).expect("valid layer");
 
         let bitrate_bps =
             bitrate_bps_from_bits(version, layer, bitrate_bits_from_header_word(header_word));
 
-        let sample_count = sample_count(version, layer);
+        let sample_count = samples_per_frame(version, layer);
 
         let mode = mode_from_header_word(header_word);
 
-        let padding = (header_word >> 9) & 0b1;
+        let mode_extension = ((header_word >> 4) & 0b11) as u8;
 
-        let frame_size = if layer == Layer::Layer1 {
-            (12 * bitrate_bps / u32::from(sample_rate_hz) + padding) * 4
-        } else {
-            u32::from(sample_count) * (bitrate_bps / 8) / u32::from(sample_rate_hz) + padding
-        };
-        debug_assert!(frame_size <= u16::MAX.into());
-        let frame_size = frame_size as u16;
+        let flags = FrameFlags::from_header_word(header_word);
 
-        Ok(Ok(Some(Self {
+        let emphasis = emphasis_from_header_word(header_word);
+
+        let frame_size = frame_size(
+            layer,
+            sample_count,
+            bitrate_bps,
+            sample_rate_hz,
+            flags.padding,
+        );
+
+        Self {
             version,
             layer,
             mode,
+            mode_extension,
+            flags,
+            emphasis,
             sample_rate_hz,
             sample_count,
             bitrate_bps: (bitrate_bps > 0).then_some(bitrate_bps),
             frame_size: (frame_size > 0).then_some(frame_size),
-        })))
+        }
+    }
+}
+
+/// Find all byte offsets that look like a valid frame header
+///
+/// Reads the whole stream byte by byte without skipping neither
+/// frame bodies nor metadata.
+pub(crate) fn scan_candidates<R: Read>(
+    reader: &mut Reader<'_, R>,
+) -> PositionalResult<Vec<(u64, FrameHeader)>> {
+    let mut candidates = Vec::new();
+    let mut next_byte_buf = [0u8; 1];
+    let mut header_word = 0u32;
+    while reader.try_read_exact_until_eof(&mut next_byte_buf)? {
+        header_word = (header_word << 8) | u32::from(next_byte_buf[0]);
+        let byte_offset = reader.position().byte_offset;
+        if byte_offset >= u64::from(FRAME_HEADER_SIZE)
+            && is_header_word_synced(header_word)
+            && maybe_valid_header_word(header_word, false)
+        {
+            candidates.push((
+                byte_offset - u64::from(FRAME_HEADER_SIZE),
+                FrameHeader::decode(header_word),
+            ));
+        }
     }
+    Ok(candidates)
 }
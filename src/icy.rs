@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Interleaved metadata of ICY streams
+//!
+//! Icecast/SHOUTcast servers interleave a metadata block after every
+//! `icy-metaint` bytes of audio data if requested by the client. Each
+//! block starts with a single length byte that needs to be multiplied
+//! by 16, followed by the padded metadata text, e.g.
+//! `StreamTitle='Artist - Title';`.
+//!
+//! <https://cast.readme.io/docs/icy>
+
+use std::io::{self, Read};
+
+const METADATA_LENGTH_MULTIPLIER: usize = 16;
+
+const STREAM_TITLE_PREFIX: &str = "StreamTitle='";
+
+const STREAM_TITLE_SUFFIX: &str = "';";
+
+/// Strips the interleaved metadata blocks from an ICY stream
+///
+/// Only the audio data is passed through and could be parsed with
+/// [`Header::read_from_source()`](crate::Header::read_from_source).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::TcpStream;
+/// use mpeg_audio_header::{Header, IcyReader, ParseMode};
+///
+/// // The response headers have already been consumed and the
+/// // value of the `icy-metaint` header is 16000.
+/// let stream = TcpStream::connect("radio.example.com:8000").unwrap();
+/// let mut source = IcyReader::new(stream, 16000);
+/// let header = Header::read_from_source(&mut source, ParseMode::PreferVbrHeaders).unwrap();
+/// println!("{:?}: {:?}", source.stream_title(), header.total_duration);
+/// ```
+#[derive(Debug)]
+pub struct IcyReader<R> {
+    source: R,
+    metaint: usize,
+    remaining_audio_bytes: usize,
+    stream_title: Option<String>,
+}
+
+impl<R> IcyReader<R> {
+    /// Wrap the audio data of an ICY stream
+    ///
+    /// The `metaint` is the number of audio bytes between two metadata
+    /// blocks as announced by the `icy-metaint` response header. The
+    /// source must start at the first audio byte after the response
+    /// headers. All bytes are passed through if `metaint` is 0.
+    #[must_use]
+    pub fn new(source: R, metaint: u32) -> Self {
+        let metaint = metaint as usize;
+        Self {
+            source,
+            metaint,
+            remaining_audio_bytes: metaint,
+            stream_title: None,
+        }
+    }
+
+    /// The most recent stream title
+    ///
+    /// `None` if no metadata block with a non-empty stream title
+    /// has been read yet.
+    #[must_use]
+    pub fn stream_title(&self) -> Option<&str> {
+        self.stream_title.as_deref()
+    }
+
+    /// Unwrap the underlying source
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+impl<R: Read> IcyReader<R> {
+    /// Read the next metadata block
+    ///
+    /// Returns `false` if the end of the stream has been reached.
+    fn read_metadata_block(&mut self) -> io::Result<bool> {
+        let mut length = [0u8; 1];
+        if !read_exact_until_eof(&mut self.source, &mut length)? {
+            return Ok(false);
+        }
+        let mut metadata = vec![0; usize::from(length[0]) * METADATA_LENGTH_MULTIPLIER];
+        if !read_exact_until_eof(&mut self.source, &mut metadata)? {
+            return Ok(false);
+        }
+        if let Some(stream_title) = parse_stream_title(&metadata) {
+            self.stream_title = (!stream_title.is_empty()).then_some(stream_title);
+        }
+        self.remaining_audio_bytes = self.metaint;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for IcyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.metaint == 0 {
+            return self.source.read(buf);
+        }
+        if self.remaining_audio_bytes == 0 && !self.read_metadata_block()? {
+            return Ok(0);
+        }
+        let max_len = buf.len().min(self.remaining_audio_bytes);
+        let num_bytes_read = self.source.read(&mut buf[..max_len])?;
+        self.remaining_audio_bytes -= num_bytes_read;
+        Ok(num_bytes_read)
+    }
+}
+
+/// Fill the whole buffer
+///
+/// Returns `false` if the end of the stream has been reached before.
+fn read_exact_until_eof(source: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match source.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Extract the stream title from the text of a metadata block
+fn parse_stream_title(metadata: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(metadata);
+    let text = text.trim_end_matches('\0');
+    let start = text.find(STREAM_TITLE_PREFIX)? + STREAM_TITLE_PREFIX.len();
+    let text = &text[start..];
+    // The title itself might contain single quotes
+    let end = text
+        .find(STREAM_TITLE_SUFFIX)
+        .or_else(|| text.strip_suffix('\'').map(str::len))?;
+    Some(text[..end].to_owned())
+}
@@ -0,0 +1,288 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Frames of ID3 version 2 tags
+//!
+//! Only the MPEG location lookup table (MLLT) and, if the `id3-lite`
+//! feature is enabled, the title and artist text frames are parsed.
+//! All other frames are skipped.
+
+use std::{io::Read, time::Duration};
+
+use crate::{bits::BitReader, reader::Reader, ParseOptions, PositionalResult};
+
+const FLAG_UNSYNCHRONISATION: u8 = 0b1000_0000;
+const FLAG_EXTENDED_HEADER: u8 = 0b0100_0000;
+
+/// Maximum size of a frame that is buffered for parsing
+///
+/// The declared size is untrusted. Bigger frames are skipped.
+const MAX_PARSED_FRAME_SIZE: u32 = 1 << 20;
+
+/// Decode a 28-bit synchronization safe integer
+fn syncsafe_u32(bytes: [u8; 4]) -> u32 {
+    u32::from(bytes[3])
+        | (u32::from(bytes[2]) << 7)
+        | (u32::from(bytes[1]) << 14)
+        | (u32::from(bytes[0]) << 21)
+}
+
+/// Seek points of the MPEG location lookup table
+///
+/// Each point is given as `(duration, byte_offset)` relative to
+/// the first MPEG frame after the tag.
+pub(crate) type MlltSeekPoints = Vec<(Duration, u64)>;
+
+/// Title and artist of an ID3 tag
+#[cfg(feature = "id3-lite")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BasicTags {
+    /// Content of the title frame (TIT2)
+    pub title: Option<String>,
+
+    /// Content of the lead artist frame (TPE1)
+    pub artist: Option<String>,
+}
+
+/// Contents of the frames that have been parsed while skipping a tag
+#[derive(Debug, Default)]
+pub(crate) struct ParsedFrames {
+    pub(crate) mllt_seek_points: Option<MlltSeekPoints>,
+    #[cfg(feature = "id3-lite")]
+    pub(crate) basic_tags: Option<BasicTags>,
+}
+
+impl ParsedFrames {
+    fn parse_frame(&mut self, frame_kind: FrameKind, frame_body: &[u8]) {
+        match frame_kind {
+            FrameKind::Mllt => {
+                self.mllt_seek_points = parse_mllt(frame_body);
+            }
+            #[cfg(feature = "id3-lite")]
+            FrameKind::Title => {
+                self.basic_tags.get_or_insert_with(Default::default).title =
+                    decode_text(frame_body);
+            }
+            #[cfg(feature = "id3-lite")]
+            FrameKind::Artist => {
+                self.basic_tags.get_or_insert_with(Default::default).artist =
+                    decode_text(frame_body);
+            }
+        }
+    }
+}
+
+/// Kinds of frames that are parsed instead of skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameKind {
+    Mllt,
+    #[cfg(feature = "id3-lite")]
+    Title,
+    #[cfg(feature = "id3-lite")]
+    Artist,
+}
+
+impl FrameKind {
+    fn from_id(id: &[u8]) -> Option<Self> {
+        match id {
+            b"MLL" | b"MLLT" => Some(Self::Mllt),
+            #[cfg(feature = "id3-lite")]
+            b"TT2" | b"TIT2" => Some(Self::Title),
+            #[cfg(feature = "id3-lite")]
+            b"TP1" | b"TPE1" => Some(Self::Artist),
+            _ => None,
+        }
+    }
+}
+
+/// Check if the frames of a tag need to be traversed instead of
+/// skipping the whole tag at once
+pub(crate) const fn needs_parsing(options: &ParseOptions) -> bool {
+    options.parse_mllt || cfg!(feature = "id3-lite")
+}
+
+/// Skip over the frames of an ID3 version 2 tag while parsing the selected frames
+///
+/// The tag header has already been consumed. Skips exactly `tag_size`
+/// bytes or less if the end of the stream has been reached.
+///
+/// The MLLT frame is only parsed if enabled by [`ParseOptions::parse_mllt`].
+/// The title and artist frames are only parsed from a leading tag that
+/// precedes all audio frames. Only the parsed frames are buffered and
+/// only up to a maximum size. All other frames are skipped.
+///
+/// Returns `true` if all bytes have been skipped together with the
+/// contents of the parsed frames.
+pub(crate) fn skip_frames<R: Read>(
+    reader: &mut Reader<'_, R>,
+    options: &ParseOptions,
+    major_version: u8,
+    flags: u8,
+    tag_size: u32,
+) -> PositionalResult<(bool, ParsedFrames)> {
+    let mut parsed = ParsedFrames::default();
+    if !(2..=4).contains(&major_version) || flags & FLAG_UNSYNCHRONISATION != 0 {
+        // Unable to parse the frames
+        return Ok((reader.try_skip_exact_until_eof(tag_size.into())?, parsed));
+    }
+    let mut remaining_size = tag_size;
+    if major_version >= 3 && flags & FLAG_EXTENDED_HEADER != 0 {
+        let mut size_bytes = [0; 4];
+        if remaining_size < 4 || !reader.try_read_exact_until_eof(&mut size_bytes)? {
+            return Ok((false, parsed));
+        }
+        remaining_size -= 4;
+        let skip_size = if major_version == 3 {
+            // Excluding the size field
+            u32::from_be_bytes(size_bytes)
+        } else {
+            // Including the size field
+            syncsafe_u32(size_bytes).saturating_sub(4)
+        }
+        .min(remaining_size);
+        if !reader.try_skip_exact_until_eof(skip_size.into())? {
+            return Ok((false, parsed));
+        }
+        remaining_size -= skip_size;
+    }
+    #[cfg(feature = "id3-lite")]
+    let parse_basic_tags = reader.position().duration.is_zero();
+    let frame_header_size = if major_version == 2 { 6 } else { 10 };
+    while remaining_size >= frame_header_size {
+        let mut frame_header = [0; 10];
+        let frame_header = &mut frame_header[..frame_header_size as usize];
+        if !reader.try_read_exact_until_eof(frame_header)? {
+            return Ok((false, parsed));
+        }
+        remaining_size -= frame_header_size;
+        if frame_header[0] == 0 {
+            // Padding
+            break;
+        }
+        let (frame_kind, frame_size, is_encoded) = match major_version {
+            2 => (
+                FrameKind::from_id(&frame_header[..3]),
+                u32::from_be_bytes([0, frame_header[3], frame_header[4], frame_header[5]]),
+                false,
+            ),
+            3 => (
+                FrameKind::from_id(&frame_header[..4]),
+                u32::from_be_bytes([
+                    frame_header[4],
+                    frame_header[5],
+                    frame_header[6],
+                    frame_header[7],
+                ]),
+                // Compression or encryption
+                frame_header[9] & 0b1100_0000 != 0,
+            ),
+            _ => (
+                FrameKind::from_id(&frame_header[..4]),
+                syncsafe_u32([
+                    frame_header[4],
+                    frame_header[5],
+                    frame_header[6],
+                    frame_header[7],
+                ]),
+                // Compression, encryption, unsynchronisation, or data length indicator
+                frame_header[9] & 0b0000_1111 != 0,
+            ),
+        };
+        if frame_size > remaining_size {
+            // Corrupt frame
+            break;
+        }
+        let frame_kind = frame_kind.filter(|frame_kind| {
+            !is_encoded
+                && frame_size <= MAX_PARSED_FRAME_SIZE
+                && match frame_kind {
+                    FrameKind::Mllt => options.parse_mllt && parsed.mllt_seek_points.is_none(),
+                    #[cfg(feature = "id3-lite")]
+                    FrameKind::Title | FrameKind::Artist => parse_basic_tags,
+                }
+        });
+        if let Some(frame_kind) = frame_kind {
+            let mut frame_body = vec![0; frame_size as usize];
+            if !reader.try_read_exact_until_eof(&mut frame_body)? {
+                return Ok((false, parsed));
+            }
+            parsed.parse_frame(frame_kind, &frame_body);
+        } else if !reader.try_skip_exact_until_eof(frame_size.into())? {
+            return Ok((false, parsed));
+        }
+        remaining_size -= frame_size;
+    }
+    // Skip padding or any remaining unparsed bytes
+    let complete = reader.try_skip_exact_until_eof(remaining_size.into())?;
+    Ok((complete, parsed))
+}
+
+/// Decode the first value of a text frame
+///
+/// The body starts with the text encoding byte: ISO-8859-1 (0),
+/// UTF-16 with BOM (1), UTF-16BE without BOM (2), or UTF-8 (3).
+#[cfg(feature = "id3-lite")]
+fn decode_text(frame_body: &[u8]) -> Option<String> {
+    let (&encoding, text) = frame_body.split_first()?;
+    let text = match encoding {
+        0 => text.iter().map(|&byte| char::from(byte)).collect(),
+        1 | 2 => {
+            let (big_endian, text) = match text {
+                [0xFE, 0xFF, text @ ..] => (true, text),
+                [0xFF, 0xFE, text @ ..] => (false, text),
+                // UTF-16 without BOM
+                _ => (encoding == 2, text),
+            };
+            let code_units = text.chunks_exact(2).map(|bytes| {
+                if big_endian {
+                    u16::from_be_bytes([bytes[0], bytes[1]])
+                } else {
+                    u16::from_le_bytes([bytes[0], bytes[1]])
+                }
+            });
+            char::decode_utf16(code_units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect()
+        }
+        3 => String::from_utf8_lossy(text).into_owned(),
+        _ => return None,
+    };
+    // Multiple values are separated by a null character
+    let text = text.split('\0').next().unwrap_or_default();
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+/// Parse the references of an MLLT frame
+fn parse_mllt(frame_body: &[u8]) -> Option<MlltSeekPoints> {
+    if frame_body.len() < 10 {
+        return None;
+    }
+    let bytes_between_reference =
+        u32::from_be_bytes([0, frame_body[2], frame_body[3], frame_body[4]]);
+    let millis_between_reference =
+        u32::from_be_bytes([0, frame_body[5], frame_body[6], frame_body[7]]);
+    let bits_for_bytes_deviation = frame_body[8];
+    let bits_for_millis_deviation = frame_body[9];
+    if bits_for_bytes_deviation > 32 || bits_for_millis_deviation > 32 {
+        return None;
+    }
+    let references = &frame_body[10..];
+    let bits_per_reference =
+        usize::from(bits_for_bytes_deviation) + usize::from(bits_for_millis_deviation);
+    let reference_count = (references.len() * 8)
+        .checked_div(bits_per_reference)
+        .unwrap_or_default();
+    let mut bits = BitReader::new(references);
+    let mut byte_offset = 0u64;
+    let mut millis = 0u64;
+    let mut seek_points = Vec::with_capacity(reference_count);
+    for _ in 0..reference_count {
+        let bytes_deviation = bits.read_bits(bits_for_bytes_deviation)?;
+        let millis_deviation = bits.read_bits(bits_for_millis_deviation)?;
+        byte_offset += u64::from(bytes_between_reference) + u64::from(bytes_deviation);
+        millis += u64::from(millis_between_reference) + u64::from(millis_deviation);
+        seek_points.push((Duration::from_millis(millis), byte_offset));
+    }
+    Some(seek_points)
+}
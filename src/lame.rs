@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! LAME extension of the XING header
+//!
+//! The LAME tag directly follows the fields of the XING header.
+//! Only the encoder version, the preset, and the encoder delay and
+//! padding are decoded.
+//!
+//! <http://gabriel.mp3-tech.org/mp3infotag.html>
+
+/// Size of the LAME tag in bytes
+pub(crate) const LAME_TAG_SIZE: u8 = 36;
+
+const ENCODER_VERSION_SIZE: usize = 9;
+
+/// Properties of the LAME tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LameInfo {
+    /// Short encoder version string, e.g. "LAME3.100"
+    pub encoder_version: String,
+
+    /// Number of samples that have been added by the encoder
+    /// at the start of the stream
+    pub encoder_delay: u16,
+
+    /// Number of samples that have been added by the encoder
+    /// at the end of the stream
+    pub encoder_padding: u16,
+
+    /// Preset that has been selected for encoding
+    ///
+    /// Best effort, only written by LAME 3.90 and later.
+    pub preset: Option<LamePreset>,
+}
+
+/// Encoding preset of LAME
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LamePreset {
+    /// Average bitrate in kbps, e.g. `--preset 128`
+    Abr(u16),
+
+    /// VBR quality level from 0 (best) to 9, e.g. `-V2`
+    Vbr(u8),
+
+    /// `--r3mix`
+    R3mix,
+
+    /// `--preset standard`
+    Standard,
+
+    /// `--preset extreme`
+    Extreme,
+
+    /// `--preset insane`, i.e. 320 kbps CBR
+    Insane,
+
+    /// `--preset fast standard`
+    StandardFast,
+
+    /// `--preset fast extreme`
+    ExtremeFast,
+
+    /// `--preset medium`
+    Medium,
+
+    /// `--preset fast medium`
+    MediumFast,
+
+    /// Unknown preset value
+    Unknown(u16),
+}
+
+impl LamePreset {
+    /// Decode the 11-bit preset value
+    ///
+    /// Returns `None` if no preset has been used.
+    const fn from_value(value: u16) -> Option<Self> {
+        let preset = match value {
+            0 => return None,
+            8..=320 => Self::Abr(value),
+            // V9 = 410, ..., V0 = 500
+            410..=500 if value.is_multiple_of(10) => Self::Vbr(((500 - value) / 10) as u8),
+            1000 => Self::R3mix,
+            1001 => Self::Standard,
+            1002 => Self::Extreme,
+            1003 => Self::Insane,
+            1004 => Self::StandardFast,
+            1005 => Self::ExtremeFast,
+            1006 => Self::Medium,
+            1007 => Self::MediumFast,
+            _ => Self::Unknown(value),
+        };
+        Some(preset)
+    }
+}
+
+/// Parse the LAME tag
+///
+/// Returns `None` if the bytes do not start with the version string
+/// of a known encoder that writes LAME tags.
+pub(crate) fn parse_lame_tag(tag: &[u8; LAME_TAG_SIZE as usize]) -> Option<LameInfo> {
+    let encoder_version = &tag[..ENCODER_VERSION_SIZE];
+    // FFmpeg writes LAME tags with its own version string
+    if !matches!(&encoder_version[..4], b"LAME" | b"Lavf" | b"Lavc") {
+        return None;
+    }
+    let encoder_version = String::from_utf8_lossy(encoder_version)
+        .trim_end_matches(['\0', ' '])
+        .to_owned();
+    // 12 bits for the delay followed by 12 bits for the padding
+    let encoder_delay = (u16::from(tag[21]) << 4) | (u16::from(tag[22]) >> 4);
+    let encoder_padding = (u16::from(tag[22] & 0x0F) << 8) | u16::from(tag[23]);
+    // 2 unused bits, 3 bits for the surround info, and 11 bits for the preset
+    let preset = LamePreset::from_value(u16::from_be_bytes([tag[26], tag[27]]) & 0x07FF);
+    Some(LameInfo {
+        encoder_version,
+        encoder_delay,
+        encoder_padding,
+        preset,
+    })
+}
@@ -0,0 +1,199 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! MPEG-2 BC multichannel extension in MPEG-1 Layer II frames
+//!
+//! The multichannel extension (ISO/IEC 13818-3) is stored in the
+//! ancillary data of an MPEG-1 compatible Layer II frame. The ancillary
+//! data starts right after the audio data, which requires decoding the
+//! bit allocation, scale factor selection information, and scale factors
+//! of all subbands.
+
+use crate::{
+    bits::BitReader,
+    frame::{FrameHeader, Layer, Mode, Version},
+};
+
+// Bit allocation codes:
+// 0: no samples
+// 3..=16: number of bits per sample
+// 17/18/19: 3/5/9 quantization levels with 3 samples grouped into 5/7/10 bits
+static BIT_ALLOCATION_CODES: [u8; 60] = [
+    0, 17, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, // offset 0
+    0, 17, 18, 3, 19, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 16, // offset 16
+    0, 17, 18, 3, 19, 4, 5, 16, // offset 32
+    0, 17, 18, 16, // offset 40
+    0, 17, 18, 19, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, // offset 44
+];
+
+/// Consecutive subbands sharing the same bit allocation codes
+struct SubbandAllocation {
+    code_offset: usize,
+    code_bits: u8,
+    subband_count: usize,
+}
+
+const fn subband_allocation(
+    code_offset: usize,
+    code_bits: u8,
+    subband_count: usize,
+) -> SubbandAllocation {
+    SubbandAllocation {
+        code_offset,
+        code_bits,
+        subband_count,
+    }
+}
+
+// ISO/IEC 11172-3 Table B.2a/b (27/30 subbands)
+static SUBBAND_ALLOCATION: [SubbandAllocation; 4] = [
+    subband_allocation(0, 4, 3),
+    subband_allocation(16, 4, 8),
+    subband_allocation(32, 3, 12),
+    subband_allocation(40, 2, 7),
+];
+
+// ISO/IEC 11172-3 Table B.2c/d (8/12 subbands)
+static SUBBAND_ALLOCATION_LOW_RATE: [SubbandAllocation; 2] =
+    [subband_allocation(44, 4, 2), subband_allocation(44, 3, 10)];
+
+const MAX_SUBBAND_COUNT: usize = 30;
+
+// Samples per subband are organized in 12 granules of 3 samples
+const GRANULE_COUNT: usize = 12;
+
+fn subband_allocation_table(frame_header: &FrameHeader) -> (&'static [SubbandAllocation], usize) {
+    let channel_count = u32::from(frame_header.channel_count());
+    // Free format frames are treated like high bitrate frames
+    let kbps_per_channel = frame_header
+        .bitrate_bps
+        .map_or(192, |bitrate_bps| bitrate_bps / 1000 / channel_count);
+    if kbps_per_channel < 56 {
+        let subband_count = if frame_header.sample_rate_hz == 32000 {
+            12
+        } else {
+            8
+        };
+        (&SUBBAND_ALLOCATION_LOW_RATE, subband_count)
+    } else if kbps_per_channel >= 96 && frame_header.sample_rate_hz != 48000 {
+        (&SUBBAND_ALLOCATION, 30)
+    } else {
+        (&SUBBAND_ALLOCATION, 27)
+    }
+}
+
+const fn sample_bits_per_granule(code: u8) -> usize {
+    match code {
+        0 => 0,
+        17 => 5,
+        18 => 7,
+        19 => 10,
+        bits => 3 * bits as usize,
+    }
+}
+
+/// Skip the audio data of a frame
+///
+/// The `bits` must start right after the frame header.
+fn skip_audio_data(frame_header: &FrameHeader, bits: &mut BitReader<'_>) -> Option<()> {
+    if frame_header.flags.protection {
+        bits.skip_bits(16)?;
+    }
+
+    let (allocation_table, subband_count) = subband_allocation_table(frame_header);
+    let channel_count = usize::from(frame_header.channel_count());
+    let stereo_bound = match frame_header.mode {
+        Mode::Mono => 0,
+        Mode::JointStereo => (usize::from(frame_header.mode_extension) + 1) * 4,
+        Mode::Stereo | Mode::DualChannel => subband_count,
+    }
+    .min(subband_count);
+
+    // Bit allocation
+    let mut allocation = [[0u8; 2]; MAX_SUBBAND_COUNT];
+    let mut subband = 0;
+    'allocation: for subband_allocation in allocation_table {
+        for _ in 0..subband_allocation.subband_count {
+            if subband >= subband_count {
+                break 'allocation;
+            }
+            let codes = &BIT_ALLOCATION_CODES[subband_allocation.code_offset..];
+            for channel in 0..channel_count {
+                if channel > 0 && subband >= stereo_bound {
+                    // Shared by both channels
+                    allocation[subband][channel] = allocation[subband][0];
+                } else {
+                    let index = bits.read_bits(subband_allocation.code_bits)?;
+                    allocation[subband][channel] = codes[index as usize];
+                }
+            }
+            subband += 1;
+        }
+    }
+
+    // Scale factor selection information
+    let mut scale_factor_count = 0;
+    for subband_allocation in &allocation[..subband_count] {
+        for code in &subband_allocation[..channel_count] {
+            if *code != 0 {
+                scale_factor_count += match bits.read_bits(2)? {
+                    0 => 3,
+                    2 => 1,
+                    _ => 2,
+                };
+            }
+        }
+    }
+
+    // Scale factors
+    bits.skip_bits(scale_factor_count * 6)?;
+
+    // Samples
+    let mut sample_bits = 0;
+    for (subband, subband_allocation) in allocation[..subband_count].iter().enumerate() {
+        for (channel, code) in subband_allocation[..channel_count].iter().enumerate() {
+            if channel > 0 && subband >= stereo_bound {
+                // Samples are shared by both channels
+                continue;
+            }
+            sample_bits += sample_bits_per_granule(*code);
+        }
+    }
+    bits.skip_bits(GRANULE_COUNT * sample_bits)
+}
+
+/// Number of channels declared by the multichannel extension
+///
+/// Returns `None` if the frame is not an MPEG-1 Layer II frame or if
+/// it does not contain enough ancillary data for a multichannel header.
+///
+/// The multichannel header has no sync word. The result is a guess
+/// that should be confirmed by consistent results from many frames.
+pub(crate) fn mc_extension_channel_count(
+    frame_header: &FrameHeader,
+    frame_body: &[u8],
+) -> Option<u8> {
+    if frame_header.version != Version::Mpeg1 || frame_header.layer != Layer::Layer2 {
+        return None;
+    }
+    let mut bits = BitReader::new(frame_body);
+    skip_audio_data(frame_header, &mut bits)?;
+
+    // Multichannel header
+    let ext_bit_stream_present = bits.read_bits(1)?;
+    if ext_bit_stream_present != 0 {
+        // n_ad_bytes
+        bits.skip_bits(8)?;
+    }
+    let center = bits.read_bits(2)?;
+    let surround = bits.read_bits(2)?;
+    let lfe = bits.read_bits(1)?;
+    let center_channel_count = u8::from(center != 0);
+    let surround_channel_count = match surround {
+        0 => 0,
+        1 => 1,
+        // Either stereo surround or a second stereo program
+        _ => 2,
+    };
+    Some(2 + center_channel_count + surround_channel_count + lfe as u8)
+}
@@ -0,0 +1,26 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Side information of Layer III frames
+//!
+//! Only the back pointer into the bit reservoir is decoded.
+
+use crate::frame::{FrameHeader, Version};
+
+/// Decode `main_data_begin` from the side information
+///
+/// The side information directly follows the frame header or the
+/// optional 16-bit CRC. The pointer is given as a negative offset in
+/// bytes from the frame header into the bit reservoir of preceding
+/// frames. Returns `None` if the side information is too short.
+pub(crate) fn main_data_begin(frame_header: &FrameHeader, side_information: &[u8]) -> Option<u16> {
+    let offset = if frame_header.flags.protection { 2 } else { 0 };
+    let bytes = side_information.get(offset..offset + 2)?;
+    let main_data_begin = match frame_header.version {
+        // 9 bits
+        Version::Mpeg1 => (u16::from(bytes[0]) << 1) | (u16::from(bytes[1]) >> 7),
+        // 8 bits
+        Version::Mpeg2 | Version::Mpeg25 => u16::from(bytes[0]),
+    };
+    Some(main_data_begin)
+}
@@ -20,34 +20,81 @@
 
 use std::{
     fs::File,
-    io::{BufReader, Read},
+    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+    num::NonZeroU8,
+    ops::Range,
     path::Path,
     time::Duration,
 };
 
+mod aggregation;
+mod apev2;
+mod bits;
+mod diagnostic;
 mod error;
 mod frame;
+mod icy;
+mod id3v2;
+mod lame;
+mod layer2;
+mod layer3;
+mod properties;
 mod reader;
+mod riff;
+mod scanner;
+mod trailing;
+mod vbri;
 
-pub use self::frame::{Layer, Mode, Version};
+pub use self::aggregation::{AggregationState, ProfileSummary};
 
-use self::frame::{FrameHeader, XING_HEADER_MIN_SIZE, XING_VBRI_HEADER_MIN_SIZE};
+pub use self::apev2::ApeV2Info;
 
-use self::reader::Reader;
+pub use self::diagnostic::Diagnostic;
+
+use self::aggregation::{aggregate_min, BitrateTrend, MinBitrateRuns};
+
+pub use self::frame::{
+    looks_like_mpeg_audio, max_frame_size_for, samples_per_frame, ChannelSemantics, Emphasis,
+    FormatProfile, FrameFlags, FrameHeader, Layer, Mode, SampleRate, Version,
+};
+
+#[cfg(feature = "id3-lite")]
+pub use self::id3v2::BasicTags;
+
+pub use self::icy::IcyReader;
+
+pub use self::lame::{LameInfo, LamePreset};
+
+pub use self::properties::AudioProperties;
+
+pub use self::scanner::{MpegSyncScanner, RangeScanResult};
+
+pub use self::trailing::{MetadataKind, MetadataRegion};
+
+pub use self::vbri::VbriToc;
+
+use self::frame::{
+    SyncStep, VBRI_HEADER_OFFSET, XING_BYTES_FIELD_SIZE, XING_FLAGS_MASK, XING_FLAG_BYTES,
+    XING_FLAG_FRAMES, XING_FLAG_QUALITY, XING_FLAG_TOC, XING_FRAMES_FIELD_SIZE,
+    XING_HEADER_MIN_SIZE, XING_VBRI_HEADER_MIN_SIZE,
+};
+
+use self::reader::{Reader, SwappedBytes};
 
 pub use self::{
-    error::{Error, PositionalError},
+    error::{Error, PositionalError, ReadError},
     reader::ReadPosition,
 };
 
 /// Result type for [`PositionalError`]
 pub type PositionalResult<T> = std::result::Result<T, PositionalError>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// Properties of an MPEG audio stream
 ///
 /// A virtual MPEG audio header, built from both the XING header and
 /// optionally aggregated from all valid MPEG frame headers.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Header {
     /// Source of the metadata in this header
     pub source: HeaderSource,
@@ -67,12 +114,82 @@ pub struct Header {
     /// The common MPEG mode in all frames or `None` if either unknown or inconsistent.
     pub mode: Option<Mode>,
 
+    /// Channel semantics
+    ///
+    /// The common channel semantics in all frames or `None` if either unknown or inconsistent.
+    pub channel_semantics: Option<ChannelSemantics>,
+
+    /// Emphasis
+    ///
+    /// The common emphasis in all frames or `None` if either unknown,
+    /// inconsistent, or reserved.
+    ///
+    /// See also: [`ParseOptions::allow_reserved_emphasis`]
+    pub emphasis: Option<Emphasis>,
+
+    /// Flags that are common to all frames
+    ///
+    /// Each flag is `None` if either unknown or inconsistent.
+    pub common_flags: FrameFlags<Option<bool>>,
+
     /// Minimum number of channels
+    ///
+    /// Channels of the MPEG-2 BC multichannel extension are not included.
     pub min_channel_count: u8,
 
     /// Maximum number of channels
+    ///
+    /// Channels of the MPEG-2 BC multichannel extension are not included.
     pub max_channel_count: u8,
 
+    /// Number of independent programs
+    ///
+    /// 2 if all frames are dual channel, 1 otherwise, or 0 if no
+    /// MPEG frames have been found.
+    ///
+    /// See also: [`ChannelSemantics::program_count()`]
+    pub program_count: u8,
+
+    /// Number of audio frames per MPEG mode
+    ///
+    /// Indexed by `Mode as usize`. Only aggregated from MPEG frame headers.
+    pub mode_frame_counts: [u64; 4],
+
+    /// Number of audio frames with a single channel
+    ///
+    /// Only aggregated from MPEG frame headers.
+    pub mono_frame_count: u64,
+
+    /// Number of audio frames with two channels
+    ///
+    /// Includes all stereo, joint stereo, and dual channel frames.
+    /// Only aggregated from MPEG frame headers.
+    pub stereo_frame_count: u64,
+
+    /// Number of audio frames with the padding bit set
+    ///
+    /// Encoders pad frames to keep the average bitrate exact when the
+    /// frame size is not an integer number of bytes. Only aggregated
+    /// from MPEG frame headers.
+    pub padding_frame_count: u64,
+
+    /// All MPEG-1 Layer II frames might carry an MPEG-2 BC multichannel extension
+    ///
+    /// Only detected if enabled by [`ParseOptions::detect_multichannel`].
+    /// The multichannel header in the ancillary data has no sync word and
+    /// the actual number of channels could not be verified without decoding
+    /// the extension. Therefore this flag is only set if all frames
+    /// consistently declare additional channels.
+    pub possible_multichannel: bool,
+
+    /// Number of channels declared by the MPEG-2 BC multichannel extension
+    ///
+    /// Only available if [`Self::possible_multichannel`] is set, i.e. if
+    /// all frames consistently declare the same number of channels. This
+    /// number includes the two channels of the MPEG-1 base stream and
+    /// the LFE channel, e.g. 6 for a 5.1 configuration.
+    pub multichannel_count: Option<u8>,
+
     /// Minimum sample rate in Hz
     pub min_sample_rate_hz: u16,
 
@@ -82,14 +199,309 @@ pub struct Header {
     /// Total number of samples per channel
     pub total_sample_count: u64,
 
+    /// Number of samples per channel in each frame
+    ///
+    /// Either 384, 576, or 1152 depending on the version and layer.
+    /// `None` if either is unknown or inconsistent.
+    pub samples_per_frame: Option<u16>,
+
     /// Total duration
+    ///
+    /// Reduced by [`Self::vbri_delay`] if taken from a VBRI header. The
+    /// encoder delay and padding of a LAME tag are not subtracted, see
+    /// [`Self::start_skip_samples`] and [`Self::end_skip_samples`] for
+    /// calculating the gapless duration.
     pub total_duration: Duration,
 
+    /// Duration declared by the VBR header
+    ///
+    /// Only available in [`ParseMode::Reconcile`], see also
+    /// [`Self::declared_duration()`].
+    pub reconciled_declared_duration: Option<Duration>,
+
+    /// Duration measured by scanning all MPEG frames
+    ///
+    /// Only available in [`ParseMode::Reconcile`].
+    pub measured_duration: Option<Duration>,
+
+    /// Number of MPEG frames counted by scanning all MPEG frames
+    ///
+    /// Only available in [`ParseMode::Reconcile`], see also
+    /// [`Self::declared_frame_count`].
+    pub measured_frame_count: Option<u64>,
+
+    /// Number of samples per channel counted by scanning all MPEG frames
+    ///
+    /// Only available in [`ParseMode::Reconcile`].
+    pub measured_sample_count: Option<u64>,
+
     /// Average sample rate in Hz
     pub avg_sample_rate_hz: Option<u16>,
 
+    /// Maximum size of all MPEG frames in bytes
+    ///
+    /// `None` if not aggregated from MPEG frame headers or if the frame
+    /// sizes are unknown, i.e. for free format streams.
+    pub max_frame_size: Option<u16>,
+
     /// Average bitrate in bits/sec
     pub avg_bitrate_bps: Option<u32>,
+
+    /// Average bitrate in kbit/sec
+    ///
+    /// Rounded to the nearest integer value if aggregated from MPEG frame headers.
+    pub avg_bitrate_kbps: Option<u16>,
+
+    /// Coefficient of variation of the bitrate
+    ///
+    /// The standard deviation of the bitrate divided by the average
+    /// bitrate, both weighted by the number of samples per frame. Exactly
+    /// 0 for CBR streams, small for ABR streams, and larger for VBR
+    /// streams. Only aggregated from MPEG frame headers with a known
+    /// bitrate.
+    pub bitrate_cv: Option<f64>,
+
+    /// Whether any Layer III frame references the bit reservoir
+    ///
+    /// `true` if `main_data_begin` in the side information of at least
+    /// one frame is nonzero, i.e. if frames are not independently
+    /// decodable. Only detected in Layer III frames if enabled by
+    /// [`ParseOptions::detect_bit_reservoir`].
+    pub uses_bit_reservoir: Option<bool>,
+
+    /// Whether the bitrate is monotonic
+    ///
+    /// `true` if the bitrate of consecutive frames is either non-decreasing
+    /// or non-increasing, with at most 5% of all bitrate changes reversing
+    /// the direction, e.g. for encoders that ramp the bitrate over the
+    /// stream. `false` if the bitrate oscillates like in true VBR streams.
+    /// A constant bitrate is monotonic. Only detected if enabled by
+    /// [`ParseOptions::detect_bitrate_monotonic`] and if at least 2 frames
+    /// with a known bitrate have been aggregated.
+    pub bitrate_monotonic: Option<bool>,
+
+    /// Byte offset of a truncated metadata block
+    ///
+    /// Set if an ID3 or APE tag has unexpectedly been cut off by the
+    /// end of the stream, e.g. for partially downloaded files. Truncated
+    /// audio data is not reported here.
+    pub truncated_metadata: Option<u64>,
+
+    /// Byte offset of the last MPEG frame if it has been truncated
+    ///
+    /// The frame has been truncated either by the end of the stream
+    /// or by the end of the audio data in a RIFF/WAVE or AIFF-C container.
+    /// Truncated frames are not included in the aggregated properties,
+    /// i.e. neither in the frame and sample counts nor in the total
+    /// duration.
+    pub truncated_frame: Option<u64>,
+
+    /// Number of frames that are not followed by the next frame
+    ///
+    /// Counts all frames with a known size where the bytes right after
+    /// the end of the frame neither start another frame, nor a metadata
+    /// block, nor the end of the stream. A nonzero count indicates that
+    /// the frame sizes don't match the actual data, i.e. either corrupt
+    /// or junk data between the frames.
+    pub alignment_errors: u64,
+
+    /// Number of bytes that have been skipped as junk
+    ///
+    /// Counts all bytes that are neither part of an MPEG frame nor of
+    /// a metadata block, e.g. garbage between the frames or null bytes
+    /// before the first frame. The header chunks of RIFF/WAVE or AIFF-C
+    /// containers are not counted.
+    pub junk_byte_count: u64,
+
+    /// Parsing stopped before the end of the stream
+    ///
+    /// The aggregated properties, including the total duration, only
+    /// cover the frames that have been parsed. Set if stopped early by
+    /// [`ParseOptions::identify_only`], [`ParseOptions::detect_vbr_header_only`],
+    /// or [`ParseOptions::stop_at_metadata`], or if reading failed with
+    /// a tolerated [`Self::read_error`].
+    pub partial: bool,
+
+    /// Byte offset of the metadata block at which parsing stopped
+    ///
+    /// Only set if enabled by [`ParseOptions::stop_at_metadata`]. The
+    /// metadata block has not been consumed, i.e. the final position
+    /// equals this offset. The header is also marked as [`Self::partial`].
+    pub stopped_at: Option<u64>,
+
+    /// I/O error that ended parsing prematurely
+    ///
+    /// Only set if enabled by [`ParseOptions::tolerate_io_errors`]. The
+    /// header is also marked as [`Self::partial`].
+    pub read_error: Option<ReadError>,
+
+    /// Position after parsing has finished
+    ///
+    /// The position right after the VBR header frame if the metadata has
+    /// been read from a VBR header. Otherwise the position where reading
+    /// the MPEG frames has stopped, usually at the end of the stream.
+    pub final_position: ReadPosition,
+
+    /// Total size of the stream in bytes
+    ///
+    /// Only known when reading from a file or a slice, including all
+    /// metadata. `None` for generic sources.
+    pub stream_byte_len: Option<u64>,
+
+    /// Raw bytes of the XING/VBRI header frame
+    ///
+    /// Starts with the MPEG frame header and ends after the TOC or
+    /// after the LAME tag if present. Only
+    /// captured if enabled by [`ParseOptions::capture_vbr_header`].
+    pub raw_vbr_header: Option<Vec<u8>>,
+
+    /// Number of frames declared by the XING/VBRI header
+    ///
+    /// Retained independent of the [`ParseMode`] for cross-checking
+    /// the declared against the aggregated properties.
+    pub declared_frame_count: Option<u32>,
+
+    /// Whether the VBR header declares a variable bitrate
+    ///
+    /// `true` for XING headers starting with "Xing" and for VBRI headers,
+    /// `false` for XING headers starting with "Info" that are written for
+    /// CBR streams. Retained independent of the [`ParseMode`].
+    ///
+    /// See also: [`Self::is_vbr()`]
+    pub declared_vbr: Option<bool>,
+
+    /// Table of contents of the VBRI header
+    ///
+    /// Retained independent of the [`ParseMode`] for seeking.
+    pub vbri_toc: Option<VbriToc>,
+
+    /// Delay declared by the VBRI header in samples
+    ///
+    /// Subtracted from [`Self::total_sample_count`] and [`Self::total_duration`]
+    /// if the properties are taken from the VBRI header, i.e. they match the
+    /// audible length of the stream. The raw totals are still available
+    /// from [`Self::declared_frame_count`] and [`Self::declared_duration()`].
+    pub vbri_delay: Option<u16>,
+
+    /// The size and frame count of the VBR header are implausible
+    ///
+    /// Only validated if enabled by [`ParseOptions::vbr_header_tolerance_percent`].
+    /// The properties are aggregated from all MPEG frames instead, even
+    /// in [`ParseMode::PreferVbrHeaders`].
+    pub vbr_header_suspect: bool,
+
+    /// The VBR header declares a constant bitrate that is contradicted by the MPEG frames
+    ///
+    /// Set if an "Info" XING header, see [`Self::declared_vbr`], is followed
+    /// by frames with varying bitrates. Such mislabeled streams might confuse
+    /// decoders that rely on a constant bitrate, e.g. for seeking. Only
+    /// available in [`ParseMode::Reconcile`].
+    pub cbr_claim_violated: bool,
+
+    /// Number of samples per channel of the XING/VBRI header frame
+    ///
+    /// The header frame is a valid MPEG frame that decodes to silence
+    /// when played back. Its samples are neither included in
+    /// [`Self::total_sample_count`] nor in [`Self::total_duration`], but
+    /// might be added for mapping byte offsets to playback times. `None`
+    /// if there is no VBR header.
+    pub vbr_frame_samples: Option<u16>,
+
+    /// Properties of the LAME tag that follows the XING header
+    pub lame_info: Option<LameInfo>,
+
+    /// Number of samples to skip at the start for gapless playback
+    ///
+    /// The encoder delay from the LAME tag. The additional delay
+    /// of the decoder is not included.
+    pub start_skip_samples: Option<u32>,
+
+    /// Number of samples to skip at the end for gapless playback
+    ///
+    /// The encoder padding from the LAME tag.
+    pub end_skip_samples: Option<u32>,
+
+    /// Runs of consecutive frames at the minimum bitrate
+    ///
+    /// Each run is given as `(sample_offset, sample_count)`. Frames at
+    /// the minimum bitrate likely contain silence, e.g. gaps between
+    /// chapters. Constant bitrate streams form a single run.
+    ///
+    /// Only collected from MPEG frame headers if enabled by
+    /// [`ParseOptions::min_bitrate_run_frames`].
+    pub min_bitrate_runs: Vec<(u64, u64)>,
+
+    /// Number of consecutive frames starting with the first MPEG frame
+    ///
+    /// Counts all frames up to the first gap, i.e. before resyncing.
+    /// Only determined from MPEG frame headers, otherwise 0.
+    ///
+    /// See also: [`ParseOptions::min_lead_in_frames`]
+    pub lead_in_confidence: u32,
+
+    /// Byte offset of the first MPEG frame
+    ///
+    /// Either a XING/VBRI header frame or the first audio frame. All
+    /// preceding bytes, e.g. metadata or a fragment of a frame at the
+    /// start of a capture, have been skipped.
+    ///
+    /// See also: [`ParseOptions::min_lead_in_frames`]
+    pub first_frame_offset: Option<u64>,
+
+    /// Byte offset right after the last audio frame
+    ///
+    /// Either the end of the last MPEG frame that has been aggregated or
+    /// the end of the audio data as declared by the number of bytes in
+    /// the VBR header. `None` if unknown.
+    ///
+    /// See also: [`Self::trailing_bytes()`]
+    pub audio_end_offset: Option<u64>,
+
+    /// All distinct profiles in order of their first occurrence
+    ///
+    /// Reveals the formats of spliced streams that are only hinted
+    /// at by the minimum and maximum values.
+    pub profiles: Vec<ProfileSummary>,
+
+    /// Seek points from the MPEG location lookup table (MLLT)
+    ///
+    /// Each point is given as `(duration, byte_offset)` relative to
+    /// the first MPEG frame after the ID3 tag. Only parsed if enabled
+    /// by [`ParseOptions::parse_mllt`].
+    pub mllt_seek_points: Option<Vec<(Duration, u64)>>,
+
+    /// Header or footer of the first APE tag
+    ///
+    /// Decoded from the footer if the tag has no header.
+    pub apev2_info: Option<ApeV2Info>,
+
+    /// Metadata blocks at the end of the stream
+    ///
+    /// ID3 v1, Lyrics3, and APE tags, ordered by their byte offset.
+    /// Only detected when reading from a file, a slice, or a seekable
+    /// source by seeking backwards from the end of the stream. Empty
+    /// for generic sources.
+    pub trailing_tags: Vec<MetadataRegion>,
+
+    /// Warnings about decisions of the parser that did not cause an error
+    ///
+    /// Only collected if enabled by [`ParseOptions::collect_diagnostics`].
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Title and artist of the leading ID3 tag
+    ///
+    /// Only available with the `id3-lite` feature.
+    #[cfg(feature = "id3-lite")]
+    pub basic_tags: Option<BasicTags>,
+
+    /// The first audio frame
+    ///
+    /// Identifies the stream precisely, even if the properties of
+    /// subsequent frames vary. Only available if aggregated from
+    /// MPEG frame headers.
+    ///
+    /// See also: [`FrameHeader::frame_size`] for the exact size in bytes
+    pub first_frame: Option<FrameHeader>,
 }
 
 /// Parse mode
@@ -117,6 +529,254 @@ pub enum ParseMode {
     /// on how and when the redundant information in the VBR headers has been
     /// calculated.
     IgnoreVbrHeaders,
+
+    /// Compare the VBR headers with the MPEG audio frames
+    ///
+    /// Aggregates the metadata from all MPEG audio frames like
+    /// [`ParseMode::IgnoreVbrHeaders`] and additionally reports both
+    /// the declared and the measured duration in [`Header::reconciled_declared_duration`]
+    /// and [`Header::measured_duration`] for revealing any discrepancies.
+    /// The measured number of frames and samples are reported in
+    /// [`Header::measured_frame_count`] and [`Header::measured_sample_count`].
+    ///
+    /// This method is as slow as [`ParseMode::IgnoreVbrHeaders`].
+    Reconcile,
+}
+
+/// Byte order of the stream
+///
+/// MPEG audio streams are stored in big-endian byte order on the wire.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Regular, unmodified byte order
+    #[default]
+    Normal,
+
+    /// The bytes of each 16-bit word are swapped
+    ///
+    /// Repairs streams that have been corrupted by broken transfer tools,
+    /// e.g. the sync word appears as `0xE0FF` instead of `0xFFE0`.
+    /// The bytes are swapped back before parsing. Byte offsets refer
+    /// to the position in the original stream.
+    Swapped,
+}
+
+/// Parse options
+///
+/// Customize the behavior of the parser. The defaults are used by
+/// [`Header::read_from_source()`].
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ParseOptions {
+    /// Byte order of the stream
+    pub byte_order: ByteOrder,
+
+    /// Validate the alignment of consecutive frames
+    ///
+    /// Only accept a frame if the bytes following it form either the
+    /// sync word of the next frame, the start of a metadata block, or
+    /// if the end of the stream is reached. Otherwise the frame is
+    /// rejected and the search for the next sync word continues at
+    /// the next byte.
+    ///
+    /// This reduces false positives when resyncing in files with
+    /// embedded binary data at the cost of reading ahead each frame.
+    /// Disabled by default.
+    pub require_next_sync: bool,
+
+    /// Detect the MPEG-2 BC multichannel extension in Layer II frames
+    ///
+    /// Requires reading and partially decoding the bodies of all
+    /// MPEG-1 Layer II frames. Disabled by default.
+    ///
+    /// See also: [`Header::possible_multichannel`]
+    pub detect_multichannel: bool,
+
+    /// Capture the raw bytes of the XING/VBRI header
+    ///
+    /// Disabled by default to avoid allocations.
+    ///
+    /// See also: [`Header::raw_vbr_header`]
+    pub capture_vbr_header: bool,
+
+    /// Collect runs of at least this many consecutive frames at the
+    /// minimum bitrate of the stream
+    ///
+    /// Those runs are likely silence. Disabled by default.
+    ///
+    /// See also: [`Header::min_bitrate_runs`]
+    pub min_bitrate_run_frames: Option<u32>,
+
+    /// Maximum number of bytes to read from the source
+    ///
+    /// Includes all skipped bytes, e.g. of oversized metadata blocks.
+    /// Parsing fails with [`Error::LimitExceeded`] instead of reading
+    /// beyond this limit. Streams that end exactly at the limit are
+    /// accepted. Unlimited by default.
+    pub max_bytes: Option<u64>,
+
+    /// Minimum number of consecutive frames for accepting the start
+    /// of the stream
+    ///
+    /// Reads ahead the given number of frames, including the first
+    /// frame, before accepting the first sync word. Otherwise the
+    /// search for the first frame continues at the next byte. This
+    /// reduces the chance of locking onto a spurious sync word, e.g.
+    /// inside image data of metadata blocks or within the fragment of
+    /// a frame at the start of a stream that has been captured mid-stream.
+    /// Disabled by default.
+    ///
+    /// See also: [`Header::lead_in_confidence`], [`Header::first_frame_offset`]
+    pub min_lead_in_frames: u32,
+
+    /// Parse the MPEG location lookup table (MLLT) of ID3 tags
+    ///
+    /// Requires to traverse all frames of ID3 tags instead of
+    /// skipping the whole tag at once. Disabled by default.
+    ///
+    /// See also: [`Header::mllt_seek_points`]
+    pub parse_mllt: bool,
+
+    /// Stop after the first frame, either a XING/VBRI or an audio frame
+    ///
+    /// A cheap probe for the presence of a VBR header. The values of
+    /// the VBR header are not trusted in [`ParseMode::IgnoreVbrHeaders`],
+    /// but [`Header::source`] still reports which kind of VBR header
+    /// has been found. Only the first audio frame is aggregated if the
+    /// stream starts without a VBR header. Disabled by default.
+    pub detect_vbr_header_only: bool,
+
+    /// Accept frames with a reserved emphasis
+    ///
+    /// Some broken encoders write the reserved emphasis value. Those
+    /// frames are rejected by default, resulting in a search for the
+    /// next sync word. If accepted, the reserved emphasis is reported
+    /// as inconsistent and all other properties are decoded as usual.
+    /// Disabled by default.
+    ///
+    /// See also: [`Header::emphasis`]
+    pub allow_reserved_emphasis: bool,
+
+    /// Stop after identifying the format of the stream
+    ///
+    /// Only aggregates the first [`Self::identify_frame_count`] audio
+    /// frames. The version, layer, mode, and sample rate are only
+    /// reported if consistent within those frames. Faster than a full
+    /// scan and more reliable than probing a single frame for detecting
+    /// streams with mixed formats. The returned header is marked as
+    /// [`Header::partial`] and [`Header::total_duration`] only sums up
+    /// the duration of the identified frames. Disabled by default.
+    pub identify_only: bool,
+
+    /// Stop at the first ID3 or APE tag
+    ///
+    /// Stops parsing at the start of the first metadata block, independent
+    /// of its position, e.g. for metadata blocks between the audio frames
+    /// that separate chapters. The block is not consumed and its offset is
+    /// reported in [`Header::stopped_at`]. Disabled by default.
+    pub stop_at_metadata: bool,
+
+    /// Treat the stream as raw MPEG audio without any metadata
+    ///
+    /// Disables the detection of ID3/APE tags, e.g. for elementary streams
+    /// that never contain any metadata. All bytes are considered as potential
+    /// MPEG frames and audio data that happens to start with "ID3", "TAG",
+    /// or "APET" is not mistaken for a metadata block. Overrides
+    /// [`Self::stop_at_metadata`]. Disabled by default.
+    pub assume_no_metadata: bool,
+
+    /// Number of audio frames for [`Self::identify_only`]
+    ///
+    /// Defaults to [`ParseOptions::DEFAULT_IDENTIFY_FRAME_COUNT`] if 0.
+    pub identify_frame_count: u32,
+
+    /// Detect if Layer III frames use the bit reservoir
+    ///
+    /// Requires reading the side information of all Layer III frames
+    /// instead of skipping it. Has no effect on other layers. Disabled
+    /// by default.
+    ///
+    /// See also: [`Header::uses_bit_reservoir`]
+    pub detect_bit_reservoir: bool,
+
+    /// Detect if the bitrate of consecutive frames is monotonic
+    ///
+    /// Disabled by default.
+    ///
+    /// See also: [`Header::bitrate_monotonic`]
+    pub detect_bitrate_monotonic: bool,
+
+    /// Alignment of frames in bytes
+    ///
+    /// Only sync words at byte offsets that are a multiple of this value,
+    /// relative to the start of the stream, are considered, e.g. 2 or 4
+    /// for streams with frames that are known to be aligned to 16 or 32
+    /// bits. The search for the next sync word advances by this step
+    /// instead of byte by byte, which avoids false positives and speeds
+    /// up resyncing. Metadata blocks are then only recognized at aligned
+    /// byte offsets, too. Byte granular if `None`.
+    pub sync_alignment: Option<NonZeroU8>,
+
+    /// Only accept frames of a known profile
+    ///
+    /// If the format is already known, e.g. from a container, all sync
+    /// words of frames with a different version, layer, or sample rate
+    /// are rejected like junk. This avoids false positives when resyncing
+    /// in damaged streams. Disabled by default.
+    pub expected_profile: Option<FormatProfile>,
+
+    /// Validate the size and frame count declared by the VBR header
+    ///
+    /// The average bitrate derived from the declared number of bytes
+    /// and frames must not exceed the range of valid bitrates for the
+    /// version and layer by more than the given tolerance in percent.
+    /// The declared number of frames must not deviate from the number
+    /// of bytes divided by the size of the first audio frame by more
+    /// than the given tolerance in percent. The bitrate of VBR streams
+    /// varies and requires a bigger tolerance than CBR streams.
+    ///
+    /// Otherwise the VBR header is considered as corrupt or stale and
+    /// not trusted. Only VBR headers that declare both the number of
+    /// bytes and frames are validated. Disabled by default.
+    ///
+    /// See also: [`Header::vbr_header_suspect`]
+    pub vbr_header_tolerance_percent: Option<u8>,
+
+    /// Number of bytes before and after the position of an error to capture
+    ///
+    /// Only supported by [`Header::read_from_seekable_source_with_options()`],
+    /// see [`PositionalError::context()`]. Disabled if 0 (default).
+    pub error_context_len: u16,
+
+    /// Collect warnings about ignored data and suspicious headers
+    ///
+    /// Disabled by default.
+    ///
+    /// See also: [`Header::diagnostics`]
+    pub collect_diagnostics: bool,
+
+    /// Finish parsing instead of failing on I/O errors after the first audio frame
+    ///
+    /// Like an unexpected end of the stream, all other I/O errors, e.g. of an
+    /// unreliable network connection, are tolerated after at least one audio
+    /// frame has been parsed. The header then only covers the frames that have
+    /// been read until the error occurred, which is reported in
+    /// [`Header::read_error`]. The limit [`Self::max_bytes`] is still enforced.
+    /// Disabled by default.
+    pub tolerate_io_errors: bool,
+}
+
+impl ParseOptions {
+    /// Default number of audio frames for [`Self::identify_only`]
+    pub const DEFAULT_IDENTIFY_FRAME_COUNT: u32 = 10;
+
+    const fn effective_identify_frame_count(&self) -> u32 {
+        if self.identify_frame_count == 0 {
+            Self::DEFAULT_IDENTIFY_FRAME_COUNT
+        } else {
+            self.identify_frame_count
+        }
+    }
 }
 
 /// Source of the parsed metadata
@@ -132,8 +792,80 @@ pub enum HeaderSource {
     MpegFrameHeaders,
 }
 
+/// Length of a stream in samples together with its duration
+///
+/// See also: [`Header::length()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamLength {
+    /// Number of samples per channel
+    pub samples: u64,
+
+    /// Playback duration
+    pub duration: Duration,
+
+    /// Sample rate in Hz that relates the samples to the duration
+    ///
+    /// `None` if the sample rate is unknown or varies, i.e. if the
+    /// duration has been accumulated from frames with different
+    /// sample rates.
+    pub sample_rate_basis: Option<u16>,
+}
+
+/// How the exact duration of a stream could be determined
+///
+/// See also: [`Header::duration_certainty()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DurationCertainty {
+    /// The duration has been measured by scanning all MPEG frames
+    ///
+    /// No more bytes need to be read.
+    ExactFromFrameScan,
+
+    /// The duration is declared by the XING/VBRI header
+    ///
+    /// Reading the first frame is sufficient, see [`Header::declared_duration()`].
+    ExactFromVbrHeader,
+
+    /// The duration could be calculated from the size of the audio data
+    ///
+    /// The stream has a constant bitrate, see [`Header::cbr_duration_from_bytes()`].
+    EstimatedFromBitrate,
+
+    /// The whole stream needs to be scanned until the end
+    RequiresFullScan,
+}
+
 const NANOS_PER_SECOND: u32 = 1_000_000_000;
 
+/// Duration of the given number of samples at a sample rate
+///
+/// Truncated to whole nanoseconds.
+///
+/// # Panics
+///
+/// Panics if `sample_rate_hz` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use mpeg_audio_header::duration_from_samples;
+///
+/// assert_eq!(Duration::from_millis(24), duration_from_samples(1152, 48000));
+/// ```
+#[must_use]
+pub fn duration_from_samples(sample_count: u64, sample_rate_hz: u16) -> Duration {
+    let sample_rate_hz = u64::from(sample_rate_hz);
+    let seconds = sample_count / sample_rate_hz;
+    // Calculated from the remainder to prevent overflows
+    let nanoseconds =
+        (sample_count % sample_rate_hz) * u64::from(NANOS_PER_SECOND) / sample_rate_hz;
+    debug_assert!(nanoseconds < NANOS_PER_SECOND.into());
+    Duration::new(seconds, nanoseconds as u32)
+}
+
 impl Header {
     /// Read from a `source` that implements `Read`
     ///
@@ -153,57 +885,515 @@ impl Header {
     /// let header = Header::read_from_source(&mut source, ParseMode::IgnoreVbrHeaders).unwrap();
     /// println!("MPEG audio header: {:?}", header);
     /// ```
-    #[allow(clippy::too_many_lines)]
+    ///
+    /// The `source` is never required to implement `Seek`. Skipped bytes,
+    /// e.g. of metadata blocks, are consumed and discarded. This allows
+    /// to parse streams that are decompressed on the fly:
+    ///
+    /// ```
+    /// use std::io::Write as _;
+    /// use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    /// use mpeg_audio_header::{Header, ParseMode};
+    ///
+    /// // An ID3v2 tag with 1000 bytes of content followed by
+    /// // an MPEG-1 Layer III frame (128 kbps, 44.1 kHz)
+    /// let mut data = b"ID3\x04\x00\x00\x00\x00\x07\x68".to_vec();
+    /// data.resize(data.len() + 1000, 0);
+    /// let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+    /// frame.resize(417, 0);
+    /// data.extend_from_slice(&frame);
+    ///
+    /// let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    /// encoder.write_all(&data).unwrap();
+    /// let compressed = encoder.finish().unwrap();
+    ///
+    /// let mut source = GzDecoder::new(compressed.as_slice());
+    /// let header = Header::read_from_source(&mut source, ParseMode::IgnoreVbrHeaders).unwrap();
+    /// assert_eq!(1152, header.total_sample_count);
+    /// assert_eq!(data.len() as u64, header.final_position.byte_offset());
+    /// ```
+    ///
+    /// A single read is never expected to fill the whole buffer. Streams
+    /// that are split into multiple buffers could be parsed by chaining
+    /// them without copying, even if frames or metadata blocks cross the
+    /// boundary:
+    ///
+    /// ```
+    /// use std::io::Read as _;
+    /// use mpeg_audio_header::{Header, ParseMode};
+    ///
+    /// // Two halves of an MPEG-1 Layer III frame (128 kbps, 44.1 kHz)
+    /// let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+    /// frame.resize(417, 0);
+    /// let (buffer_a, buffer_b) = frame.split_at(200);
+    ///
+    /// let mut source = buffer_a.chain(buffer_b);
+    /// let header = Header::read_from_source(&mut source, ParseMode::IgnoreVbrHeaders).unwrap();
+    /// assert_eq!(1152, header.total_sample_count);
+    /// ```
     pub fn read_from_source(
         source: &mut impl Read,
         parse_mode: ParseMode,
     ) -> PositionalResult<Self> {
-        let mut reader = Reader::new(source);
-
-        let mut version = None;
-        let mut version_consistent = true;
-
-        let mut layer = None;
-        let mut layer_consistent = true;
-
-        let mut mode = None;
-        let mut mode_consistent = true;
-
-        let mut min_channel_count = 0;
-        let mut max_channel_count = 0;
-
-        let mut sum_sample_count = 0u64;
-
-        let mut min_sample_rate_hz = 0;
-        let mut max_sample_rate_hz = 0;
-        let mut accmul_sample_rate_hz = 0u64;
-
-        let mut min_bitrate_bps = 0;
-        let mut max_bitrate_bps = 0;
-        let mut accmul_bitrate_bps = 0u64;
+        Self::read_from_source_with_options(source, parse_mode, &ParseOptions::default())
+    }
 
-        loop {
-            let next_read_res = match FrameHeader::try_read(&mut reader) {
-                Ok(res) => res,
-                Err(err) => {
-                    if err.is_unexpected_eof() && sum_sample_count > 0 {
-                        // Silently ignore all unrecognized data after at least one
-                        // non-empty MPEG frame has been parsed.
-                        break;
-                    }
-                    return Err(err);
-                }
+    /// Read from a `source` that implements `Read` with custom options
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{path::Path, fs::File, io::BufReader};
+    /// use mpeg_audio_header::{ByteOrder, Header, ParseMode, ParseOptions};
+    ///
+    /// let path = Path::new("test/source.mp3");
+    /// let file = File::open(path).unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let options = ParseOptions {
+    ///     byte_order: ByteOrder::Swapped,
+    ///     ..Default::default()
+    /// };
+    /// let header = Header::read_from_source_with_options(
+    ///     &mut source,
+    ///     ParseMode::IgnoreVbrHeaders,
+    ///     &options,
+    /// ).unwrap();
+    /// println!("MPEG audio header: {:?}", header);
+    /// ```
+    pub fn read_from_source_with_options(
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+        options: &ParseOptions,
+    ) -> PositionalResult<Self> {
+        Self::read_from_source_into(source, parse_mode, options, None, None)
+    }
+
+    /// Read from a `source` after some leading bytes have already been consumed
+    ///
+    /// The `prefix` contains the bytes that have already been read from
+    /// `source`, e.g. for detecting the format. Parsing starts with the
+    /// `prefix` and continues with the remaining bytes of `source`. All
+    /// byte offsets are relative to the start of the `prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::{BufReader, Read as _}};
+    /// use mpeg_audio_header::{looks_like_mpeg_audio, Header, ParseMode};
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let mut prefix = [0; 10];
+    /// source.read_exact(&mut prefix).unwrap();
+    /// if looks_like_mpeg_audio(&prefix) {
+    ///     let header = Header::read_from_source_with_prefix(
+    ///         &prefix,
+    ///         &mut source,
+    ///         ParseMode::IgnoreVbrHeaders,
+    ///     ).unwrap();
+    ///     println!("MPEG audio header: {:?}", header);
+    /// }
+    /// ```
+    pub fn read_from_source_with_prefix(
+        prefix: &[u8],
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+    ) -> PositionalResult<Self> {
+        Self::read_from_source(&mut prefix.chain(source), parse_mode)
+    }
+
+    /// Read from a `source` and list all audio frames
+    ///
+    /// Collects the byte offset and the header of each audio frame that
+    /// has been aggregated into the returned [`Header`] in a single pass.
+    /// XING/VBRI header frames are not listed. Only the VBR header frame
+    /// is read if [`ParseMode::PreferVbrHeaders`] finds a VBR header,
+    /// i.e. the list remains empty in this case.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader};
+    /// use mpeg_audio_header::{Header, ParseMode};
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let (header, frames) = Header::read_with_frames(&mut source, ParseMode::IgnoreVbrHeaders).unwrap();
+    /// for (byte_offset, frame_header) in frames {
+    ///     println!("{byte_offset}: {:?}", frame_header.bitrate_bps);
+    /// }
+    /// println!("MPEG audio header: {:?}", header);
+    /// ```
+    pub fn read_with_frames(
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+    ) -> PositionalResult<(Self, Vec<(u64, FrameHeader)>)> {
+        let mut frames = Vec::new();
+        let header = Self::read_from_source_into(
+            source,
+            parse_mode,
+            &ParseOptions::default(),
+            Some(&mut frames),
+            None,
+        )?;
+        Ok((header, frames))
+    }
+
+    /// Read from a `source` and report the progress
+    ///
+    /// Invokes `on_progress` after each audio frame with the current
+    /// position, i.e. both the number of bytes that have been read and
+    /// the accumulated duration of all audio frames so far. Not invoked
+    /// for XING/VBRI header frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader};
+    /// use mpeg_audio_header::{Header, ParseMode, ParseOptions};
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let byte_len = file.metadata().unwrap().len();
+    /// let mut source = BufReader::new(file);
+    /// let header = Header::read_with_progress(
+    ///     &mut source,
+    ///     ParseMode::IgnoreVbrHeaders,
+    ///     &ParseOptions::default(),
+    ///     |position| {
+    ///         println!(
+    ///             "{:?} ({} of {byte_len} bytes)",
+    ///             position.duration(),
+    ///             position.byte_offset(),
+    ///         );
+    ///     },
+    /// ).unwrap();
+    /// println!("MPEG audio header: {:?}", header);
+    /// ```
+    pub fn read_with_progress(
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+        options: &ParseOptions,
+        mut on_progress: impl FnMut(&ReadPosition),
+    ) -> PositionalResult<Self> {
+        Self::read_from_source_into(source, parse_mode, options, None, Some(&mut on_progress))
+    }
+
+    /// Read from a `source` and verify the total duration
+    ///
+    /// Fails if [`Self::total_duration`] differs from the `expected`
+    /// duration by more than the `tolerance`, e.g. for confirming the
+    /// duration that is already known from another source.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] with [`Error::DurationMismatch`]
+    /// at the final position if the duration does not match, or on
+    /// any other kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader, time::Duration};
+    /// use mpeg_audio_header::{Error, Header, ParseMode};
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// match Header::read_and_verify_duration(
+    ///     &mut source,
+    ///     ParseMode::IgnoreVbrHeaders,
+    ///     Duration::from_secs(180),
+    ///     Duration::from_millis(100),
+    /// ) {
+    ///     Ok(header) => println!("MPEG audio header: {:?}", header),
+    ///     Err(err) if matches!(err.source(), Error::DurationMismatch { .. }) => {
+    ///         println!("Unexpected duration: {}", err);
+    ///     }
+    ///     Err(err) => println!("Failed to parse: {}", err),
+    /// }
+    /// ```
+    pub fn read_and_verify_duration(
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+        expected: Duration,
+        tolerance: Duration,
+    ) -> PositionalResult<Self> {
+        let header = Self::read_from_source(source, parse_mode)?;
+        let actual = header.total_duration;
+        if actual.abs_diff(expected) > tolerance {
+            return Err(PositionalError {
+                source: Error::DurationMismatch { expected, actual },
+                position: header.final_position,
+                context: None,
+            });
+        }
+        Ok(header)
+    }
+
+    fn read_from_source_into(
+        source: &mut impl Read,
+        parse_mode: ParseMode,
+        options: &ParseOptions,
+        frames: Option<&mut Vec<(u64, FrameHeader)>>,
+        on_progress: Option<&mut dyn FnMut(&ReadPosition)>,
+    ) -> PositionalResult<Self> {
+        match options.byte_order {
+            ByteOrder::Normal => Self::read_from_reader(
+                Reader::new(source),
+                parse_mode,
+                options,
+                frames,
+                on_progress,
+            ),
+            ByteOrder::Swapped => {
+                let mut source = SwappedBytes::new(source);
+                Self::read_from_reader(
+                    Reader::new(&mut source),
+                    parse_mode,
+                    options,
+                    frames,
+                    on_progress,
+                )
+            }
+        }
+    }
+
+    /// Read from a seekable `source` with custom options
+    ///
+    /// Parses the stream from the current position of the `source`
+    /// like [`Self::read_from_source_with_options()`]. Afterwards the
+    /// trailing tags are detected by seeking backwards from the end of
+    /// the stream, see [`Self::trailing_tags`]. On failure the
+    /// bytes around the position of the error are captured as
+    /// [`PositionalError::context()`] if enabled by
+    /// [`ParseOptions::error_context_len`]. The context contains the
+    /// raw bytes of the `source`, independent of [`ParseOptions::byte_order`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader};
+    /// use mpeg_audio_header::{Header, ParseMode, ParseOptions};
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let options = ParseOptions {
+    ///     error_context_len: 16,
+    ///     ..Default::default()
+    /// };
+    /// if let Err(err) =
+    ///     Header::read_from_seekable_source_with_options(&mut source, ParseMode::PreferVbrHeaders, &options)
+    /// {
+    ///     println!("{err}: {:02X?}", err.context());
+    /// }
+    /// ```
+    pub fn read_from_seekable_source_with_options(
+        source: &mut (impl Read + Seek),
+        parse_mode: ParseMode,
+        options: &ParseOptions,
+    ) -> PositionalResult<Self> {
+        let start_offset = source.stream_position().map_err(|e| PositionalError {
+            source: e.into(),
+            position: ReadPosition::default(),
+            context: None,
+        })?;
+        let mut header = Self::read_from_source_with_options(source, parse_mode, options).map_err(
+            |mut err| {
+                if options.error_context_len > 0 {
+                    err.capture_context(source, start_offset, options.error_context_len);
+                }
+                err
+            },
+        )?;
+        header.read_trailing_tags(source, start_offset)?;
+        Ok(header)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn read_from_reader<R: Read>(
+        mut reader: Reader<'_, R>,
+        parse_mode: ParseMode,
+        options: &ParseOptions,
+        mut frames: Option<&mut Vec<(u64, FrameHeader)>>,
+        mut on_progress: Option<&mut dyn FnMut(&ReadPosition)>,
+    ) -> PositionalResult<Self> {
+        reader.set_max_bytes(options.max_bytes);
+
+        let data_end_offset = riff::skip_to_data_chunk(&mut reader)?;
+        reader.set_end_offset(data_end_offset);
+
+        let mut aggregation = AggregationState::new();
+
+        let mut mc_channel_count = None;
+        let mut mc_channel_count_consistent = options.detect_multichannel;
+        let mut frame_body = Vec::new();
+
+        let mut raw_vbr_header = None;
+
+        let mut declared_frame_count = None;
+
+        let mut declared_vbr = None;
+
+        let mut vbri_toc = None;
+        let mut vbri_delay = None;
+        let mut vbr_header_suspect = false;
+        let mut vbr_frame_samples = None;
+
+        let mut detected_vbr_header = None;
+
+        let mut lame_info: Option<LameInfo> = None;
+
+        let mut min_bitrate_runs = options.min_bitrate_run_frames.map(MinBitrateRuns::new);
+
+        let mut bitrate_trend = options.detect_bitrate_monotonic.then(BitrateTrend::default);
+
+        let mut truncated_frame = None;
+
+        let mut alignment_errors = 0;
+
+        let mut junk_byte_count = 0;
+        // The number of junk bytes up to the previous frame
+        let mut resync_junk_byte_count = 0;
+
+        let mut diagnostics = Vec::new();
+
+        let mut partial = false;
+
+        let mut uses_bit_reservoir: Option<bool> = None;
+
+        let mut lead_in_accepted = options.min_lead_in_frames <= 1;
+        let mut lead_in_frame_count = 0;
+        let mut first_frame_offset = None;
+        let mut audio_end_offset = None;
+        // The expected start of the next consecutive frame
+        let mut lead_in_end_offset = None;
+        // The unused remainder of a VBR header frame is not junk
+        let mut vbr_header_frame_end_offset = None;
+
+        loop {
+            // All bytes that are skipped until the next frame and that don't
+            // belong to a metadata block are junk.
+            let scan_start_offset = reader
+                .position()
+                .byte_offset
+                .max(vbr_header_frame_end_offset.take().unwrap_or_default());
+            let scan_start_metadata_byte_count = reader.metadata_byte_count();
+            let junk_bytes_until = |reader: &Reader<'_, R>, byte_offset: u64| {
+                byte_offset
+                    .saturating_sub(scan_start_offset)
+                    .saturating_sub(reader.metadata_byte_count() - scan_start_metadata_byte_count)
+            };
+            let sync_step = match frame::sync_next_frame(&mut reader, options, lead_in_accepted) {
+                Ok(sync_step) => sync_step,
+                Err(err) => {
+                    if err.is_unexpected_eof() && aggregation.total_sample_count() > 0 {
+                        // Silently ignore all unrecognized data after at least one
+                        // non-empty MPEG frame has been parsed.
+                        let trailing_byte_count =
+                            junk_bytes_until(&reader, reader.position().byte_offset);
+                        junk_byte_count += trailing_byte_count;
+                        if options.collect_diagnostics && trailing_byte_count > 0 {
+                            diagnostics.push(Diagnostic::TrailingDataIgnored {
+                                byte_offset: scan_start_offset,
+                                byte_len: trailing_byte_count,
+                            });
+                        }
+                        break;
+                    }
+                    return Err(err);
+                }
             };
-            match next_read_res {
-                Ok(Some(frame_header)) => {
+            match sync_step {
+                SyncStep::LeadInRejected => {
+                    // Continue searching at the next byte
+                    junk_byte_count += junk_bytes_until(&reader, reader.position().byte_offset);
+                }
+                SyncStep::Frame(frame_header, header_word) => {
                     // MPEG frame
+                    let frame_start_offset =
+                        reader.position().byte_offset - u64::from(frame::FRAME_HEADER_SIZE);
+                    lead_in_accepted = true;
+                    junk_byte_count += junk_bytes_until(&reader, frame_start_offset);
+                    if options.collect_diagnostics && junk_byte_count > resync_junk_byte_count {
+                        diagnostics.push(Diagnostic::ResyncPerformed {
+                            byte_offset: frame_start_offset,
+                            skipped_byte_count: junk_byte_count - resync_junk_byte_count,
+                        });
+                    }
+                    resync_junk_byte_count = junk_byte_count;
+                    first_frame_offset.get_or_insert(frame_start_offset);
+                    if options.capture_vbr_header && aggregation.total_sample_count() == 0 {
+                        reader.start_recording(&header_word.to_be_bytes());
+                    }
+                    if mc_channel_count_consistent {
+                        if let Some(frame_size) = frame_header.frame_size {
+                            // Read ahead the frame body and push it back afterwards
+                            frame_body.resize(
+                                usize::from(frame_size) - usize::from(frame::FRAME_HEADER_SIZE),
+                                0,
+                            );
+                            let num_bytes_read = reader.try_read_until_eof(&mut frame_body)?;
+                            reader.unread(&frame_body[..num_bytes_read]);
+                            if num_bytes_read == frame_body.len() {
+                                let next_mc_channel_count =
+                                    layer2::mc_extension_channel_count(&frame_header, &frame_body);
+                                if mc_channel_count.is_none() {
+                                    mc_channel_count = next_mc_channel_count;
+                                }
+                                mc_channel_count_consistent = next_mc_channel_count.is_some()
+                                    && mc_channel_count == next_mc_channel_count;
+                            }
+                        }
+                    }
                     let mut num_bytes_consumed = u32::from(frame::FRAME_HEADER_SIZE);
-                    if !reader
-                        .try_skip_exact_until_eof(u64::from(frame_header.side_information_size()))?
-                    {
-                        break;
+                    let mut main_data_begin = None;
+                    // Layer I/II frames have no side information. The bytes are only
+                    // skipped if the frame is big enough to contain a XING header at
+                    // the same offset as in Layer III frames, which is written by some
+                    // muxers. Otherwise small frames would be rejected.
+                    let skip_side_information = frame_header.layer == Layer::Layer3
+                        || frame_header.check_payload_size(
+                            num_bytes_consumed as u16
+                                + frame_header.side_information_size()
+                                + u16::from(XING_HEADER_MIN_SIZE),
+                        );
+                    if skip_side_information {
+                        if options.detect_bit_reservoir && frame_header.layer == Layer::Layer3 {
+                            let mut side_information =
+                                vec![0; usize::from(frame_header.side_information_size())];
+                            if !reader.try_read_exact_until_eof(&mut side_information)? {
+                                truncated_frame = Some(frame_start_offset);
+                                break;
+                            }
+                            main_data_begin =
+                                layer3::main_data_begin(&frame_header, &side_information);
+                        } else if !reader.try_skip_exact_until_eof(u64::from(
+                            frame_header.side_information_size(),
+                        ))? {
+                            truncated_frame = Some(frame_start_offset);
+                            break;
+                        }
+                        num_bytes_consumed += u32::from(frame_header.side_information_size());
                     }
-                    num_bytes_consumed += u32::from(frame_header.side_information_size());
                     if !frame_header.check_payload_size(num_bytes_consumed as u16) {
                         return Err(reader.positional_error(Error::FrameError(
                             "invalid payload size".to_string(),
@@ -214,36 +1404,92 @@ impl Header {
 
                     // XING header frames may only appear at the start of the file before
                     // the first MPEG frame with audio data.
-                    if sum_sample_count == 0
+                    if skip_side_information
+                        && aggregation.total_sample_count() == 0
                         && frame_header.check_payload_size(
                             num_bytes_consumed as u16 + u16::from(XING_HEADER_MIN_SIZE),
                         )
                     {
                         let mut xing_header = [0; XING_HEADER_MIN_SIZE as usize];
                         if !reader.try_read_exact_until_eof(&mut xing_header)? {
+                            truncated_frame = Some(frame_start_offset);
                             break;
                         }
                         num_bytes_consumed += u32::from(XING_HEADER_MIN_SIZE);
 
+                        // The VBRI header is located at a fixed offset after the frame
+                        // header independent of the size of the side information
+                        let vbri_header_start =
+                            u32::from(frame::FRAME_HEADER_SIZE) + u32::from(VBRI_HEADER_OFFSET);
+                        let xing_header_start =
+                            num_bytes_consumed - u32::from(XING_HEADER_MIN_SIZE);
+                        if !matches!(&xing_header[..4], b"Xing" | b"Info" | b"VBRI")
+                            && xing_header_start < vbri_header_start
+                            && frame_header.check_payload_size(
+                                (vbri_header_start + u32::from(XING_HEADER_MIN_SIZE)) as u16,
+                            )
+                        {
+                            let gap_size = (vbri_header_start - xing_header_start) as usize;
+                            let mut lookahead = xing_header.to_vec();
+                            lookahead.resize(gap_size + usize::from(XING_HEADER_MIN_SIZE), 0);
+                            let num_bytes_read =
+                                reader.try_read_until_eof(&mut lookahead[xing_header.len()..])?;
+                            if num_bytes_read == lookahead.len() - xing_header.len()
+                                && &lookahead[gap_size..gap_size + 4] == b"VBRI"
+                            {
+                                xing_header.copy_from_slice(&lookahead[gap_size..]);
+                                num_bytes_consumed += num_bytes_read as u32;
+                            } else {
+                                reader.unread(
+                                    &lookahead
+                                        [xing_header.len()..xing_header.len() + num_bytes_read],
+                                );
+                            }
+                        }
+
                         let mut vbr_total_frames: Option<(HeaderSource, u32)> = None;
+                        let mut vbr_total_bytes: Option<u32> = None;
                         match &xing_header[..4] {
                             // XING header starts with either "Xing" or "Info"
                             // https://www.codeproject.com/Articles/8295/MPEG-Audio-Frame-Header#XINGHeader
                             b"Xing" | b"Info" => {
                                 // No audio data in these special frames!
                                 is_audio_frame = false;
+                                detected_vbr_header = Some(HeaderSource::XingHeader);
+                                declared_vbr = Some(&xing_header[..4] == b"Xing");
 
                                 // The XING header must precede all MPEG frames
-                                debug_assert!(version.is_none());
-                                debug_assert!(layer.is_none());
-                                debug_assert!(mode.is_none());
+                                debug_assert_eq!(aggregation.frame_count(), 0);
 
-                                if xing_header[7] & 0b0001 != 0 {
-                                    // 4 Bytes
-                                    let mut total_frames_bytes = [0; 4];
+                                let xing_flags = u32::from_be_bytes([
+                                    xing_header[4],
+                                    xing_header[5],
+                                    xing_header[6],
+                                    xing_header[7],
+                                ]);
+                                if options.collect_diagnostics && xing_flags & !XING_FLAGS_MASK != 0
+                                {
+                                    diagnostics.push(Diagnostic::SuspiciousXingFlags {
+                                        byte_offset: frame_start_offset,
+                                        flags: xing_flags,
+                                    });
+                                }
+                                // The frames field is the only field needed for calculating
+                                // the duration. It is parsed independent of all other flags as
+                                // long as it fits into the frame.
+                                if xing_flags & XING_FLAG_FRAMES != 0
+                                    && frame_header.check_payload_size(
+                                        num_bytes_consumed as u16
+                                            + u16::from(XING_FRAMES_FIELD_SIZE),
+                                    )
+                                {
+                                    let mut total_frames_bytes =
+                                        [0; XING_FRAMES_FIELD_SIZE as usize];
                                     if !reader.try_read_exact_until_eof(&mut total_frames_bytes)? {
+                                        truncated_frame = Some(frame_start_offset);
                                         break;
                                     }
+                                    num_bytes_consumed += u32::from(XING_FRAMES_FIELD_SIZE);
                                     let total_frames = u32::from_be_bytes(total_frames_bytes);
                                     if total_frames > 0 {
                                         vbr_total_frames =
@@ -251,21 +1497,56 @@ impl Header {
                                     }
                                 }
                                 let mut skip_size = 0u32;
-                                if xing_header[7] & 0b0010 != 0 {
+                                if xing_flags & XING_FLAG_BYTES != 0
+                                    && frame_header.check_payload_size(
+                                        num_bytes_consumed as u16
+                                            + u16::from(XING_BYTES_FIELD_SIZE),
+                                    )
+                                {
+                                    let mut total_bytes_bytes = [0; XING_BYTES_FIELD_SIZE as usize];
+                                    if !reader.try_read_exact_until_eof(&mut total_bytes_bytes)? {
+                                        truncated_frame = Some(frame_start_offset);
+                                        break;
+                                    }
+                                    num_bytes_consumed += u32::from(XING_BYTES_FIELD_SIZE);
+                                    vbr_total_bytes = Some(u32::from_be_bytes(total_bytes_bytes));
+                                } else if xing_flags & XING_FLAG_BYTES != 0 {
                                     // Size
                                     skip_size += 4;
                                 }
-                                if xing_header[7] & 0b0100 != 0 {
+                                if xing_flags & XING_FLAG_TOC != 0 {
                                     // TOC
                                     skip_size += 100;
                                 }
-                                if xing_header[7] & 0b1000 != 0 {
+                                if xing_flags & XING_FLAG_QUALITY != 0 {
                                     // Audio quality
                                     skip_size += 4;
                                 }
+                                // Optional fields that don't fit into the frame are missing
+                                // and must not be skipped beyond the end of the frame.
+                                if let Some(frame_size) = frame_header.frame_size {
+                                    skip_size = skip_size.min(
+                                        u32::from(frame_size).saturating_sub(num_bytes_consumed),
+                                    );
+                                }
                                 if !reader.try_skip_exact_until_eof(u64::from(skip_size))? {
+                                    truncated_frame = Some(frame_start_offset);
                                     break;
                                 }
+                                num_bytes_consumed += skip_size;
+                                if frame_header.check_payload_size(
+                                    num_bytes_consumed as u16 + u16::from(lame::LAME_TAG_SIZE),
+                                ) {
+                                    let mut lame_tag = [0; lame::LAME_TAG_SIZE as usize];
+                                    if !reader.try_read_exact_until_eof(&mut lame_tag)? {
+                                        truncated_frame = Some(frame_start_offset);
+                                        break;
+                                    }
+                                    lame_info = lame::parse_lame_tag(&lame_tag);
+                                    if lame_info.is_none() {
+                                        reader.unread(&lame_tag);
+                                    }
+                                }
                                 // Finally finish this frame by pretending that we have consumed all bytes
                                 num_bytes_consumed = frame_header
                                     .frame_size
@@ -280,16 +1561,24 @@ impl Header {
                             {
                                 // No audio data in these special frames!
                                 is_audio_frame = false;
+                                detected_vbr_header = Some(HeaderSource::VbriHeader);
+                                declared_vbr = Some(true);
 
-                                // We only read total_frames and skip the rest. The words containing version (2 bytes)
-                                // and delay (2 bytes) have already been read into the XING header:
-                                // | 4 ("VBRI") + 2 (version) + 2 (delay) + 2 (quality) + 4 (size/bytes) + 4 (total_frames) + ...
+                                // The words containing version (2 bytes) and delay (2 bytes) have
+                                // already been read into the XING header:
+                                // | 4 ("VBRI") + 2 (version) + 2 (delay) | 2 (quality) + 4 (size/bytes) + 4 (total_frames)
+                                // | + 2 (TOC entries) + 2 (TOC scale) + 2 (TOC entry size) + 2 (frames per TOC entry)
                                 // |<-         XING Header              ->|<-                 XING/VBRI Header...
                                 let mut xing_vbri_header = [0; XING_VBRI_HEADER_MIN_SIZE as usize];
                                 if !reader.try_read_exact_until_eof(&mut xing_vbri_header)? {
+                                    truncated_frame = Some(frame_start_offset);
                                     break;
                                 }
+                                num_bytes_consumed += u32::from(XING_VBRI_HEADER_MIN_SIZE);
 
+                                vbri_delay = Some(u16::from_be_bytes(
+                                    xing_header[6..8].try_into().expect("2 bytes"),
+                                ));
                                 let total_frames = u32::from_be_bytes(
                                     xing_vbri_header[6..10].try_into().expect("4 bytes"),
                                 );
@@ -297,19 +1586,42 @@ impl Header {
                                     vbr_total_frames =
                                         Some((HeaderSource::VbriHeader, total_frames));
                                 }
+                                vbr_total_bytes = Some(u32::from_be_bytes(
+                                    xing_vbri_header[2..6].try_into().expect("4 bytes"),
+                                ));
 
                                 let toc_entries_count = u16::from_be_bytes(
+                                    xing_vbri_header[10..12].try_into().expect("2 bytes"),
+                                );
+                                let toc_scale = u16::from_be_bytes(
                                     xing_vbri_header[12..14].try_into().expect("2 bytes"),
                                 );
-
                                 let toc_entry_size = u16::from_be_bytes(
+                                    xing_vbri_header[14..16].try_into().expect("2 bytes"),
+                                );
+                                let frames_per_toc_entry = u16::from_be_bytes(
                                     xing_vbri_header[16..18].try_into().expect("2 bytes"),
                                 );
 
-                                // Skip all trailing TOC entries
                                 let toc_size =
                                     u32::from(toc_entries_count) * u32::from(toc_entry_size);
-                                if !reader.try_skip_exact_until_eof(u64::from(toc_size))? {
+                                if frame_header.frame_size.is_some_and(|frame_size| {
+                                    num_bytes_consumed + toc_size <= u32::from(frame_size)
+                                }) {
+                                    let mut toc = vec![0; toc_size as usize];
+                                    if !reader.try_read_exact_until_eof(&mut toc)? {
+                                        truncated_frame = Some(frame_start_offset);
+                                        break;
+                                    }
+                                    vbri_toc = vbri::parse_vbri_toc(
+                                        toc_scale,
+                                        frames_per_toc_entry,
+                                        toc_entry_size,
+                                        &toc,
+                                    );
+                                } else if !reader.try_skip_exact_until_eof(u64::from(toc_size))? {
+                                    // Skip all trailing TOC entries that don't fit into the frame
+                                    truncated_frame = Some(frame_start_offset);
                                     break;
                                 }
 
@@ -323,188 +1635,316 @@ impl Header {
                                 debug_assert!(is_audio_frame);
                             }
                         }
+                        if let Some(recording) = reader.finish_recording() {
+                            if !is_audio_frame {
+                                raw_vbr_header = Some(recording);
+                            }
+                        }
+                        if !is_audio_frame {
+                            vbr_frame_samples = Some(frame_header.sample_count);
+                        }
                         if let Some((source, total_frames)) = vbr_total_frames {
-                            let total_sample_count =
-                                u64::from(total_frames) * u64::from(frame_header.sample_count);
-                            let seconds =
-                                total_sample_count / u64::from(frame_header.sample_rate_hz);
-                            let nanoseconds = (total_sample_count * u64::from(NANOS_PER_SECOND))
-                                / u64::from(frame_header.sample_rate_hz)
-                                - u64::from(NANOS_PER_SECOND) * seconds;
-                            debug_assert!(nanoseconds < NANOS_PER_SECOND.into());
-                            let total_duration = Duration::new(seconds, nanoseconds as u32);
+                            declared_frame_count = Some(total_frames);
+                            if let Some((tolerance_percent, total_bytes)) =
+                                options.vbr_header_tolerance_percent.zip(vbr_total_bytes)
+                            {
+                                // Quick scan of the first audio frame that follows
+                                finish_vbr_header_frame(
+                                    &mut reader,
+                                    &frame_header,
+                                    frame_start_offset,
+                                )?;
+                                let first_frame_size =
+                                    frame::peek_next_frame_header(&mut reader, options)?
+                                        .and_then(|next_frame_header| next_frame_header.frame_size);
+                                vbr_header_suspect = !is_plausible_vbr_header(
+                                    &frame_header,
+                                    total_frames,
+                                    total_bytes,
+                                    first_frame_size,
+                                    tolerance_percent,
+                                );
+                            }
+                            let total_sample_count = (u64::from(total_frames)
+                                * u64::from(frame_header.sample_count))
+                            .saturating_sub(vbri_delay.map_or(0, u64::from));
+                            let total_duration = duration_from_samples(
+                                total_sample_count,
+                                frame_header.sample_rate_hz,
+                            );
                             match parse_mode {
-                                ParseMode::PreferVbrHeaders => {
+                                ParseMode::PreferVbrHeaders if !vbr_header_suspect => {
+                                    finish_vbr_header_frame(
+                                        &mut reader,
+                                        &frame_header,
+                                        frame_start_offset,
+                                    )?;
                                     return Ok(Self {
                                         source,
                                         version: Some(frame_header.version),
                                         layer: Some(frame_header.layer),
                                         mode: Some(frame_header.mode),
+                                        channel_semantics: Some(frame_header.effective_channels()),
+                                        emphasis: Some(frame_header.emphasis)
+                                            .filter(|emphasis| *emphasis != Emphasis::Reserved),
+                                        // The padding of the VBR header frame is meaningless
+                                        common_flags: FrameFlags {
+                                            protection: Some(frame_header.flags.protection),
+                                            padding: None,
+                                            private: Some(frame_header.flags.private),
+                                            copyright: Some(frame_header.flags.copyright),
+                                            original: Some(frame_header.flags.original),
+                                        },
                                         min_channel_count: frame_header.channel_count(),
                                         max_channel_count: frame_header.channel_count(),
+                                        program_count: frame_header
+                                            .effective_channels()
+                                            .program_count(),
+                                        mode_frame_counts: Default::default(),
+                                        mono_frame_count: 0,
+                                        stereo_frame_count: 0,
+                                        padding_frame_count: 0,
+                                        possible_multichannel: false,
+                                        multichannel_count: None,
                                         min_sample_rate_hz: frame_header.sample_rate_hz,
                                         max_sample_rate_hz: frame_header.sample_rate_hz,
                                         total_sample_count,
+                                        samples_per_frame: Some(frame_header.sample_count),
                                         total_duration,
+                                        reconciled_declared_duration: None,
+                                        measured_duration: None,
+                                        measured_frame_count: None,
+                                        measured_sample_count: None,
                                         avg_sample_rate_hz: Some(frame_header.sample_rate_hz),
+                                        max_frame_size: None,
                                         avg_bitrate_bps: frame_header.bitrate_bps,
+                                        // Bitrates of MPEG frames are always multiples of 1 kbps
+                                        avg_bitrate_kbps: frame_header
+                                            .bitrate_bps
+                                            .map(|bitrate_bps| (bitrate_bps / 1000) as u16),
+                                        bitrate_cv: None,
+                                        uses_bit_reservoir: None,
+                                        bitrate_monotonic: None,
+                                        truncated_metadata: reader.truncated_metadata_offset(),
+                                        truncated_frame: None,
+                                        alignment_errors: 0,
+                                        junk_byte_count,
+                                        partial: false,
+                                        stopped_at: None,
+                                        read_error: None,
+                                        final_position: reader.position().clone(),
+                                        stream_byte_len: None,
+                                        raw_vbr_header,
+                                        declared_frame_count,
+                                        declared_vbr,
+                                        vbri_toc,
+                                        vbri_delay,
+                                        vbr_header_suspect,
+                                        cbr_claim_violated: false,
+                                        vbr_frame_samples,
+                                        start_skip_samples: lame_info
+                                            .as_ref()
+                                            .map(|info| info.encoder_delay.into()),
+                                        end_skip_samples: lame_info
+                                            .as_ref()
+                                            .map(|info| info.encoder_padding.into()),
+                                        lame_info,
+                                        min_bitrate_runs: Vec::new(),
+                                        lead_in_confidence: 0,
+                                        first_frame_offset,
+                                        // The declared size includes the VBR header frame
+                                        audio_end_offset: vbr_total_bytes.map(|total_bytes| {
+                                            frame_start_offset + u64::from(total_bytes)
+                                        }),
+                                        profiles: vec![ProfileSummary {
+                                            version: frame_header.version,
+                                            layer: frame_header.layer,
+                                            sample_rate_hz: frame_header.sample_rate_hz,
+                                            frame_count: total_frames.into(),
+                                            total_samples: total_sample_count,
+                                        }],
+                                        mllt_seek_points: reader.take_mllt_seek_points(),
+                                        apev2_info: reader.take_apev2_info(),
+                                        trailing_tags: Vec::new(),
+                                        diagnostics,
+                                        #[cfg(feature = "id3-lite")]
+                                        basic_tags: reader.take_basic_tags(),
+                                        first_frame: None,
                                     });
                                 }
-                                ParseMode::IgnoreVbrHeaders => {
+                                ParseMode::PreferVbrHeaders
+                                | ParseMode::IgnoreVbrHeaders
+                                | ParseMode::Reconcile => {
                                     // Just skip the VBR headers
                                 }
                             }
                         }
                     }
+                    // Stop recording if the frame has not been checked for a VBR header
+                    reader.finish_recording();
                     if let Some(frame_size) = frame_header.frame_size {
-                        debug_assert!(u32::from(frame_size) >= num_bytes_consumed);
-                        if !reader.try_skip_exact_until_eof(u64::from(
-                            u32::from(frame_size) - num_bytes_consumed,
-                        ))? {
+                        let Some(remaining_size) =
+                            u32::from(frame_size).checked_sub(num_bytes_consumed)
+                        else {
+                            return Err(reader.positional_error(Error::FrameError(
+                                "invalid payload size".to_string(),
+                            )));
+                        };
+                        if !reader.try_skip_exact_until_eof(u64::from(remaining_size))? {
+                            truncated_frame = Some(frame_start_offset);
                             break;
                         }
+                        // The unused remainder of VBR header frames has not been skipped
+                        if is_audio_frame
+                            && !frame::is_aligned_with_next_frame(&mut reader, options)?
+                        {
+                            alignment_errors += 1;
+                        }
                     }
 
+                    if !is_audio_frame {
+                        vbr_header_frame_end_offset = frame_header
+                            .frame_size
+                            .map(|frame_size| frame_start_offset + u64::from(frame_size));
+                    }
                     if is_audio_frame {
-                        if version_consistent {
-                            if let Some(some_version) = version {
-                                version_consistent = some_version == frame_header.version;
-                                if !version_consistent {
-                                    version = None;
-                                }
-                            } else {
-                                version = Some(frame_header.version);
-                            }
+                        if aggregation.frame_count() == 0
+                            || lead_in_end_offset == Some(frame_start_offset)
+                        {
+                            lead_in_frame_count += 1;
+                            lead_in_end_offset = frame_header
+                                .frame_size
+                                .map(|frame_size| frame_start_offset + u64::from(frame_size));
+                        } else {
+                            lead_in_end_offset = None;
                         }
-
-                        if !layer_consistent {
-                            if let Some(some_layer) = layer {
-                                layer_consistent = some_layer == frame_header.layer;
-                                if !layer_consistent {
-                                    layer = None;
-                                }
-                            } else {
-                                layer = Some(frame_header.layer);
-                            }
+                        if let Some(bitrate_trend) = &mut bitrate_trend {
+                            bitrate_trend.feed_frame(&frame_header);
                         }
-
-                        if mode_consistent {
-                            if let Some(some_mode) = mode {
-                                mode_consistent = some_mode == frame_header.mode;
-                                if !mode_consistent {
-                                    mode = None;
-                                }
-                            } else {
-                                mode = Some(frame_header.mode);
-                            }
+                        if let Some(min_bitrate_runs) = &mut min_bitrate_runs {
+                            min_bitrate_runs
+                                .feed_frame(aggregation.total_sample_count(), &frame_header);
                         }
-
-                        let frame_samples = u64::from(frame_header.sample_count);
-                        debug_assert!(frame_samples > 0);
-                        sum_sample_count += frame_samples;
-
-                        let channel_count = frame_header.channel_count();
-                        debug_assert!(channel_count > 0);
-                        if min_channel_count == 0 {
-                            min_channel_count = channel_count;
-                        } else {
-                            min_channel_count = min_channel_count.min(channel_count);
+                        if let Some(frames) = &mut frames {
+                            frames.push((frame_start_offset, frame_header.clone()));
                         }
-                        if max_channel_count == 0 {
-                            max_channel_count = channel_count;
-                        } else {
-                            max_channel_count = max_channel_count.max(channel_count);
+                        aggregation.feed_frame(&frame_header);
+                        reader.add_duration(frame_header.frame_duration());
+                        if let Some(on_progress) = &mut on_progress {
+                            on_progress(reader.position());
                         }
-
-                        // Free bitrate = 0 bps
-                        if let Some(bitrate_bps) = frame_header.bitrate_bps {
-                            if min_bitrate_bps == 0 {
-                                min_bitrate_bps = bitrate_bps;
-                            } else {
-                                min_bitrate_bps = min_bitrate_bps.min(bitrate_bps);
-                            }
-                            if max_bitrate_bps == 0 {
-                                max_bitrate_bps = bitrate_bps;
-                            } else {
-                                max_bitrate_bps = max_bitrate_bps.max(bitrate_bps);
-                            }
-                            accmul_bitrate_bps += u64::from(bitrate_bps) * frame_samples;
+                        if let Some(main_data_begin) = main_data_begin {
+                            uses_bit_reservoir =
+                                Some(uses_bit_reservoir.unwrap_or(false) || main_data_begin != 0);
                         }
-
-                        debug_assert!(frame_header.sample_rate_hz > 0);
-                        if min_sample_rate_hz == 0 {
-                            min_sample_rate_hz = frame_header.sample_rate_hz;
-                        } else {
-                            min_sample_rate_hz =
-                                min_sample_rate_hz.min(frame_header.sample_rate_hz);
+                        audio_end_offset = Some(reader.position().byte_offset);
+                        if options.tolerate_io_errors {
+                            reader.tolerate_io_errors();
                         }
-                        if max_sample_rate_hz == 0 {
-                            max_sample_rate_hz = frame_header.sample_rate_hz;
-                        } else {
-                            max_sample_rate_hz =
-                                max_sample_rate_hz.max(frame_header.sample_rate_hz);
+                    }
+                    if options.detect_vbr_header_only {
+                        if !is_audio_frame {
+                            finish_vbr_header_frame(
+                                &mut reader,
+                                &frame_header,
+                                frame_start_offset,
+                            )?;
                         }
-                        accmul_sample_rate_hz +=
-                            u64::from(frame_header.sample_rate_hz) * frame_samples;
-
-                        let frame_duration_nanos: u64 = (frame_samples
-                            * u64::from(NANOS_PER_SECOND))
-                            / u64::from(frame_header.sample_rate_hz);
-                        debug_assert!(frame_duration_nanos < NANOS_PER_SECOND.into());
-                        reader.add_duration(Duration::new(0, frame_duration_nanos as u32));
+                        partial = true;
+                        break;
+                    }
+                    if options.identify_only
+                        && aggregation.frame_count()
+                            >= options.effective_identify_frame_count().into()
+                    {
+                        partial = true;
+                        break;
                     }
                 }
-                Ok(None) => break,
-                Err((frame_header_bytes, header_err)) => {
-                    if frame::skip_metadata(&mut reader, frame_header_bytes)? {
-                        if sum_sample_count > 0 {
-                            // No more MPEG frames after a trailing metadata frame expected
-                            break;
-                        }
-                    } else {
-                        return Err(header_err);
+                SyncStep::Finished => {
+                    let trailing_byte_count =
+                        junk_bytes_until(&reader, reader.position().byte_offset);
+                    junk_byte_count += trailing_byte_count;
+                    if options.collect_diagnostics
+                        && trailing_byte_count > 0
+                        && reader.stopped_at_metadata_offset().is_none()
+                    {
+                        diagnostics.push(Diagnostic::TrailingDataIgnored {
+                            byte_offset: scan_start_offset,
+                            byte_len: trailing_byte_count,
+                        });
+                    }
+                    break;
+                }
+                SyncStep::MetadataSkipped => {
+                    junk_byte_count += junk_bytes_until(&reader, reader.position().byte_offset);
+                    if aggregation.total_sample_count() > 0 {
+                        // No more MPEG frames after a trailing metadata frame expected
+                        break;
                     }
                 }
             }
         }
 
-        let total_sample_count = sum_sample_count;
-        let total_duration = reader.position().duration;
-
-        let avg_sample_rate_hz = if total_sample_count > 0 {
-            let avg_sample_rate_hz = accmul_sample_rate_hz / total_sample_count;
-            debug_assert!(avg_sample_rate_hz <= u16::MAX.into());
-            Some(avg_sample_rate_hz as u16)
-        } else {
-            None
-        };
-
-        let avg_bitrate_bps = if total_sample_count > 0 {
-            let avg_bitrate_bps = accmul_bitrate_bps / total_sample_count;
-            debug_assert!(avg_bitrate_bps <= u32::MAX.into());
-            Some(avg_bitrate_bps as u32)
-        } else {
-            None
-        };
-
-        Ok(Self {
-            source: HeaderSource::MpegFrameHeaders,
-            version,
-            layer,
-            mode,
-            min_channel_count,
-            max_channel_count,
-            min_sample_rate_hz,
-            max_sample_rate_hz,
-            total_sample_count,
-            total_duration,
-            avg_sample_rate_hz,
-            avg_bitrate_bps,
-        })
+        let measured_frame_count = aggregation.frame_count();
+        let mut header = aggregation.finalize();
+        header.multichannel_count =
+            mc_channel_count.filter(|count| mc_channel_count_consistent && *count > 2);
+        header.possible_multichannel = header.multichannel_count.is_some();
+        header.truncated_metadata = reader.truncated_metadata_offset();
+        header.truncated_frame = truncated_frame;
+        header.alignment_errors = alignment_errors;
+        header.junk_byte_count = junk_byte_count;
+        header.diagnostics = diagnostics;
+        header.stopped_at = reader.stopped_at_metadata_offset();
+        header.read_error = reader.take_read_error();
+        header.partial = partial || header.stopped_at.is_some() || header.read_error.is_some();
+        header.uses_bit_reservoir = uses_bit_reservoir;
+        header.bitrate_monotonic = bitrate_trend.and_then(BitrateTrend::finish);
+        header.final_position = reader.position().clone();
+        header.raw_vbr_header = raw_vbr_header;
+        header.declared_frame_count = declared_frame_count;
+        header.declared_vbr = declared_vbr;
+        header.vbri_toc = vbri_toc;
+        header.vbri_delay = vbri_delay;
+        header.vbr_header_suspect = vbr_header_suspect;
+        header.vbr_frame_samples = vbr_frame_samples;
+        header.start_skip_samples = lame_info.as_ref().map(|info| info.encoder_delay.into());
+        header.end_skip_samples = lame_info.as_ref().map(|info| info.encoder_padding.into());
+        header.lame_info = lame_info;
+        header.min_bitrate_runs = min_bitrate_runs
+            .map(MinBitrateRuns::finish)
+            .unwrap_or_default();
+        header.lead_in_confidence = lead_in_frame_count;
+        header.first_frame_offset = first_frame_offset;
+        header.audio_end_offset = audio_end_offset;
+        header.mllt_seek_points = reader.take_mllt_seek_points();
+        header.apev2_info = reader.take_apev2_info();
+        #[cfg(feature = "id3-lite")]
+        {
+            header.basic_tags = reader.take_basic_tags();
+        }
+        if options.detect_vbr_header_only {
+            if let Some(source) = detected_vbr_header {
+                header.source = source;
+            }
+        }
+        if matches!(parse_mode, ParseMode::Reconcile) {
+            header.reconciled_declared_duration = header.declared_duration();
+            header.measured_duration = Some(header.total_duration);
+            header.measured_frame_count = Some(measured_frame_count);
+            header.measured_sample_count = Some(header.total_sample_count);
+            header.cbr_claim_violated = header.declared_vbr == Some(false)
+                && header.bitrate_cv.is_some_and(|bitrate_cv| bitrate_cv > 0.0);
+        }
+        Ok(header)
     }
 
     /// Read from a file
     ///
+    /// The size of the file is reported in [`Self::stream_byte_len`]
+    /// and the trailing tags in [`Self::trailing_tags`].
+    ///
     /// # Errors
     ///
     /// Returns a [`PositionalError`] on any kind of failure.
@@ -521,17 +1961,69 @@ impl Header {
     /// println!("MPEG audio header: {:?}", header);
     /// ```
     pub fn read_from_file(file: &File, parse_mode: ParseMode) -> PositionalResult<Self> {
+        let stream_byte_len = file
+            .metadata()
+            .map_err(|e| PositionalError {
+                source: e.into(),
+                position: ReadPosition::default(),
+                context: None,
+            })?
+            .len();
         let mut source = BufReader::new(file);
-        Self::read_from_source(&mut source, parse_mode)
+        let mut header = Self::read_from_source(&mut source, parse_mode)?;
+        header.stream_byte_len = Some(stream_byte_len);
+        header.read_trailing_tags(&mut source.into_inner(), 0)?;
+        Ok(header)
     }
 
-    /// Read from a file path
+    /// Read from a slice
     ///
-    /// # Errors
+    /// The length of the slice is reported in [`Self::stream_byte_len`]
+    /// and the trailing tags in [`Self::trailing_tags`].
     ///
-    /// Returns a [`PositionalError`] on any kind of failure.
+    /// Skipped bytes, i.e. the bodies of all frames, are not copied.
+    /// Parsing data in memory is considerably faster than reading from
+    /// a file.
     ///
-    /// # Examples
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    pub fn read_from_slice(data: &[u8], parse_mode: ParseMode) -> PositionalResult<Self> {
+        let mut source = data;
+        let mut header = Self::read_from_reader(
+            Reader::new_buffered(&mut source),
+            parse_mode,
+            &ParseOptions::default(),
+            None,
+            None,
+        )?;
+        header.stream_byte_len = Some(data.len() as u64);
+        header.read_trailing_tags(&mut Cursor::new(data), 0)?;
+        Ok(header)
+    }
+
+    /// Detect the metadata blocks at the end of a seekable `source`
+    fn read_trailing_tags(
+        &mut self,
+        source: &mut (impl Read + Seek),
+        start_offset: u64,
+    ) -> PositionalResult<()> {
+        self.trailing_tags =
+            trailing::read_trailing_tags(source, start_offset).map_err(|e| PositionalError {
+                source: e.into(),
+                position: self.final_position.clone(),
+                context: None,
+            })?;
+        Ok(())
+    }
+
+    /// Read from a file path
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
     ///
     /// ```no_run
     /// use std::path::Path;
@@ -545,10 +2037,1615 @@ impl Header {
         File::open(path)
             .map_err(|e| PositionalError {
                 source: e.into(),
-                position: ReadPosition::new(),
+                position: ReadPosition::default(),
+                context: None,
             })
             .and_then(|file| Self::read_from_file(&file, parse_mode))
     }
+
+    /// Find all positions in a `source` that look like a valid frame header
+    ///
+    /// This is a diagnostic tool for analyzing damaged files. It reports
+    /// the byte offsets of all sync words that could be decoded into a
+    /// frame header, including those that are skipped as junk by the
+    /// regular parser. Neither frame bodies nor metadata is skipped.
+    ///
+    /// The whole stream is read byte by byte, which might be slow.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    pub fn scan_candidates(source: &mut impl Read) -> PositionalResult<Vec<(u64, FrameHeader)>> {
+        let mut reader = Reader::new(source);
+        frame::scan_candidates(&mut reader)
+    }
+
+    /// Find the boundaries of all frames within a byte range
+    ///
+    /// Only reads the bytes within `range` of a seekable `source` and
+    /// reports all frames that are fully contained in it, e.g. for
+    /// processing chunks of a huge file in parallel and stitching the
+    /// results together. The range may start in the middle of a frame.
+    /// Therefore only frames that are followed by either another frame,
+    /// a metadata block, or the end of the range are accepted, see
+    /// [`ParseOptions::require_next_sync`]. Metadata blocks between the
+    /// frames are skipped. Frames with an unknown size, i.e. free format
+    /// frames, are reported and assumed to extend up to the next frame
+    /// or the end of the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure. The position
+    /// is relative to the start of the range.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader};
+    /// use mpeg_audio_header::Header;
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let chunk = Header::scan_range_boundaries(&mut source, 0..1_000_000).unwrap();
+    /// println!("{} frames", chunk.frames.len());
+    /// ```
+    pub fn scan_range_boundaries(
+        source: &mut (impl Read + Seek),
+        range: Range<u64>,
+    ) -> PositionalResult<RangeScanResult> {
+        source
+            .seek(SeekFrom::Start(range.start))
+            .map_err(|e| PositionalError {
+                source: e.into(),
+                position: ReadPosition::default(),
+                context: None,
+            })?;
+        let mut chunk = source.take(range.end.saturating_sub(range.start));
+        let options = ParseOptions {
+            require_next_sync: true,
+            ..Default::default()
+        };
+        let mut scanner = MpegSyncScanner::new(&mut chunk, &options);
+        scanner.continue_after_metadata();
+        let mut frames = Vec::new();
+        loop {
+            let frame_header = match scanner.next_frame() {
+                Ok(Some(frame_header)) => frame_header,
+                Ok(None) => break,
+                Err(err) if err.is_unexpected_eof() => break,
+                Err(err) => return Err(err),
+            };
+            // Only the header of free format frames has been consumed
+            let consumed_size = frame_header
+                .frame_size
+                .unwrap_or(frame::FRAME_HEADER_SIZE.into());
+            let frame_offset =
+                range.start + scanner.position().byte_offset - u64::from(consumed_size);
+            frames.push((frame_offset, frame_header));
+        }
+        let scan_end = range.start + scanner.position().byte_offset;
+        let first_full_frame_offset = frames.first().map(|(frame_offset, _)| *frame_offset);
+        let last_full_frame_end = frames.last().map(|(frame_offset, frame_header)| {
+            frame_header
+                .frame_size
+                .map_or(scan_end, |frame_size| frame_offset + u64::from(frame_size))
+        });
+        let range_end = range.end.max(range.start);
+        Ok(RangeScanResult {
+            first_full_frame_offset,
+            last_full_frame_end,
+            partial_head_bytes: first_full_frame_offset.unwrap_or(range_end) - range.start,
+            partial_tail_bytes: range_end - last_full_frame_end.unwrap_or(range_end),
+            frames,
+        })
+    }
+
+    /// Average bitrate over time for plotting
+    ///
+    /// Scans all frames of a `source` with [`MpegSyncScanner`] and groups
+    /// them into consecutive time buckets of the given `resolution` by
+    /// their start time. Returns the start time of each bucket together
+    /// with the average bitrate in bits per second of all frames in this
+    /// bucket, weighted by their duration. Buckets without any frames and
+    /// frames with a free bitrate are omitted. A XING/VBRI header frame
+    /// at the start contains no audio data and is skipped.
+    ///
+    /// Requires a full scan of the whole stream. A zero `resolution` is
+    /// treated as 1 ns, i.e. a separate bucket for each frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`] on any kind of failure.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader, time::Duration};
+    /// use mpeg_audio_header::Header;
+    ///
+    /// let file = File::open("test/source.mp3").unwrap();
+    /// let mut source = BufReader::new(file);
+    /// let timeline = Header::bitrate_timeline(&mut source, Duration::from_secs(1)).unwrap();
+    /// for (start, bitrate_bps) in timeline {
+    ///     println!("{:?}: {} kbps", start, bitrate_bps / 1000);
+    /// }
+    /// ```
+    pub fn bitrate_timeline(
+        source: &mut impl Read,
+        resolution: Duration,
+    ) -> PositionalResult<Vec<(Duration, u32)>> {
+        let resolution_nanos = resolution.as_nanos().max(1);
+        let mut timeline = Vec::new();
+        // Index of the current bucket with the accumulated bits and nanoseconds
+        let mut bucket: Option<(u128, u128, u128)> = None;
+        let push_bucket = |timeline: &mut Vec<(Duration, u32)>,
+                           (index, bits, nanos): (u128, u128, u128)| {
+            let start_nanos = index * resolution_nanos;
+            let start = Duration::new(
+                (start_nanos / u128::from(NANOS_PER_SECOND)) as u64,
+                (start_nanos % u128::from(NANOS_PER_SECOND)) as u32,
+            );
+            let bitrate_bps = u32::try_from(bits / nanos).unwrap_or(u32::MAX);
+            timeline.push((start, bitrate_bps));
+        };
+        let mut scanner = MpegSyncScanner::new(source, &ParseOptions::default());
+        scanner.skip_vbr_header_frame();
+        let mut elapsed = Duration::ZERO;
+        while let Some(frame_header) = scanner.next_frame()? {
+            let frame_duration = frame_header.frame_duration();
+            let index = elapsed.as_nanos() / resolution_nanos;
+            elapsed += frame_duration;
+            let Some(bitrate_bps) = frame_header.bitrate_bps else {
+                continue;
+            };
+            let (bucket_index, bits, nanos) = match bucket {
+                Some(current) if current.0 == index => current,
+                Some(current) => {
+                    push_bucket(&mut timeline, current);
+                    (index, 0, 0)
+                }
+                None => (index, 0, 0),
+            };
+            let frame_nanos = frame_duration.as_nanos();
+            bucket = Some((
+                bucket_index,
+                bits + u128::from(bitrate_bps) * frame_nanos,
+                nanos + frame_nanos,
+            ));
+        }
+        if let Some(current) = bucket {
+            push_bucket(&mut timeline, current);
+        }
+        Ok(timeline)
+    }
+
+    /// Total duration in seconds
+    ///
+    /// Shortcut for [`Duration::as_secs_f64()`] of [`Self::total_duration`].
+    #[must_use]
+    pub fn duration_secs(&self) -> f64 {
+        self.total_duration.as_secs_f64()
+    }
+
+    /// Total duration in milliseconds
+    ///
+    /// Fractional milliseconds are preserved.
+    #[must_use]
+    pub fn duration_millis(&self) -> f64 {
+        self.total_duration.as_secs_f64() * 1000.0
+    }
+
+    /// Version, layer, and sample rate if known and consistent
+    fn consistent_profile(&self) -> Option<(Version, Layer, u16)> {
+        let (Some(version), Some(layer)) = (self.version, self.layer) else {
+            return None;
+        };
+        if self.min_sample_rate_hz == 0 || self.min_sample_rate_hz != self.max_sample_rate_hz {
+            return None;
+        }
+        Some((version, layer, self.min_sample_rate_hz))
+    }
+
+    /// Duration calculated from [`Self::declared_frame_count`]
+    ///
+    /// Not reduced by [`Self::vbri_delay`]. Requires that the version,
+    /// layer, and sample rate of the stream are known and consistent.
+    /// Otherwise `None` is returned.
+    ///
+    /// Available independent of the [`ParseMode`], unlike
+    /// [`Self::reconciled_declared_duration`].
+    #[must_use]
+    pub fn declared_duration(&self) -> Option<Duration> {
+        let declared_frame_count = self.declared_frame_count?;
+        let (version, layer, sample_rate_hz) = self.consistent_profile()?;
+        let total_sample_count =
+            u64::from(declared_frame_count) * u64::from(frame::samples_per_frame(version, layer));
+        Some(duration_from_samples(total_sample_count, sample_rate_hz))
+    }
+
+    /// Absolute difference between the declared and the measured duration
+    ///
+    /// Only available in [`ParseMode::Reconcile`] if the stream starts
+    /// with a VBR header, see [`Self::reconciled_declared_duration`] and
+    /// [`Self::measured_duration`]. A significant drift indicates that
+    /// the stream has been edited without updating the VBR header. The
+    /// measured duration is accumulated from the truncated durations of
+    /// the individual frames, i.e. expect a drift of a few nanoseconds
+    /// even for an accurate VBR header.
+    #[must_use]
+    pub fn vbr_duration_drift(&self) -> Option<Duration> {
+        Some(
+            self.reconciled_declared_duration?
+                .abs_diff(self.measured_duration?),
+        )
+    }
+
+    /// Check if the properties have been read from a XING or VBRI header
+    ///
+    /// The declared properties of the VBR header are always available,
+    /// see [`Self::declared_frame_count`] and [`Self::declared_duration()`].
+    #[must_use]
+    pub const fn from_vbr_header(&self) -> bool {
+        matches!(
+            self.source,
+            HeaderSource::XingHeader | HeaderSource::VbriHeader
+        )
+    }
+
+    /// Check if the properties have been aggregated from all MPEG frames
+    ///
+    /// In [`ParseMode::Reconcile`] the measured properties are also available
+    /// separately, see [`Self::measured_frame_count`], [`Self::measured_sample_count`],
+    /// and [`Self::measured_duration`].
+    #[must_use]
+    pub const fn from_frame_scan(&self) -> bool {
+        matches!(self.source, HeaderSource::MpegFrameHeaders)
+    }
+
+    /// Check if the stream has a variable bitrate
+    ///
+    /// Evaluated from the bitrates of the MPEG frames if at least 2 frames
+    /// with a known bitrate have been aggregated: `true` if the bitrate
+    /// varies, i.e. if [`Self::bitrate_cv`] is greater than 0, and `false`
+    /// for a constant bitrate. Otherwise falls back to [`Self::declared_vbr`]
+    /// of the VBR header.
+    ///
+    /// Returns `None` if there is insufficient evidence, e.g. for a single
+    /// frame or for free format streams without a VBR header.
+    #[must_use]
+    pub fn is_vbr(&self) -> Option<bool> {
+        if self.source == HeaderSource::MpegFrameHeaders {
+            let frame_count: u64 = self
+                .profiles
+                .iter()
+                .map(|profile| profile.frame_count)
+                .sum();
+            if let Some(bitrate_cv) = self.bitrate_cv.filter(|_| frame_count >= 2) {
+                return Some(bitrate_cv > 0.0);
+            }
+        }
+        self.declared_vbr
+    }
+
+    /// Number of unexplained bytes after the end of the audio data
+    ///
+    /// Counts all bytes between [`Self::audio_end_offset`] and the end of
+    /// a stream of `stream_byte_len` bytes that do not belong to any of
+    /// the [`Self::trailing_tags`], e.g. junk or another stream that has
+    /// been appended. Use the size of the file or slice, see
+    /// [`Self::stream_byte_len`].
+    ///
+    /// Returns `None` if the end of the audio data is unknown, if parsing
+    /// stopped early, see [`Self::partial`], or if the audio data exceeds
+    /// the stream.
+    #[must_use]
+    pub fn trailing_bytes(&self, stream_byte_len: u64) -> Option<u64> {
+        if self.partial {
+            return None;
+        }
+        let audio_end_offset = self.audio_end_offset?;
+        let trailing_byte_len = stream_byte_len.checked_sub(audio_end_offset)?;
+        let trailing_tags_byte_len: u64 = self
+            .trailing_tags
+            .iter()
+            .filter(|region| region.byte_offset >= audio_end_offset)
+            .map(|region| region.byte_len)
+            .sum();
+        Some(trailing_byte_len.saturating_sub(trailing_tags_byte_len))
+    }
+
+    /// Check if all frames are independently decodable
+    ///
+    /// `true` if the stream could be cut at any frame boundary without
+    /// losing audio data. Layer I/II frames are always independent. Layer
+    /// III frames are only independent if none of them references the bit
+    /// reservoir, see [`Self::uses_bit_reservoir`].
+    ///
+    /// Returns `None` for streams without frames or if the use of the bit
+    /// reservoir has not been detected for Layer III frames.
+    #[must_use]
+    pub fn all_frames_independent(&self) -> Option<bool> {
+        if self.profiles.is_empty() {
+            return None;
+        }
+        if self
+            .profiles
+            .iter()
+            .all(|profile| profile.layer != Layer::Layer3)
+        {
+            return Some(true);
+        }
+        self.uses_bit_reservoir
+            .map(|uses_bit_reservoir| !uses_bit_reservoir)
+    }
+
+    /// Heuristic confidence that the stream is valid MPEG audio
+    ///
+    /// Returns a score between 0 (no audio frames at all) and 100 (no
+    /// anomalies). Starting from 100 the following penalties are
+    /// subtracted:
+    ///
+    /// - Up to 40 for [`Self::alignment_errors`], i.e. resyncs, proportional
+    ///   to the number of frames. The maximum applies if 10% of all frames
+    ///   are not followed by the next frame.
+    /// - Up to 30 for [`Self::junk_byte_count`], proportional to the number
+    ///   of bytes in the stream. The maximum applies if 10% of all bytes
+    ///   are junk.
+    /// - 15 if the frames don't share a single profile, see [`Self::profiles`].
+    /// - 5 if the final frame has been truncated, see [`Self::truncated_frame`].
+    /// - 5 if a metadata block has been truncated, see [`Self::truncated_metadata`].
+    /// - 5 if the VBR header is implausible, see [`Self::vbr_header_suspect`].
+    ///
+    /// The size of the stream is [`Self::stream_byte_len`] if known and
+    /// otherwise the [`Self::final_position`].
+    #[must_use]
+    pub fn confidence(&self) -> u8 {
+        let frame_count: u64 = self
+            .profiles
+            .iter()
+            .map(|profile| profile.frame_count)
+            .sum();
+        if frame_count == 0 {
+            return 0;
+        }
+        let byte_count = self
+            .stream_byte_len
+            .unwrap_or_default()
+            .max(self.final_position.byte_offset)
+            .max(1);
+        let alignment_penalty = (self.alignment_errors.saturating_mul(400))
+            .div_ceil(frame_count)
+            .min(40);
+        let junk_penalty = (self.junk_byte_count.saturating_mul(300))
+            .div_ceil(byte_count)
+            .min(30);
+        let mut penalty = alignment_penalty + junk_penalty;
+        if self.profiles.len() > 1 {
+            penalty += 15;
+        }
+        if self.truncated_frame.is_some() {
+            penalty += 5;
+        }
+        if self.truncated_metadata.is_some() {
+            penalty += 5;
+        }
+        if self.vbr_header_suspect {
+            penalty += 5;
+        }
+        debug_assert!(penalty <= 100);
+        (100 - penalty) as u8
+    }
+
+    /// The duration of a single frame
+    ///
+    /// Requires that the version, layer, and sample rate of the
+    /// stream are known and consistent. Otherwise `None` is returned.
+    ///
+    /// See also: [`FrameHeader::frame_duration()`]
+    #[must_use]
+    pub fn nominal_frame_duration(&self) -> Option<Duration> {
+        let (version, layer, sample_rate_hz) = self.consistent_profile()?;
+        Some(duration_from_samples(
+            frame::samples_per_frame(version, layer).into(),
+            sample_rate_hz,
+        ))
+    }
+
+    /// The index of the frame that contains a sample
+    ///
+    /// The `sample` is counted per channel from the start of the stream.
+    /// Requires that the version, layer, and sample rate of the stream are
+    /// known and consistent. Otherwise `None` is returned.
+    ///
+    /// See also: [`Self::sample_for_frame_index()`]
+    #[must_use]
+    pub fn frame_index_for_sample(&self, sample: u64) -> Option<u64> {
+        let (version, layer, _) = self.consistent_profile()?;
+        Some(sample / u64::from(frame::samples_per_frame(version, layer)))
+    }
+
+    /// The first sample of a frame
+    ///
+    /// The inverse of [`Self::frame_index_for_sample()`] with the same
+    /// requirements. Returns `None` on overflow.
+    #[must_use]
+    pub fn sample_for_frame_index(&self, frame: u64) -> Option<u64> {
+        let (version, layer, _) = self.consistent_profile()?;
+        frame.checked_mul(frame::samples_per_frame(version, layer).into())
+    }
+
+    /// Check how the exact duration could be determined
+    ///
+    /// Guides clients that fetch as few bytes as possible after probing
+    /// the start of a stream, e.g. with [`ParseOptions::identify_only`]:
+    ///
+    /// - [`DurationCertainty::ExactFromFrameScan`] if all MPEG frames have
+    ///   been scanned, i.e. unless parsing stopped early, see [`Self::partial`].
+    /// - [`DurationCertainty::ExactFromVbrHeader`] if the number of frames
+    ///   is declared by a VBR header and the profile is consistent.
+    /// - [`DurationCertainty::EstimatedFromBitrate`] if all frames that have
+    ///   been read share the same bitrate and a known frame size. The
+    ///   estimate is exact unless the bitrate changes later in the stream.
+    /// - [`DurationCertainty::RequiresFullScan`] otherwise.
+    #[must_use]
+    pub fn duration_certainty(&self) -> DurationCertainty {
+        if self.from_frame_scan() && !self.partial {
+            return DurationCertainty::ExactFromFrameScan;
+        }
+        if self.declared_duration().is_some() {
+            return DurationCertainty::ExactFromVbrHeader;
+        }
+        if self.is_vbr() == Some(false)
+            && self
+                .first_frame
+                .as_ref()
+                .is_some_and(|first_frame| first_frame.frame_size.is_some())
+        {
+            return DurationCertainty::EstimatedFromBitrate;
+        }
+        DurationCertainty::RequiresFullScan
+    }
+
+    /// Duration of a CBR stream with the given size of the audio data
+    ///
+    /// Quick path for headerless CBR streams that could not be scanned
+    /// completely, e.g. if only the first frames have been read from a
+    /// non-seekable source with a known content length. The number of
+    /// frames is derived from the bitrate of [`Self::first_frame`] and exact
+    /// for CBR streams, accounting for padded frames.
+    ///
+    /// The size `audio_bytes` must only include the MPEG frames, i.e.
+    /// exclude any metadata. Returns `None` for VBR streams, see
+    /// [`Self::is_vbr()`], or for free format streams.
+    #[must_use]
+    pub fn cbr_duration_from_bytes(&self, audio_bytes: u64) -> Option<Duration> {
+        if self.is_vbr() == Some(true) {
+            return None;
+        }
+        let first_frame = self.first_frame.as_ref()?;
+        first_frame.frame_size?;
+        let bitrate_bps = first_frame.bitrate_bps?;
+        // The average frame size in bytes including padding is
+        // bitrate_bps * sample_count / (8 * sample_rate_hz). Rounded
+        // to the nearest number of frames, tolerating missing padding.
+        let numerator = u128::from(audio_bytes) * 8 * u128::from(first_frame.sample_rate_hz);
+        let denominator = u128::from(bitrate_bps) * u128::from(first_frame.sample_count);
+        let frame_count = (numerator + denominator / 2) / denominator;
+        let total_sample_count =
+            u64::try_from(frame_count * u128::from(first_frame.sample_count)).ok()?;
+        Some(duration_from_samples(
+            total_sample_count,
+            first_frame.sample_rate_hz,
+        ))
+    }
+
+    /// Exact total duration in nanoseconds as a reduced fraction
+    ///
+    /// Returns `(numerator, denominator)` of [`Self::total_sample_count`]
+    /// divided by the sample rate without any rounding, in contrast to
+    /// [`Self::total_duration`] that is truncated to whole nanoseconds.
+    ///
+    /// Requires that the sample rate is known and consistent. Otherwise,
+    /// or if the numerator exceeds `u64`, `None` is returned.
+    #[must_use]
+    pub fn exact_duration_rational(&self) -> Option<(u64, u64)> {
+        if self.min_sample_rate_hz == 0 || self.min_sample_rate_hz != self.max_sample_rate_hz {
+            return None;
+        }
+        let numerator = u128::from(self.total_sample_count) * u128::from(NANOS_PER_SECOND);
+        let denominator = u128::from(self.min_sample_rate_hz);
+        let divisor = gcd(numerator, denominator);
+        Some((
+            u64::try_from(numerator / divisor).ok()?,
+            u64::try_from(denominator / divisor).ok()?,
+        ))
+    }
+
+    /// Header of the stream without some leading and trailing frames
+    ///
+    /// Reduces the total number of samples, the total duration, the
+    /// profile, the frame counts, the measured and declared properties,
+    /// and the number of samples to skip for gapless playback by the
+    /// dropped frames. Runs of frames at the minimum bitrate are clipped
+    /// to the remaining frames.
+    ///
+    /// Byte offsets are adjusted if all frames have the same size, i.e.
+    /// for a constant bitrate without padding. Otherwise the offsets of
+    /// the first and after the last remaining frame are unknown. Frame
+    /// counts by mode, by number of channels, and with padding are only
+    /// retained if they covered all frames. The remaining properties of the VBR header and the
+    /// metadata still refer to the whole stream.
+    ///
+    /// Requires a single profile in [`Self::profiles`], i.e. a known
+    /// number of frames with a constant number of samples per frame.
+    /// Returns `None` otherwise or if more frames are dropped than
+    /// available.
+    #[must_use]
+    pub fn trimmed(&self, drop_leading_frames: u32, drop_trailing_frames: u32) -> Option<Header> {
+        let [profile] = self.profiles.as_slice() else {
+            return None;
+        };
+        let samples_per_frame = u64::from(frame::samples_per_frame(profile.version, profile.layer));
+        let drop_frames = u64::from(drop_leading_frames) + u64::from(drop_trailing_frames);
+        let frame_count = profile.frame_count.checked_sub(drop_frames)?;
+        let total_sample_count = frame_count * samples_per_frame;
+        let total_duration = duration_from_samples(total_sample_count, profile.sample_rate_hz);
+        let drop_leading_samples = u64::from(drop_leading_frames) * samples_per_frame;
+        let drop_trailing_samples = u64::from(drop_trailing_frames) * samples_per_frame;
+        let drop_duration =
+            duration_from_samples(drop_frames * samples_per_frame, profile.sample_rate_hz);
+        let frame_duration = duration_from_samples(samples_per_frame, profile.sample_rate_hz);
+        let reduce_skip_samples = |skip_samples: Option<u32>, drop_samples: u64| {
+            skip_samples
+                .map(|skip_samples| u64::from(skip_samples).saturating_sub(drop_samples) as u32)
+        };
+
+        // Byte offsets could only be calculated for frames of equal size
+        let constant_frame_size = self
+            .first_frame
+            .as_ref()
+            .and_then(|first_frame| first_frame.frame_size)
+            .filter(|_| self.bitrate_cv == Some(0.0) && self.padding_frame_count == 0)
+            .map(u64::from);
+        let audio_end_offset = if drop_trailing_frames == 0 {
+            self.audio_end_offset
+        } else {
+            self.audio_end_offset
+                .zip(constant_frame_size)
+                .and_then(|(end_offset, frame_size)| {
+                    end_offset.checked_sub(u64::from(drop_trailing_frames) * frame_size)
+                })
+        };
+        let first_frame_offset = if drop_leading_frames == 0 {
+            self.first_frame_offset
+        } else {
+            audio_end_offset
+                .zip(constant_frame_size)
+                .and_then(|(end_offset, frame_size)| {
+                    end_offset.checked_sub(frame_count * frame_size)
+                })
+        };
+        let final_position = ReadPosition {
+            byte_offset: if drop_trailing_frames == 0 {
+                self.final_position.byte_offset
+            } else {
+                audio_end_offset.unwrap_or(self.final_position.byte_offset)
+            },
+            // Accumulated frame by frame while reading
+            duration: self.final_position.duration.saturating_sub(
+                frame_duration * drop_leading_frames + frame_duration * drop_trailing_frames,
+            ),
+        };
+
+        // Frame counts are only retained if they covered all frames
+        let reduce_frame_count = |count: u64| {
+            if count == profile.frame_count {
+                frame_count
+            } else {
+                0
+            }
+        };
+
+        // Clip the runs to the remaining samples
+        let min_bitrate_runs = self
+            .min_bitrate_runs
+            .iter()
+            .filter_map(|(sample_offset, sample_count)| {
+                let start = (*sample_offset).max(drop_leading_samples);
+                let end =
+                    (sample_offset + sample_count).min(drop_leading_samples + total_sample_count);
+                (start < end).then(|| (start - drop_leading_samples, end - start))
+            })
+            .collect();
+
+        Some(Header {
+            total_sample_count,
+            total_duration,
+            reconciled_declared_duration: self
+                .reconciled_declared_duration
+                .map(|duration| duration.saturating_sub(drop_duration)),
+            measured_duration: self.measured_duration.map(|_| total_duration),
+            measured_frame_count: self.measured_frame_count.map(|_| frame_count),
+            measured_sample_count: self.measured_sample_count.map(|_| total_sample_count),
+            declared_frame_count: self.declared_frame_count.map(|declared_frame_count| {
+                u64::from(declared_frame_count).saturating_sub(drop_frames) as u32
+            }),
+            start_skip_samples: reduce_skip_samples(self.start_skip_samples, drop_leading_samples),
+            end_skip_samples: reduce_skip_samples(self.end_skip_samples, drop_trailing_samples),
+            profiles: vec![ProfileSummary {
+                frame_count,
+                total_samples: total_sample_count,
+                ..profile.clone()
+            }],
+            mono_frame_count: reduce_frame_count(self.mono_frame_count),
+            stereo_frame_count: reduce_frame_count(self.stereo_frame_count),
+            padding_frame_count: reduce_frame_count(self.padding_frame_count),
+            mode_frame_counts: self.mode_frame_counts.map(reduce_frame_count),
+            min_bitrate_runs,
+            first_frame_offset,
+            audio_end_offset,
+            final_position,
+            ..self.clone()
+        })
+    }
+
+    /// Total number of samples and duration
+    ///
+    /// Combines [`Self::total_sample_count`] and [`Self::total_duration`]
+    /// with the sample rate that relates them if known and consistent.
+    #[must_use]
+    pub fn length(&self) -> StreamLength {
+        let sample_rate_basis =
+            Some(self.min_sample_rate_hz).filter(|hz| *hz > 0 && *hz == self.max_sample_rate_hz);
+        StreamLength {
+            samples: self.total_sample_count,
+            duration: self.total_duration,
+            sample_rate_basis,
+        }
+    }
+
+    /// Minimum sample rate
+    ///
+    /// Typed variant of [`Self::min_sample_rate_hz`] or `None` if unknown.
+    #[must_use]
+    pub const fn min_sample_rate(&self) -> Option<SampleRate> {
+        if self.min_sample_rate_hz == 0 {
+            return None;
+        }
+        Some(SampleRate::from_hz(self.min_sample_rate_hz))
+    }
+
+    /// Maximum sample rate
+    ///
+    /// Typed variant of [`Self::max_sample_rate_hz`] or `None` if unknown.
+    #[must_use]
+    pub const fn max_sample_rate(&self) -> Option<SampleRate> {
+        if self.max_sample_rate_hz == 0 {
+            return None;
+        }
+        Some(SampleRate::from_hz(self.max_sample_rate_hz))
+    }
+
+    /// Recommended size of the input buffer for decoding in bytes
+    ///
+    /// Calculated as `max_frame_size + max_bit_reservoir_size` with
+    ///
+    /// - `max_frame_size`: The aggregated [`Self::max_frame_size`] if
+    ///   available. Otherwise the theoretical maximum frame size at the
+    ///   highest bitrate for the version, layer, and minimum sample rate,
+    ///   including the padding byte.
+    /// - `max_bit_reservoir_size`: The maximum number of bytes from preceding
+    ///   frames that could be referenced by `main_data_begin`, i.e. 511 bytes
+    ///   for MPEG-1 Layer III and 255 bytes for MPEG-2/2.5 Layer III. Frames
+    ///   of Layer I/II are independent and don't need a bit reservoir.
+    ///
+    /// If either the version or the layer is unknown the worst case for
+    /// all possible values is assumed.
+    #[must_use]
+    pub fn recommended_input_buffer_size(&self) -> usize {
+        let versions = self.version.as_ref().map_or(
+            &[Version::Mpeg1, Version::Mpeg2, Version::Mpeg25][..],
+            std::slice::from_ref,
+        );
+        let layers = self.layer.as_ref().map_or(
+            &[Layer::Layer1, Layer::Layer2, Layer::Layer3][..],
+            std::slice::from_ref,
+        );
+        let mut max_frame_size = 0;
+        let mut max_bit_reservoir_size = 0;
+        for version in versions {
+            for layer in layers {
+                max_frame_size = max_frame_size.max(self.max_frame_size.unwrap_or_else(|| {
+                    // Frames are bigger for lower sample rates. The minimum sample
+                    // rate of the stream is not valid for all versions.
+                    frame::max_frame_size(*version, *layer, self.min_sample_rate_hz)
+                        .or_else(|| {
+                            frame::max_frame_size(
+                                *version,
+                                *layer,
+                                frame::min_sample_rate_hz(*version),
+                            )
+                        })
+                        .unwrap_or_default()
+                }));
+                max_bit_reservoir_size =
+                    max_bit_reservoir_size.max(frame::max_bit_reservoir_size(*version, *layer));
+            }
+        }
+        usize::from(max_frame_size) + usize::from(max_bit_reservoir_size)
+    }
+
+    /// Check if both headers describe the same audio stream
+    ///
+    /// Only the properties of the audio stream are compared, i.e. version,
+    /// layer, mode, channel counts, sample rates, and the total length.
+    /// All other fields that depend on how and from which source the
+    /// metadata has been obtained are ignored.
+    ///
+    /// Two files containing the same recording but different ID3 or APE
+    /// tags are considered as equivalent.
+    #[must_use]
+    pub fn audio_equivalent(&self, other: &Header) -> bool {
+        let Self {
+            source: _,
+            version,
+            layer,
+            mode,
+            channel_semantics,
+            emphasis: _,
+            common_flags: _,
+            min_channel_count,
+            max_channel_count,
+            program_count,
+            mode_frame_counts: _,
+            mono_frame_count: _,
+            stereo_frame_count: _,
+            padding_frame_count: _,
+            possible_multichannel: _,
+            multichannel_count: _,
+            min_sample_rate_hz,
+            max_sample_rate_hz,
+            total_sample_count,
+            samples_per_frame: _,
+            total_duration,
+            reconciled_declared_duration: _,
+            measured_duration: _,
+            measured_frame_count: _,
+            measured_sample_count: _,
+            avg_sample_rate_hz: _,
+            max_frame_size: _,
+            avg_bitrate_bps: _,
+            avg_bitrate_kbps: _,
+            bitrate_cv: _,
+            uses_bit_reservoir: _,
+            bitrate_monotonic: _,
+            truncated_metadata: _,
+            truncated_frame: _,
+            alignment_errors: _,
+            junk_byte_count: _,
+            partial: _,
+            stopped_at: _,
+            read_error: _,
+            final_position: _,
+            stream_byte_len: _,
+            raw_vbr_header: _,
+            declared_frame_count: _,
+            declared_vbr: _,
+            vbr_header_suspect: _,
+            cbr_claim_violated: _,
+            vbr_frame_samples: _,
+            vbri_toc: _,
+            vbri_delay: _,
+            lame_info: _,
+            start_skip_samples: _,
+            end_skip_samples: _,
+            min_bitrate_runs: _,
+            lead_in_confidence: _,
+            first_frame_offset: _,
+            audio_end_offset: _,
+            profiles: _,
+            mllt_seek_points: _,
+            apev2_info: _,
+            trailing_tags: _,
+            diagnostics: _,
+            first_frame: _,
+            #[cfg(feature = "id3-lite")]
+                basic_tags: _,
+        } = self;
+        *version == other.version
+            && *layer == other.layer
+            && *mode == other.mode
+            && *channel_semantics == other.channel_semantics
+            && *min_channel_count == other.min_channel_count
+            && *max_channel_count == other.max_channel_count
+            && *program_count == other.program_count
+            && *min_sample_rate_hz == other.min_sample_rate_hz
+            && *max_sample_rate_hz == other.max_sample_rate_hz
+            && *total_sample_count == other.total_sample_count
+            && *total_duration == other.total_duration
+    }
+
+    /// Flat list of key/value pairs of all populated fields
+    ///
+    /// Intended for generic tooling that renders the properties of
+    /// a stream uniformly, e.g. as a table. The keys are the names of
+    /// the fields, nested fields are joined with a dot. Collections
+    /// are summarized by their number of elements and enums are
+    /// formatted with [`Debug`](std::fmt::Debug). Fields that are
+    /// `None` or empty are omitted.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn to_fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            source,
+            version,
+            layer,
+            mode,
+            channel_semantics,
+            emphasis,
+            common_flags,
+            min_channel_count,
+            max_channel_count,
+            program_count,
+            mode_frame_counts,
+            mono_frame_count,
+            stereo_frame_count,
+            padding_frame_count,
+            possible_multichannel,
+            multichannel_count,
+            min_sample_rate_hz,
+            max_sample_rate_hz,
+            total_sample_count,
+            samples_per_frame,
+            total_duration,
+            reconciled_declared_duration,
+            measured_duration,
+            measured_frame_count,
+            measured_sample_count,
+            avg_sample_rate_hz,
+            max_frame_size,
+            avg_bitrate_bps,
+            avg_bitrate_kbps,
+            bitrate_cv,
+            uses_bit_reservoir,
+            bitrate_monotonic,
+            truncated_metadata,
+            truncated_frame,
+            alignment_errors,
+            junk_byte_count,
+            partial,
+            stopped_at,
+            read_error,
+            final_position,
+            stream_byte_len,
+            raw_vbr_header,
+            declared_frame_count,
+            declared_vbr,
+            vbri_toc,
+            vbri_delay,
+            vbr_header_suspect,
+            cbr_claim_violated,
+            vbr_frame_samples,
+            lame_info,
+            start_skip_samples,
+            end_skip_samples,
+            min_bitrate_runs,
+            lead_in_confidence,
+            first_frame_offset,
+            audio_end_offset,
+            profiles,
+            mllt_seek_points,
+            apev2_info,
+            trailing_tags,
+            diagnostics,
+            #[cfg(feature = "id3-lite")]
+            basic_tags,
+            first_frame,
+        } = self;
+        let mut fields = Fields::default();
+        fields.push_debug("source", Some(source));
+        fields.push_debug("version", version.as_ref());
+        fields.push_debug("layer", layer.as_ref());
+        fields.push_debug("mode", mode.as_ref());
+        fields.push_debug("channel_semantics", channel_semantics.as_ref());
+        fields.push_debug("emphasis", emphasis.as_ref());
+        let FrameFlags {
+            protection,
+            padding,
+            private,
+            copyright,
+            original,
+        } = common_flags;
+        fields.push("common_flags.protection", *protection);
+        fields.push("common_flags.padding", *padding);
+        fields.push("common_flags.private", *private);
+        fields.push("common_flags.copyright", *copyright);
+        fields.push("common_flags.original", *original);
+        fields.push("min_channel_count", Some(min_channel_count));
+        fields.push("max_channel_count", Some(max_channel_count));
+        fields.push("program_count", Some(program_count));
+        fields.push_debug("mode_frame_counts", Some(mode_frame_counts));
+        fields.push("mono_frame_count", Some(mono_frame_count));
+        fields.push("stereo_frame_count", Some(stereo_frame_count));
+        fields.push("padding_frame_count", Some(padding_frame_count));
+        fields.push("possible_multichannel", Some(possible_multichannel));
+        fields.push("multichannel_count", multichannel_count.as_ref());
+        fields.push("min_sample_rate_hz", Some(min_sample_rate_hz));
+        fields.push("max_sample_rate_hz", Some(max_sample_rate_hz));
+        fields.push("total_sample_count", Some(total_sample_count));
+        fields.push("samples_per_frame", samples_per_frame.as_ref());
+        fields.push_debug("total_duration", Some(total_duration));
+        fields.push_debug(
+            "reconciled_declared_duration",
+            reconciled_declared_duration.as_ref(),
+        );
+        fields.push_debug("measured_duration", measured_duration.as_ref());
+        fields.push("measured_frame_count", measured_frame_count.as_ref());
+        fields.push("measured_sample_count", measured_sample_count.as_ref());
+        fields.push("avg_sample_rate_hz", avg_sample_rate_hz.as_ref());
+        fields.push("max_frame_size", max_frame_size.as_ref());
+        fields.push("avg_bitrate_bps", avg_bitrate_bps.as_ref());
+        fields.push("avg_bitrate_kbps", avg_bitrate_kbps.as_ref());
+        fields.push("bitrate_cv", bitrate_cv.as_ref());
+        fields.push("uses_bit_reservoir", uses_bit_reservoir.as_ref());
+        fields.push("bitrate_monotonic", bitrate_monotonic.as_ref());
+        fields.push("truncated_metadata", truncated_metadata.as_ref());
+        fields.push("truncated_frame", truncated_frame.as_ref());
+        fields.push("alignment_errors", Some(alignment_errors));
+        fields.push("junk_byte_count", Some(junk_byte_count));
+        fields.push("partial", Some(partial));
+        fields.push("stopped_at", stopped_at.as_ref());
+        if let Some(ReadError {
+            byte_offset,
+            kind,
+            message,
+        }) = read_error
+        {
+            fields.push("read_error.byte_offset", Some(byte_offset));
+            fields.push_debug("read_error.kind", Some(&kind));
+            fields.push("read_error.message", Some(message));
+        }
+        fields.push(
+            "final_position.byte_offset",
+            Some(final_position.byte_offset),
+        );
+        fields.push_debug("final_position.duration", Some(&final_position.duration));
+        fields.push("stream_byte_len", stream_byte_len.as_ref());
+        fields.push("raw_vbr_header.len", raw_vbr_header.as_ref().map(Vec::len));
+        fields.push("declared_frame_count", declared_frame_count.as_ref());
+        fields.push("declared_vbr", declared_vbr.as_ref());
+        if let Some(VbriToc {
+            scale,
+            frames_per_entry,
+            entries,
+        }) = vbri_toc
+        {
+            fields.push("vbri_toc.scale", Some(scale));
+            fields.push("vbri_toc.frames_per_entry", Some(frames_per_entry));
+            fields.push("vbri_toc.entries.len", Some(entries.len()));
+        }
+        fields.push("vbri_delay", vbri_delay.as_ref());
+        fields.push("vbr_header_suspect", Some(vbr_header_suspect));
+        fields.push("cbr_claim_violated", Some(cbr_claim_violated));
+        fields.push("vbr_frame_samples", vbr_frame_samples.as_ref());
+        if let Some(LameInfo {
+            encoder_version,
+            encoder_delay,
+            encoder_padding,
+            preset,
+        }) = lame_info
+        {
+            fields.push("lame_info.encoder_version", Some(encoder_version));
+            fields.push("lame_info.encoder_delay", Some(encoder_delay));
+            fields.push("lame_info.encoder_padding", Some(encoder_padding));
+            fields.push_debug("lame_info.preset", preset.as_ref());
+        }
+        fields.push("start_skip_samples", start_skip_samples.as_ref());
+        fields.push("end_skip_samples", end_skip_samples.as_ref());
+        fields.push(
+            "min_bitrate_runs.len",
+            Some(min_bitrate_runs.len()).filter(|len| *len > 0),
+        );
+        fields.push("lead_in_confidence", Some(lead_in_confidence));
+        fields.push("first_frame_offset", first_frame_offset.as_ref());
+        fields.push("audio_end_offset", audio_end_offset.as_ref());
+        fields.push("profiles.len", Some(profiles.len()).filter(|len| *len > 0));
+        fields.push(
+            "mllt_seek_points.len",
+            mllt_seek_points.as_ref().map(Vec::len),
+        );
+        if let Some(ApeV2Info {
+            version,
+            item_count,
+            flags,
+            size,
+        }) = apev2_info
+        {
+            fields.push("apev2_info.version", Some(version));
+            fields.push("apev2_info.item_count", Some(item_count));
+            fields.push("apev2_info.flags", Some(flags));
+            fields.push("apev2_info.size", Some(size));
+        }
+        fields.push(
+            "trailing_tags.len",
+            Some(trailing_tags.len()).filter(|len| *len > 0),
+        );
+        fields.push(
+            "diagnostics.len",
+            Some(diagnostics.len()).filter(|len| *len > 0),
+        );
+        #[cfg(feature = "id3-lite")]
+        if let Some(BasicTags { title, artist }) = basic_tags {
+            fields.push("basic_tags.title", title.as_ref());
+            fields.push("basic_tags.artist", artist.as_ref());
+        }
+        if let Some(first_frame) = first_frame {
+            fields.push_debug("first_frame.version", Some(&first_frame.version));
+            fields.push_debug("first_frame.layer", Some(&first_frame.layer));
+            fields.push_debug("first_frame.mode", Some(&first_frame.mode));
+            fields.push(
+                "first_frame.sample_rate_hz",
+                Some(first_frame.sample_rate_hz),
+            );
+            fields.push("first_frame.bitrate_bps", first_frame.bitrate_bps.as_ref());
+            fields.push("first_frame.frame_size", first_frame.frame_size.as_ref());
+        }
+        fields.0
+    }
+
+    /// Combine the headers of two contiguous parts of a stream
+    ///
+    /// Both headers must have been aggregated from MPEG frame headers
+    /// and `other` must continue directly after `self`, i.e. the stream
+    /// must have been split on a frame boundary at the final position
+    /// of `self`. All byte offsets, sample offsets, and durations of
+    /// `other` are relative to the split point and shifted accordingly.
+    ///
+    /// Properties are only consistent if they are consistent in both
+    /// parts. Averages are re-derived, weighted by the number of samples.
+    /// Properties that are only available at the start of the stream,
+    /// e.g. the VBR and LAME headers, are taken from `self`.
+    ///
+    /// Runs of frames at the minimum bitrate are concatenated, joining
+    /// the runs at the split point. They are only accurate if the
+    /// minimum bitrate is the same in both parts.
+    ///
+    /// The bitrate is only known to not be monotonic if it oscillates in
+    /// either part. Otherwise [`Self::bitrate_monotonic`] is unknown,
+    /// because the direction of the bitrate might reverse at the split
+    /// point.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn merge(self, other: Header) -> Header {
+        let stream_byte_len = self
+            .stream_byte_len
+            .zip(other.stream_byte_len)
+            .map(|(lhs, rhs)| lhs + rhs);
+        if other.total_sample_count == 0 && other.profiles.is_empty() {
+            // Nothing to merge
+            return Header {
+                stream_byte_len,
+                truncated_metadata: other
+                    .truncated_metadata
+                    .map(|offset| self.final_position.byte_offset + offset)
+                    .or(self.truncated_metadata),
+                truncated_frame: other
+                    .truncated_frame
+                    .map(|offset| self.final_position.byte_offset + offset)
+                    .or(self.truncated_frame),
+                alignment_errors: self.alignment_errors + other.alignment_errors,
+                partial: self.partial || other.partial,
+                final_position: ReadPosition {
+                    byte_offset: self.final_position.byte_offset + other.final_position.byte_offset,
+                    duration: self.final_position.duration,
+                },
+                apev2_info: self.apev2_info.or(other.apev2_info),
+                trailing_tags: shift_metadata_regions(
+                    other.trailing_tags,
+                    self.final_position.byte_offset,
+                ),
+                diagnostics: concat_diagnostics(
+                    self.diagnostics,
+                    other.diagnostics,
+                    self.final_position.byte_offset,
+                ),
+                junk_byte_count: self.junk_byte_count + other.junk_byte_count,
+                stopped_at: other
+                    .stopped_at
+                    .map(|offset| self.final_position.byte_offset + offset)
+                    .or(self.stopped_at),
+                read_error: other
+                    .read_error
+                    .map(|read_error| read_error.shifted(self.final_position.byte_offset))
+                    .or(self.read_error),
+                audio_end_offset: other
+                    .audio_end_offset
+                    .map(|offset| self.final_position.byte_offset + offset)
+                    .or(self.audio_end_offset),
+                ..self
+            };
+        }
+        if self.total_sample_count == 0 && self.profiles.is_empty() {
+            let byte_offset = self.final_position.byte_offset;
+            return Header {
+                source: self.source,
+                truncated_metadata: other
+                    .truncated_metadata
+                    .map(|offset| byte_offset + offset)
+                    .or(self.truncated_metadata),
+                truncated_frame: other
+                    .truncated_frame
+                    .map(|offset| byte_offset + offset)
+                    .or(self.truncated_frame),
+                alignment_errors: self.alignment_errors + other.alignment_errors,
+                partial: self.partial || other.partial,
+                stopped_at: other
+                    .stopped_at
+                    .map(|offset| byte_offset + offset)
+                    .or(self.stopped_at),
+                read_error: other
+                    .read_error
+                    .map(|read_error| read_error.shifted(byte_offset))
+                    .or(self.read_error),
+                junk_byte_count: self.junk_byte_count + other.junk_byte_count,
+                final_position: ReadPosition {
+                    byte_offset: byte_offset + other.final_position.byte_offset,
+                    duration: other.final_position.duration,
+                },
+                stream_byte_len,
+                raw_vbr_header: self.raw_vbr_header,
+                declared_frame_count: self.declared_frame_count,
+                declared_vbr: self.declared_vbr,
+                vbri_toc: self.vbri_toc,
+                vbri_delay: self.vbri_delay,
+                vbr_header_suspect: self.vbr_header_suspect,
+                cbr_claim_violated: self.cbr_claim_violated,
+                vbr_frame_samples: self.vbr_frame_samples,
+                lame_info: self.lame_info,
+                start_skip_samples: self.start_skip_samples,
+                end_skip_samples: self.end_skip_samples,
+                lead_in_confidence: self.lead_in_confidence.max(other.lead_in_confidence),
+                first_frame_offset: self
+                    .first_frame_offset
+                    .or(other.first_frame_offset.map(|offset| byte_offset + offset)),
+                audio_end_offset: other
+                    .audio_end_offset
+                    .map(|offset| byte_offset + offset)
+                    .or(self.audio_end_offset),
+                mllt_seek_points: self.mllt_seek_points,
+                apev2_info: self.apev2_info.or(other.apev2_info),
+                trailing_tags: shift_metadata_regions(other.trailing_tags, byte_offset),
+                diagnostics: concat_diagnostics(self.diagnostics, other.diagnostics, byte_offset),
+                #[cfg(feature = "id3-lite")]
+                basic_tags: self.basic_tags,
+                ..other
+            };
+        }
+        let Self {
+            source,
+            version,
+            layer,
+            mode,
+            channel_semantics,
+            emphasis,
+            common_flags,
+            min_channel_count,
+            max_channel_count,
+            program_count: _,
+            mode_frame_counts,
+            mono_frame_count,
+            stereo_frame_count,
+            padding_frame_count,
+            possible_multichannel,
+            multichannel_count,
+            min_sample_rate_hz,
+            max_sample_rate_hz,
+            total_sample_count,
+            samples_per_frame: _,
+            total_duration,
+            reconciled_declared_duration,
+            measured_duration,
+            measured_frame_count,
+            measured_sample_count,
+            avg_sample_rate_hz,
+            max_frame_size,
+            avg_bitrate_bps,
+            avg_bitrate_kbps: _,
+            bitrate_cv,
+            uses_bit_reservoir,
+            bitrate_monotonic,
+            truncated_metadata,
+            truncated_frame,
+            alignment_errors,
+            junk_byte_count,
+            partial,
+            stopped_at,
+            read_error,
+            final_position,
+            stream_byte_len: _,
+            raw_vbr_header,
+            declared_frame_count,
+            declared_vbr,
+            vbri_toc,
+            vbri_delay,
+            vbr_header_suspect,
+            cbr_claim_violated,
+            vbr_frame_samples,
+            lame_info,
+            start_skip_samples,
+            end_skip_samples,
+            mut min_bitrate_runs,
+            lead_in_confidence,
+            first_frame_offset,
+            audio_end_offset,
+            mut profiles,
+            mllt_seek_points,
+            apev2_info,
+            trailing_tags: _,
+            diagnostics,
+            first_frame,
+            #[cfg(feature = "id3-lite")]
+            basic_tags,
+        } = self;
+        let byte_offset = final_position.byte_offset;
+        let frame_count: u64 = profiles.iter().map(|profile| profile.frame_count).sum();
+
+        let version = merge_consistent(version, other.version);
+        let layer = merge_consistent(layer, other.layer);
+        let mode = merge_consistent(mode, other.mode);
+        let channel_semantics = merge_consistent(channel_semantics, other.channel_semantics);
+        let emphasis = merge_consistent(emphasis, other.emphasis);
+        let common_flags = FrameFlags {
+            protection: merge_consistent(common_flags.protection, other.common_flags.protection),
+            padding: merge_consistent(common_flags.padding, other.common_flags.padding),
+            private: merge_consistent(common_flags.private, other.common_flags.private),
+            copyright: merge_consistent(common_flags.copyright, other.common_flags.copyright),
+            original: merge_consistent(common_flags.original, other.common_flags.original),
+        };
+        let program_count = channel_semantics.map_or(1, ChannelSemantics::program_count);
+
+        let mut min_channel_count = min_channel_count;
+        aggregate_min(&mut min_channel_count, other.min_channel_count);
+        let mut min_sample_rate_hz = min_sample_rate_hz;
+        aggregate_min(&mut min_sample_rate_hz, other.min_sample_rate_hz);
+
+        let mut mode_frame_counts = mode_frame_counts;
+        for (count, other_count) in mode_frame_counts.iter_mut().zip(other.mode_frame_counts) {
+            *count += other_count;
+        }
+
+        let avg_sample_rate_hz = weighted_avg(
+            avg_sample_rate_hz.map(Into::into),
+            total_sample_count,
+            other.avg_sample_rate_hz.map(Into::into),
+            other.total_sample_count,
+        )
+        .map(|avg_sample_rate_hz| avg_sample_rate_hz as u16);
+        let bitrate_cv = merge_bitrate_cv(
+            (avg_bitrate_bps, bitrate_cv, total_sample_count),
+            (
+                other.avg_bitrate_bps,
+                other.bitrate_cv,
+                other.total_sample_count,
+            ),
+        );
+        let avg_bitrate_bps = weighted_avg(
+            avg_bitrate_bps,
+            total_sample_count,
+            other.avg_bitrate_bps,
+            other.total_sample_count,
+        );
+
+        if let (Some((last_offset, last_count)), Some(&(0, first_count))) =
+            (min_bitrate_runs.last_mut(), other.min_bitrate_runs.first())
+        {
+            if *last_offset + *last_count == total_sample_count {
+                // Join the runs at the split point
+                *last_count += first_count;
+            } else {
+                min_bitrate_runs.push((total_sample_count, first_count));
+            }
+            min_bitrate_runs.extend(
+                other.min_bitrate_runs[1..]
+                    .iter()
+                    .map(|(offset, count)| (total_sample_count + offset, *count)),
+            );
+        } else {
+            min_bitrate_runs.extend(
+                other
+                    .min_bitrate_runs
+                    .iter()
+                    .map(|(offset, count)| (total_sample_count + offset, *count)),
+            );
+        }
+
+        for other_profile in other.profiles {
+            if let Some(profile) = profiles.iter_mut().find(|profile| {
+                profile.version == other_profile.version
+                    && profile.layer == other_profile.layer
+                    && profile.sample_rate_hz == other_profile.sample_rate_hz
+            }) {
+                profile.frame_count += other_profile.frame_count;
+                profile.total_samples += other_profile.total_samples;
+            } else {
+                profiles.push(other_profile);
+            }
+        }
+
+        Header {
+            source: if source == other.source {
+                source
+            } else {
+                HeaderSource::MpegFrameHeaders
+            },
+            version,
+            layer,
+            mode,
+            channel_semantics,
+            emphasis,
+            common_flags,
+            min_channel_count,
+            max_channel_count: max_channel_count.max(other.max_channel_count),
+            program_count,
+            mode_frame_counts,
+            mono_frame_count: mono_frame_count + other.mono_frame_count,
+            stereo_frame_count: stereo_frame_count + other.stereo_frame_count,
+            padding_frame_count: padding_frame_count + other.padding_frame_count,
+            possible_multichannel: possible_multichannel && other.possible_multichannel,
+            multichannel_count: merge_consistent(multichannel_count, other.multichannel_count),
+            min_sample_rate_hz,
+            max_sample_rate_hz: max_sample_rate_hz.max(other.max_sample_rate_hz),
+            total_sample_count: total_sample_count + other.total_sample_count,
+            samples_per_frame: version
+                .zip(layer)
+                .map(|(version, layer)| frame::samples_per_frame(version, layer)),
+            total_duration: total_duration + other.total_duration,
+            reconciled_declared_duration,
+            measured_duration: measured_duration
+                .zip(other.measured_duration)
+                .map(|(lhs, rhs)| lhs + rhs),
+            measured_frame_count: measured_frame_count
+                .zip(other.measured_frame_count)
+                .map(|(lhs, rhs)| lhs + rhs),
+            measured_sample_count: measured_sample_count
+                .zip(other.measured_sample_count)
+                .map(|(lhs, rhs)| lhs + rhs),
+            avg_sample_rate_hz,
+            max_frame_size: max_frame_size.max(other.max_frame_size),
+            avg_bitrate_bps,
+            // Round to the nearest kbps
+            avg_bitrate_kbps: avg_bitrate_bps.map(|bps| ((bps + 500) / 1000) as u16),
+            bitrate_cv,
+            uses_bit_reservoir: match (uses_bit_reservoir, other.uses_bit_reservoir) {
+                (Some(lhs), Some(rhs)) => Some(lhs || rhs),
+                (lhs, rhs) => lhs.or(rhs),
+            },
+            bitrate_monotonic: match (bitrate_monotonic, other.bitrate_monotonic) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                // The direction might reverse at the split point
+                _ => None,
+            },
+            truncated_metadata: other
+                .truncated_metadata
+                .map(|offset| byte_offset + offset)
+                .or(truncated_metadata),
+            truncated_frame: other
+                .truncated_frame
+                .map(|offset| byte_offset + offset)
+                .or(truncated_frame),
+            alignment_errors: alignment_errors + other.alignment_errors,
+            junk_byte_count: junk_byte_count + other.junk_byte_count,
+            partial: partial || other.partial,
+            stopped_at: other
+                .stopped_at
+                .map(|offset| byte_offset + offset)
+                .or(stopped_at),
+            read_error: other
+                .read_error
+                .map(|read_error| read_error.shifted(byte_offset))
+                .or(read_error),
+            final_position: ReadPosition {
+                byte_offset: byte_offset + other.final_position.byte_offset,
+                duration: final_position.duration + other.final_position.duration,
+            },
+            stream_byte_len,
+            raw_vbr_header,
+            declared_frame_count,
+            declared_vbr,
+            vbri_toc,
+            vbri_delay,
+            vbr_header_suspect,
+            cbr_claim_violated: cbr_claim_violated || other.cbr_claim_violated,
+            vbr_frame_samples,
+            lame_info,
+            start_skip_samples,
+            end_skip_samples,
+            min_bitrate_runs,
+            lead_in_confidence: if u64::from(lead_in_confidence) == frame_count {
+                // Consecutive frames continue after the split point
+                lead_in_confidence + other.lead_in_confidence
+            } else {
+                lead_in_confidence
+            },
+            first_frame_offset: first_frame_offset
+                .or(other.first_frame_offset.map(|offset| byte_offset + offset)),
+            audio_end_offset: other
+                .audio_end_offset
+                .map(|offset| byte_offset + offset)
+                .or(audio_end_offset),
+            profiles,
+            mllt_seek_points,
+            apev2_info: apev2_info.or(other.apev2_info),
+            trailing_tags: shift_metadata_regions(other.trailing_tags, byte_offset),
+            diagnostics: concat_diagnostics(diagnostics, other.diagnostics, byte_offset),
+            first_frame: first_frame.or(other.first_frame),
+            #[cfg(feature = "id3-lite")]
+            basic_tags,
+        }
+    }
+}
+
+/// Check if the size and frame count of a VBR header are consistent
+///
+/// The average bitrate must be within the valid range and the frame
+/// count must match the number of frames estimated from the size and
+/// the size of the first audio frame, if known. Both the range and the
+/// estimate are extended by the given tolerance in percent.
+fn is_plausible_vbr_header(
+    frame_header: &FrameHeader,
+    total_frames: u32,
+    total_bytes: u32,
+    first_frame_size: Option<u16>,
+    tolerance_percent: u8,
+) -> bool {
+    let (min_bitrate_bps, max_bitrate_bps) =
+        frame::bitrate_bps_range(frame_header.version, frame_header.layer);
+    let tolerance_percent = u64::from(tolerance_percent);
+    let min_bitrate_bps =
+        u64::from(min_bitrate_bps) * 100_u64.saturating_sub(tolerance_percent) / 100;
+    let max_bitrate_bps = u64::from(max_bitrate_bps) * (100 + tolerance_percent) / 100;
+    let total_sample_count = u64::from(total_frames) * u64::from(frame_header.sample_count);
+    let avg_bitrate_bps =
+        u64::from(total_bytes) * 8 * u64::from(frame_header.sample_rate_hz) / total_sample_count;
+    if !(min_bitrate_bps..=max_bitrate_bps).contains(&avg_bitrate_bps) {
+        return false;
+    }
+    let Some(first_frame_size) = first_frame_size.filter(|frame_size| *frame_size > 0) else {
+        return true;
+    };
+    let estimated_frames = u64::from(total_bytes) / u64::from(first_frame_size);
+    let max_deviation = estimated_frames * tolerance_percent / 100;
+    u64::from(total_frames).abs_diff(estimated_frames) <= max_deviation
+}
+
+/// Greatest common divisor
+fn gcd(mut lhs: u128, mut rhs: u128) -> u128 {
+    while rhs != 0 {
+        (lhs, rhs) = (rhs, lhs % rhs);
+    }
+    lhs
+}
+
+/// Key/value pairs collected by [`Header::to_fields()`]
+#[derive(Default)]
+struct Fields(Vec<(&'static str, String)>);
+
+impl Fields {
+    fn push(&mut self, key: &'static str, value: Option<impl ToString>) {
+        if let Some(value) = value {
+            self.0.push((key, value.to_string()));
+        }
+    }
+
+    fn push_debug(&mut self, key: &'static str, value: Option<&impl std::fmt::Debug>) {
+        if let Some(value) = value {
+            self.0.push((key, format!("{value:?}")));
+        }
+    }
+}
+
+/// Merge two properties that are only defined if consistent
+fn merge_consistent<T: Copy + PartialEq>(lhs: Option<T>, rhs: Option<T>) -> Option<T> {
+    if lhs == rhs {
+        lhs
+    } else {
+        None
+    }
+}
+
+/// Shift the offsets of the metadata regions of the second part of a stream
+fn shift_metadata_regions(regions: Vec<MetadataRegion>, byte_offset: u64) -> Vec<MetadataRegion> {
+    regions
+        .into_iter()
+        .map(|region| MetadataRegion {
+            byte_offset: byte_offset + region.byte_offset,
+            ..region
+        })
+        .collect()
+}
+
+/// Append the diagnostics of the second part of a stream
+fn concat_diagnostics(
+    mut diagnostics: Vec<Diagnostic>,
+    other: Vec<Diagnostic>,
+    byte_offset: u64,
+) -> Vec<Diagnostic> {
+    diagnostics.extend(
+        other
+            .into_iter()
+            .map(|diagnostic| diagnostic.shifted(byte_offset)),
+    );
+    diagnostics
+}
+
+/// Average of two averages weighted by the number of samples
+fn weighted_avg(
+    lhs: Option<u32>,
+    lhs_sample_count: u64,
+    rhs: Option<u32>,
+    rhs_sample_count: u64,
+) -> Option<u32> {
+    if lhs.is_none() && rhs.is_none() {
+        return None;
+    }
+    let accmul = u128::from(lhs.unwrap_or_default()) * u128::from(lhs_sample_count)
+        + u128::from(rhs.unwrap_or_default()) * u128::from(rhs_sample_count);
+    accmul
+        .checked_div(u128::from(lhs_sample_count) + u128::from(rhs_sample_count))
+        .map(|avg| avg as u32)
+}
+
+/// Pool the coefficients of variation of two parts
+///
+/// Each part is given as `(avg_bitrate_bps, bitrate_cv, sample_count)`.
+#[allow(clippy::cast_precision_loss)]
+fn merge_bitrate_cv(
+    lhs: (Option<u32>, Option<f64>, u64),
+    rhs: (Option<u32>, Option<f64>, u64),
+) -> Option<f64> {
+    let (Some(lhs_mean), Some(lhs_cv), lhs_sample_count) = lhs else {
+        return rhs.1;
+    };
+    let (Some(rhs_mean), Some(rhs_cv), rhs_sample_count) = rhs else {
+        return Some(lhs_cv);
+    };
+    if lhs_cv == 0.0 && rhs_cv == 0.0 && lhs_mean == rhs_mean {
+        // Avoid rounding errors for constant bitrates
+        return Some(0.0);
+    }
+    let lhs_mean = f64::from(lhs_mean);
+    let rhs_mean = f64::from(rhs_mean);
+    // Mean of squares = (cv^2 + 1) * mean^2
+    let lhs_mean_of_squares = (lhs_cv * lhs_cv + 1.0) * lhs_mean * lhs_mean;
+    let rhs_mean_of_squares = (rhs_cv * rhs_cv + 1.0) * rhs_mean * rhs_mean;
+    let lhs_weight = lhs_sample_count as f64;
+    let rhs_weight = rhs_sample_count as f64;
+    let sum_weight = lhs_weight + rhs_weight;
+    let mean = (lhs_mean * lhs_weight + rhs_mean * rhs_weight) / sum_weight;
+    let mean_of_squares =
+        (lhs_mean_of_squares * lhs_weight + rhs_mean_of_squares * rhs_weight) / sum_weight;
+    // Rounding errors might result in a slightly negative variance
+    let variance = (mean_of_squares - mean * mean).max(0.0);
+    Some(variance.sqrt() / mean)
+}
+
+/// Finish reading the VBR header frame
+///
+/// The frame size of XING/VBRI frames is not accounted for while
+/// parsing the VBR header.
+fn finish_vbr_header_frame<R: Read>(
+    reader: &mut Reader<'_, R>,
+    frame_header: &FrameHeader,
+    frame_start_offset: u64,
+) -> PositionalResult<()> {
+    if let Some(frame_size) = frame_header.frame_size {
+        let frame_end_offset = frame_start_offset + u64::from(frame_size);
+        let byte_offset = reader.position().byte_offset;
+        if frame_end_offset > byte_offset {
+            reader.try_skip_exact_until_eof(frame_end_offset - byte_offset)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
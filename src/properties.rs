@@ -0,0 +1,47 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::time::Duration;
+
+use crate::Header;
+
+/// Basic properties of an audio stream
+///
+/// A minimal common interface for abstracting over different kinds of
+/// audio metadata. Each property is reduced to a single representative
+/// value.
+pub trait AudioProperties {
+    /// Total duration
+    fn duration(&self) -> Duration;
+
+    /// Sample rate in Hz
+    fn sample_rate(&self) -> Option<u32>;
+
+    /// Number of channels
+    fn channels(&self) -> Option<u8>;
+
+    /// Bitrate in bits/sec
+    fn bitrate(&self) -> Option<u32>;
+}
+
+impl AudioProperties for Header {
+    /// [`Header::total_duration`]
+    fn duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    /// [`Header::avg_sample_rate_hz`]
+    fn sample_rate(&self) -> Option<u32> {
+        self.avg_sample_rate_hz.map(Into::into)
+    }
+
+    /// [`Header::max_channel_count`] or `None` if unknown
+    fn channels(&self) -> Option<u8> {
+        (self.max_channel_count > 0).then_some(self.max_channel_count)
+    }
+
+    /// [`Header::avg_bitrate_bps`]
+    fn bitrate(&self) -> Option<u32> {
+        self.avg_bitrate_bps
+    }
+}
@@ -2,30 +2,30 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use std::{
+    collections::VecDeque,
     io::{self, prelude::*},
     time::Duration,
 };
 
 use crate::{
-    error::{Error, PositionalError},
+    apev2::ApeV2Info,
+    error::{Error, PositionalError, ReadError},
+    id3v2::MlltSeekPoints,
     PositionalResult,
 };
 
+#[cfg(feature = "id3-lite")]
+use crate::id3v2::BasicTags;
+
 /// Position within a readable source
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadPosition {
     pub(crate) byte_offset: u64,
     pub(crate) duration: Duration,
 }
 
 impl ReadPosition {
-    pub(crate) const fn new() -> Self {
-        Self {
-            byte_offset: 0,
-            duration: Duration::ZERO,
-        }
-    }
-
     /// The number of bytes that have been consumed
     #[must_use]
     pub const fn byte_offset(&self) -> u64 {
@@ -39,27 +39,177 @@ impl ReadPosition {
     }
 }
 
+/// Consume up to the given number of bytes from the buffer of a source
+///
+/// Returns the number of bytes that have been consumed, 0 at EOF.
+type ConsumeInPlace<T> = fn(&mut T, u64) -> io::Result<u64>;
+
+fn consume_in_place<T: BufRead>(source: &mut T, max_bytes: u64) -> io::Result<u64> {
+    let num_bytes = (source.fill_buf()?.len() as u64).min(max_bytes);
+    source.consume(num_bytes as usize);
+    Ok(num_bytes)
+}
+
+#[derive(Debug)]
 pub(crate) struct Reader<'r, T> {
-    reader: &'r mut T,
+    source: &'r mut T,
+    consume_in_place: Option<ConsumeInPlace<T>>,
     position: ReadPosition,
+    lookahead: VecDeque<u8>,
+    recording: Option<Vec<u8>>,
+    truncated_metadata_offset: Option<u64>,
+    stopped_at_metadata_offset: Option<u64>,
+    metadata_byte_count: u64,
+    max_bytes: Option<u64>,
+    end_offset: Option<u64>,
+    tolerate_io_errors: bool,
+    continue_after_metadata: bool,
+    read_error: Option<ReadError>,
+    mllt_seek_points: Option<MlltSeekPoints>,
+    apev2_info: Option<ApeV2Info>,
+    #[cfg(feature = "id3-lite")]
+    basic_tags: Option<BasicTags>,
+}
+
+impl<'r, T: BufRead> Reader<'r, T> {
+    /// Read from a buffered source
+    ///
+    /// Skipped bytes are consumed from the buffer of the source without
+    /// copying them, e.g. by advancing a slice.
+    #[must_use]
+    pub(crate) fn new_buffered(source: &'r mut T) -> Self {
+        let mut reader = Self::new(source);
+        reader.consume_in_place = Some(consume_in_place::<T>);
+        reader
+    }
 }
 
 impl<'r, T: Read> Reader<'r, T> {
     #[must_use]
-    pub(crate) fn new(reader: &'r mut T) -> Self {
+    pub(crate) fn new(source: &'r mut T) -> Self {
         Reader {
-            reader,
-            position: ReadPosition::new(),
+            source,
+            consume_in_place: None,
+            position: ReadPosition::default(),
+            lookahead: VecDeque::new(),
+            recording: None,
+            truncated_metadata_offset: None,
+            stopped_at_metadata_offset: None,
+            metadata_byte_count: 0,
+            max_bytes: None,
+            end_offset: None,
+            tolerate_io_errors: false,
+            continue_after_metadata: false,
+            read_error: None,
+            mllt_seek_points: None,
+            apev2_info: None,
+            #[cfg(feature = "id3-lite")]
+            basic_tags: None,
+        }
+    }
+
+    /// Limit the total number of bytes that could be consumed
+    ///
+    /// Includes skipped bytes. Reading beyond the limit fails with
+    /// [`Error::LimitExceeded`].
+    pub(crate) fn set_max_bytes(&mut self, max_bytes: Option<u64>) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// Pretend that the stream ends at the given byte offset
+    pub(crate) fn set_end_offset(&mut self, end_offset: Option<u64>) {
+        self.end_offset = end_offset;
+    }
+
+    /// Pretend that the stream ends before the first I/O error
+    ///
+    /// The error is remembered instead of being returned.
+    pub(crate) fn tolerate_io_errors(&mut self) {
+        self.tolerate_io_errors = true;
+    }
+
+    /// Continue searching for MPEG frames after a trailing metadata block
+    ///
+    /// By default the stream ends at the first metadata block that
+    /// follows an MPEG frame.
+    pub(crate) fn continue_after_metadata(&mut self) {
+        self.continue_after_metadata = true;
+    }
+
+    pub(crate) const fn continues_after_metadata(&self) -> bool {
+        self.continue_after_metadata
+    }
+
+    /// The tolerated I/O error
+    pub(crate) fn take_read_error(&mut self) -> Option<ReadError> {
+        self.read_error.take()
+    }
+
+    /// Pretend that the stream ends at `byte_offset` if I/O errors are tolerated
+    ///
+    /// Returns `false` if the error must be returned.
+    fn end_at_io_error(&mut self, err: &io::Error, byte_offset: u64) -> bool {
+        if !self.tolerate_io_errors {
+            return false;
+        }
+        self.read_error = Some(ReadError {
+            byte_offset,
+            kind: err.kind(),
+            message: err.to_string(),
+        });
+        self.end_offset = Some(byte_offset);
+        true
+    }
+
+    /// The number of bytes until the end offset is reached
+    fn bytes_until_end(&self) -> Option<u64> {
+        self.end_offset
+            .map(|end_offset| end_offset.saturating_sub(self.position.byte_offset))
+    }
+
+    fn limit_exceeded(&self, num_bytes: u64) -> Option<u64> {
+        self.max_bytes
+            .filter(|max_bytes| self.position.byte_offset + num_bytes > *max_bytes)
+    }
+
+    /// Fail if the limit has been reached and more bytes are available
+    ///
+    /// Requests are clamped to the limit. The limit is only exceeded if
+    /// bytes beyond the limit would actually be consumed, i.e. a stream
+    /// that ends exactly at the limit is accepted.
+    fn check_limit_reached(&mut self, max_bytes: u64) -> PositionalResult<()> {
+        debug_assert!(self.position.byte_offset >= max_bytes);
+        let mut next_byte = [0u8; 1];
+        if self.read_until_eof(&mut next_byte)? > 0 {
+            self.unread(&next_byte);
+            return Err(self.positional_error(Error::LimitExceeded(max_bytes)));
+        }
+        Ok(())
+    }
+
+    /// Consume buffered bytes from the lookahead buffer first
+    fn read_lookahead(&mut self, buffer: &mut [u8]) -> usize {
+        let num_bytes = self.lookahead.len().min(buffer.len());
+        for (dst, src) in buffer.iter_mut().zip(self.lookahead.drain(..num_bytes)) {
+            *dst = src;
         }
+        num_bytes
     }
 
     fn read_exact(&mut self, buffer: &mut [u8]) -> PositionalResult<()> {
-        self.reader
-            .read_exact(buffer)
-            .map(|()| {
-                self.position.byte_offset += buffer.len() as u64;
-            })
-            .map_err(|e| self.positional_error(e.into()))
+        if self
+            .bytes_until_end()
+            .is_some_and(|num_bytes| (buffer.len() as u64) > num_bytes)
+        {
+            return Err(self.positional_error(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+        }
+        // Loop on short reads instead of using `Read::read_exact()` that
+        // leaves the number of consumed bytes unspecified on failure
+        let num_bytes_read = self.try_read_until_eof(buffer)?;
+        if num_bytes_read < buffer.len() {
+            return Err(self.positional_error(io::Error::from(io::ErrorKind::UnexpectedEof).into()));
+        }
+        Ok(())
     }
 
     pub(crate) fn try_read_exact_until_eof(&mut self, buffer: &mut [u8]) -> PositionalResult<bool> {
@@ -72,15 +222,148 @@ impl<'r, T: Read> Reader<'r, T> {
         })
     }
 
+    /// Read as many bytes as available until the buffer is full or EOF is reached
+    ///
+    /// Returns the number of bytes that have been read.
+    pub(crate) fn try_read_until_eof(&mut self, buffer: &mut [u8]) -> PositionalResult<usize> {
+        let buffer = match self.bytes_until_end() {
+            Some(num_bytes) if (buffer.len() as u64) > num_bytes => {
+                &mut buffer[..num_bytes as usize]
+            }
+            _ => buffer,
+        };
+        if let Some(max_bytes) = self.limit_exceeded(buffer.len() as u64) {
+            // Only fail if the limit is actually reached before EOF
+            let remaining_bytes = max_bytes.saturating_sub(self.position.byte_offset);
+            let num_bytes_read = self.read_until_eof(&mut buffer[..remaining_bytes as usize])?;
+            if num_bytes_read as u64 == remaining_bytes {
+                self.check_limit_reached(max_bytes)?;
+            }
+            return Ok(num_bytes_read);
+        }
+        self.read_until_eof(buffer)
+    }
+
+    fn read_until_eof(&mut self, buffer: &mut [u8]) -> PositionalResult<usize> {
+        let mut num_bytes_read = self.read_lookahead(buffer);
+        while num_bytes_read < buffer.len() {
+            match self.source.read(&mut buffer[num_bytes_read..]) {
+                Ok(0) => break,
+                Ok(num_bytes) => num_bytes_read += num_bytes,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    let byte_offset = self.position.byte_offset + num_bytes_read as u64;
+                    if self.end_at_io_error(&err, byte_offset) {
+                        break;
+                    }
+                    self.position.byte_offset = byte_offset;
+                    return Err(self.positional_error(err.into()));
+                }
+            }
+        }
+        self.position.byte_offset += num_bytes_read as u64;
+        if let Some(recording) = &mut self.recording {
+            recording.extend_from_slice(&buffer[..num_bytes_read]);
+        }
+        Ok(num_bytes_read)
+    }
+
+    /// Push back bytes that have just been read
+    ///
+    /// The bytes will be read again before reading more bytes from the source.
+    pub(crate) fn unread(&mut self, bytes: &[u8]) {
+        debug_assert!(bytes.len() as u64 <= self.position.byte_offset);
+        for byte in bytes.iter().rev() {
+            self.lookahead.push_front(*byte);
+        }
+        self.position.byte_offset -= bytes.len() as u64;
+        if let Some(recording) = &mut self.recording {
+            recording.truncate(recording.len().saturating_sub(bytes.len()));
+        }
+    }
+
+    /// Start recording all consumed bytes, including skipped bytes
+    ///
+    /// The recording is initialized with the given bytes that have
+    /// already been consumed.
+    pub(crate) fn start_recording(&mut self, consumed_bytes: &[u8]) {
+        self.recording = Some(consumed_bytes.to_vec());
+    }
+
+    /// Stop recording and return all recorded bytes
+    pub(crate) fn finish_recording(&mut self) -> Option<Vec<u8>> {
+        self.recording.take()
+    }
+
     fn skip(&mut self, max_bytes: u64) -> PositionalResult<u64> {
-        match io::copy(&mut self.reader.take(max_bytes), &mut io::sink()) {
-            Err(e) => Err(self.positional_error(e.into())),
-            Ok(num_bytes_skipped) => {
-                debug_assert!(num_bytes_skipped <= max_bytes);
-                self.position.byte_offset += num_bytes_skipped;
-                Ok(num_bytes_skipped)
+        const CHUNK_SIZE: usize = 8192;
+        let max_bytes = self
+            .bytes_until_end()
+            .map_or(max_bytes, |num_bytes| num_bytes.min(max_bytes));
+        if let Some(limit) = self.limit_exceeded(max_bytes) {
+            let remaining_bytes = limit.saturating_sub(self.position.byte_offset);
+            let num_bytes_skipped = self.skip(remaining_bytes)?;
+            if num_bytes_skipped == remaining_bytes {
+                self.check_limit_reached(limit)?;
+            }
+            return Ok(num_bytes_skipped);
+        }
+        let num_buffered_bytes = (self.lookahead.len() as u64).min(max_bytes);
+        let buffered_bytes = self.lookahead.drain(..num_buffered_bytes as usize);
+        if let Some(recording) = &mut self.recording {
+            recording.extend(buffered_bytes);
+        } else {
+            drop(buffered_bytes);
+        }
+        self.position.byte_offset += num_buffered_bytes;
+        let mut num_bytes_skipped = num_buffered_bytes;
+        // Recorded bytes need to be copied
+        if let Some(consume_in_place) = self.consume_in_place.filter(|_| self.recording.is_none()) {
+            while num_bytes_skipped < max_bytes {
+                match consume_in_place(self.source, max_bytes - num_bytes_skipped) {
+                    Ok(0) => break,
+                    Ok(num_bytes) => {
+                        self.position.byte_offset += num_bytes;
+                        num_bytes_skipped += num_bytes;
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        if self.end_at_io_error(&err, self.position.byte_offset) {
+                            break;
+                        }
+                        return Err(self.positional_error(err.into()));
+                    }
+                }
             }
+            debug_assert!(num_bytes_skipped <= max_bytes);
+            return Ok(num_bytes_skipped);
         }
+        // Account for every chunk immediately to keep the position accurate
+        // if reading fails. A single read might return fewer bytes than
+        // requested, e.g. at the boundary of chained sources.
+        let mut chunk = [0u8; CHUNK_SIZE];
+        while num_bytes_skipped < max_bytes {
+            let chunk_size = (max_bytes - num_bytes_skipped).min(CHUNK_SIZE as u64) as usize;
+            match self.source.read(&mut chunk[..chunk_size]) {
+                Ok(0) => break,
+                Ok(num_bytes) => {
+                    if let Some(recording) = &mut self.recording {
+                        recording.extend_from_slice(&chunk[..num_bytes]);
+                    }
+                    self.position.byte_offset += num_bytes as u64;
+                    num_bytes_skipped += num_bytes as u64;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    if self.end_at_io_error(&err, self.position.byte_offset) {
+                        break;
+                    }
+                    return Err(self.positional_error(err.into()));
+                }
+            }
+        }
+        debug_assert!(num_bytes_skipped <= max_bytes);
+        Ok(num_bytes_skipped)
     }
 
     pub(crate) fn try_skip_exact_until_eof(&mut self, num_bytes: u64) -> PositionalResult<bool> {
@@ -99,6 +382,47 @@ impl<'r, T: Read> Reader<'r, T> {
         }
     }
 
+    /// Skip all consecutive zero bytes
+    ///
+    /// Reads ahead in growing chunks instead of byte by byte and pushes back all
+    /// bytes after the first nonzero byte. Stops at EOF or right before
+    /// exceeding the limit.
+    ///
+    /// Returns the number of skipped bytes.
+    pub(crate) fn skip_zero_bytes(&mut self) -> PositionalResult<u64> {
+        const CHUNK_SIZE: usize = 4096;
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let mut num_bytes_skipped = 0;
+        // Start with a single byte and double the chunk size while only
+        // zero bytes are found. Usually there are no zero bytes at all
+        // and reading ahead a whole chunk would be wasted.
+        let mut max_chunk_size = 1;
+        loop {
+            let chunk_size = self.max_bytes.map_or(max_chunk_size, |max_bytes| {
+                max_bytes
+                    .saturating_sub(self.position.byte_offset)
+                    .min(max_chunk_size as u64) as usize
+            });
+            if chunk_size == 0 {
+                break;
+            }
+            let num_bytes_read = self.try_read_until_eof(&mut chunk[..chunk_size])?;
+            let bytes_read = &chunk[..num_bytes_read];
+            let num_zero_bytes = bytes_read
+                .iter()
+                .position(|byte| *byte != 0)
+                .unwrap_or(num_bytes_read);
+            self.unread(&bytes_read[num_zero_bytes..]);
+            num_bytes_skipped += num_zero_bytes as u64;
+            if num_zero_bytes < chunk_size {
+                // Either a nonzero byte or EOF has been reached
+                break;
+            }
+            max_chunk_size = (max_chunk_size * 2).min(CHUNK_SIZE);
+        }
+        Ok(num_bytes_skipped)
+    }
+
     #[must_use]
     pub(crate) fn position(&self) -> &ReadPosition {
         &self.position
@@ -108,12 +432,175 @@ impl<'r, T: Read> Reader<'r, T> {
         self.position.duration += duration;
     }
 
+    /// The byte offset of the first truncated metadata block
+    #[must_use]
+    pub(crate) fn truncated_metadata_offset(&self) -> Option<u64> {
+        self.truncated_metadata_offset
+    }
+
+    /// Remember that the metadata block starting at `byte_offset`
+    /// has been truncated by the end of the stream
+    pub(crate) fn mark_truncated_metadata(&mut self, byte_offset: u64) {
+        if self.truncated_metadata_offset.is_none() {
+            self.truncated_metadata_offset = Some(byte_offset);
+        }
+    }
+
+    /// The byte offset of the metadata block at which reading stopped
+    #[must_use]
+    pub(crate) fn stopped_at_metadata_offset(&self) -> Option<u64> {
+        self.stopped_at_metadata_offset
+    }
+
+    /// Remember that reading stopped at the metadata block that
+    /// starts at the current position
+    pub(crate) fn mark_stopped_at_metadata(&mut self) {
+        self.stopped_at_metadata_offset = Some(self.position.byte_offset);
+    }
+
+    /// Total number of bytes of all metadata blocks that have been skipped
+    pub(crate) const fn metadata_byte_count(&self) -> u64 {
+        self.metadata_byte_count
+    }
+
+    /// Account for a metadata block that has been skipped
+    pub(crate) fn add_metadata_bytes(&mut self, num_bytes: u64) {
+        self.metadata_byte_count += num_bytes;
+    }
+
+    /// Remember the seek points of the first MLLT frame
+    pub(crate) fn set_mllt_seek_points(&mut self, mllt_seek_points: MlltSeekPoints) {
+        if self.mllt_seek_points.is_none() {
+            self.mllt_seek_points = Some(mllt_seek_points);
+        }
+    }
+
+    pub(crate) fn take_mllt_seek_points(&mut self) -> Option<MlltSeekPoints> {
+        self.mllt_seek_points.take()
+    }
+
+    /// Remember the header or footer of the first APE tag
+    pub(crate) fn set_apev2_info(&mut self, apev2_info: ApeV2Info) {
+        if self.apev2_info.is_none() {
+            self.apev2_info = Some(apev2_info);
+        }
+    }
+
+    pub(crate) fn take_apev2_info(&mut self) -> Option<ApeV2Info> {
+        self.apev2_info.take()
+    }
+
+    /// Remember the title and artist of the first tag
+    #[cfg(feature = "id3-lite")]
+    pub(crate) fn set_basic_tags(&mut self, basic_tags: BasicTags) {
+        if self.basic_tags.is_none() {
+            self.basic_tags = Some(basic_tags);
+        }
+    }
+
+    #[cfg(feature = "id3-lite")]
+    pub(crate) fn take_basic_tags(&mut self) -> Option<BasicTags> {
+        self.basic_tags.take()
+    }
+
     #[must_use]
     pub(crate) fn positional_error(&self, source: Error) -> PositionalError {
         let Self { position, .. } = self;
         PositionalError {
             source,
             position: position.clone(),
+            context: None,
+        }
+    }
+}
+
+/// Swaps the bytes of each 16-bit word of the underlying source
+pub(crate) struct SwappedBytes<'r, T> {
+    source: &'r mut T,
+    /// The second byte of a swapped word that has not been consumed yet
+    pending_byte: Option<u8>,
+    /// The first byte of a word that could not be completed due to an error
+    odd_byte: Option<u8>,
+}
+
+impl<'r, T> SwappedBytes<'r, T> {
+    #[must_use]
+    pub(crate) fn new(source: &'r mut T) -> Self {
+        Self {
+            source,
+            pending_byte: None,
+            odd_byte: None,
+        }
+    }
+}
+
+/// Read once and retry if interrupted
+fn read_uninterrupted(source: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match source.read(buf) {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            res => return res,
+        }
+    }
+}
+
+impl<T: Read> Read for SwappedBytes<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if let Some(odd_byte) = self.odd_byte {
+            // Complete the word that has been interrupted by an error
+            let mut next_byte = [0u8; 1];
+            if read_uninterrupted(self.source, &mut next_byte)? > 0 {
+                buf[0] = next_byte[0];
+                self.pending_byte = Some(odd_byte);
+            } else {
+                // A trailing odd byte could not be swapped
+                buf[0] = odd_byte;
+            }
+            self.odd_byte = None;
+            return Ok(1);
+        }
+        let mut num_bytes = 0;
+        if let Some(pending_byte) = self.pending_byte.take() {
+            buf[0] = pending_byte;
+            num_bytes = 1;
+        }
+        // Fill the remaining buffer with whole words
+        let num_bytes_read = match read_uninterrupted(self.source, &mut buf[num_bytes..]) {
+            Ok(num_bytes_read) => num_bytes_read,
+            Err(err) => {
+                self.pending_byte = (num_bytes > 0).then_some(buf[0]);
+                return Err(err);
+            }
+        };
+        let mut words = buf[num_bytes..num_bytes + num_bytes_read].chunks_exact_mut(2);
+        num_bytes += num_bytes_read;
+        for word in &mut words {
+            word.swap(0, 1);
+        }
+        if let [odd_byte] = words.into_remainder() {
+            // Complete the last word with the next byte
+            let mut next_byte = [0u8; 1];
+            match read_uninterrupted(self.source, &mut next_byte) {
+                Ok(0) => {
+                    // A trailing odd byte could not be swapped
+                }
+                Ok(_) => {
+                    self.pending_byte = Some(*odd_byte);
+                    *odd_byte = next_byte[0];
+                }
+                Err(err) => {
+                    // Defer the odd byte and report the error if nothing else has been read
+                    self.odd_byte = Some(*odd_byte);
+                    num_bytes -= 1;
+                    if num_bytes == 0 {
+                        return Err(err);
+                    }
+                }
+            }
         }
+        Ok(num_bytes)
     }
 }
@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! MPEG audio wrapped in a RIFF/WAVE or AIFF-C container
+//!
+//! The MPEG frames are stored in the `data` chunk of RIFF/WAVE or
+//! in the `SSND` chunk of AIFF-C. All other chunks are skipped.
+
+use std::io::Read;
+
+use crate::{error::Error, reader::Reader, PositionalResult};
+
+const CHUNK_HEADER_SIZE: usize = 8;
+
+const WAVE_FORMAT_MPEG: u16 = 0x0050;
+const WAVE_FORMAT_MPEGLAYER3: u16 = 0x0055;
+
+/// Chunk size of streams with an unknown length
+const UNKNOWN_CHUNK_SIZE: u32 = u32::MAX;
+
+/// Compression types of MPEG audio in the `COMM` chunk of AIFF-C
+///
+/// Either the types defined by Apple or the WAVE format tags prefixed with `ms`.
+const AIFC_COMPRESSION_TYPES_MPEG: [&[u8; 4]; 5] =
+    [b".mp1", b".mp2", b".mp3", b"ms\x00\x50", b"ms\x00\x55"];
+
+/// Size of the `COMM` chunk of AIFF-C up to and including the compression type
+const AIFC_COMM_CHUNK_MIN_SIZE: u32 = 22;
+
+/// Size of the offset and block size fields of the `SSND` chunk
+const AIFC_SSND_HEADER_SIZE: u32 = 8;
+
+/// Skip all chunks preceding the audio data of a RIFF/WAVE or AIFF-C container
+///
+/// Nothing is consumed if the stream does not start with a RIFF or FORM header.
+///
+/// Returns the end offset of the `data` or `SSND` chunk if known.
+pub(crate) fn skip_to_data_chunk<R: Read>(
+    reader: &mut Reader<'_, R>,
+) -> PositionalResult<Option<u64>> {
+    let mut riff_header = [0; 12];
+    let num_bytes_read = reader.try_read_until_eof(&mut riff_header)?;
+    if num_bytes_read < riff_header.len() || !matches!(&riff_header[..4], b"RIFF" | b"FORM") {
+        reader.unread(&riff_header[..num_bytes_read]);
+        return Ok(None);
+    }
+    if &riff_header[..4] == b"FORM" {
+        return skip_to_sound_data_chunk(reader, &riff_header[8..]);
+    }
+    if &riff_header[8..] != b"WAVE" {
+        return Err(reader.positional_error(Error::UnsupportedFormat(format!(
+            "RIFF form type {}",
+            String::from_utf8_lossy(&riff_header[8..])
+        ))));
+    }
+    loop {
+        let mut chunk_header = [0; CHUNK_HEADER_SIZE];
+        if !reader.try_read_exact_until_eof(&mut chunk_header)? {
+            // No data chunk
+            return Ok(Some(reader.position().byte_offset));
+        }
+        let chunk_size = u32::from_le_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]);
+        match &chunk_header[..4] {
+            b"data" => {
+                if chunk_size == UNKNOWN_CHUNK_SIZE {
+                    return Ok(None);
+                }
+                return Ok(Some(reader.position().byte_offset + u64::from(chunk_size)));
+            }
+            b"fmt " if chunk_size >= 2 => {
+                let mut format_tag = [0; 2];
+                if !reader.try_read_exact_until_eof(&mut format_tag)? {
+                    return Ok(Some(reader.position().byte_offset));
+                }
+                let format_tag = u16::from_le_bytes(format_tag);
+                if !matches!(format_tag, WAVE_FORMAT_MPEG | WAVE_FORMAT_MPEGLAYER3) {
+                    return Err(reader.positional_error(Error::UnsupportedFormat(format!(
+                        "WAVE format tag 0x{format_tag:04X}"
+                    ))));
+                }
+                skip_chunk_body(reader, chunk_size - 2)?;
+            }
+            _ => {
+                skip_chunk_body(reader, chunk_size)?;
+            }
+        }
+    }
+}
+
+/// Skip all chunks preceding the sound data of an AIFF-C container
+///
+/// The FORM header has already been consumed.
+fn skip_to_sound_data_chunk<R: Read>(
+    reader: &mut Reader<'_, R>,
+    form_type: &[u8],
+) -> PositionalResult<Option<u64>> {
+    if form_type != b"AIFC" {
+        return Err(reader.positional_error(Error::UnsupportedFormat(format!(
+            "FORM type {}",
+            String::from_utf8_lossy(form_type)
+        ))));
+    }
+    loop {
+        let mut chunk_header = [0; CHUNK_HEADER_SIZE];
+        if !reader.try_read_exact_until_eof(&mut chunk_header)? {
+            // No sound data chunk
+            return Ok(Some(reader.position().byte_offset));
+        }
+        let chunk_size = u32::from_be_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]);
+        match &chunk_header[..4] {
+            b"SSND" if chunk_size >= AIFC_SSND_HEADER_SIZE => {
+                let mut ssnd_header = [0; AIFC_SSND_HEADER_SIZE as usize];
+                if !reader.try_read_exact_until_eof(&mut ssnd_header)? {
+                    return Ok(Some(reader.position().byte_offset));
+                }
+                let data_end_offset =
+                    reader.position().byte_offset + u64::from(chunk_size - AIFC_SSND_HEADER_SIZE);
+                // The audio data starts after an offset that is usually 0
+                let offset = u32::from_be_bytes([
+                    ssnd_header[0],
+                    ssnd_header[1],
+                    ssnd_header[2],
+                    ssnd_header[3],
+                ]);
+                reader.try_skip_exact_until_eof(offset.into())?;
+                return Ok(Some(data_end_offset));
+            }
+            b"COMM" if chunk_size >= AIFC_COMM_CHUNK_MIN_SIZE => {
+                let mut comm = [0; AIFC_COMM_CHUNK_MIN_SIZE as usize];
+                if !reader.try_read_exact_until_eof(&mut comm)? {
+                    return Ok(Some(reader.position().byte_offset));
+                }
+                let compression_type = &comm[18..];
+                if !AIFC_COMPRESSION_TYPES_MPEG
+                    .iter()
+                    .any(|mpeg| mpeg.as_slice() == compression_type)
+                {
+                    return Err(reader.positional_error(Error::UnsupportedFormat(format!(
+                        "AIFF-C compression type {}",
+                        String::from_utf8_lossy(compression_type)
+                    ))));
+                }
+                skip_chunk_body(reader, chunk_size - AIFC_COMM_CHUNK_MIN_SIZE)?;
+            }
+            _ => {
+                skip_chunk_body(reader, chunk_size)?;
+            }
+        }
+    }
+}
+
+/// Skip the remaining bytes of a chunk including the padding byte
+fn skip_chunk_body<R: Read>(reader: &mut Reader<'_, R>, num_bytes: u32) -> PositionalResult<()> {
+    // Chunks are aligned at 16-bit boundaries
+    let padded_num_bytes = u64::from(num_bytes) + u64::from(num_bytes & 1);
+    reader.try_skip_exact_until_eof(padded_num_bytes)?;
+    Ok(())
+}
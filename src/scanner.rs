@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+use std::io::Read;
+
+use crate::{
+    frame::{self, SyncStep},
+    reader::Reader,
+    riff, FrameHeader, ParseOptions, PositionalResult, ReadPosition,
+};
+
+/// Iterates over the MPEG frames of a stream
+///
+/// Finds the sync word of each frame and skips all metadata blocks
+/// (ID3/APE tags) and junk in between, like [`crate::Header::read_from_source()`]
+/// does, but without any aggregation. The body of each frame is skipped.
+///
+/// XING/VBRI header frames are not treated specially and reported like
+/// all other frames. The frame headers could be fed into an
+/// [`crate::AggregationState`] for deriving the properties of the stream.
+///
+/// The search for the first frame respects [`ParseOptions::min_lead_in_frames`].
+/// Only [`ByteOrder::Normal`](crate::ByteOrder::Normal) is supported, i.e.
+/// [`ParseOptions::byte_order`] is ignored.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{fs::File, io::BufReader};
+/// use mpeg_audio_header::{AggregationState, MpegSyncScanner, ParseOptions};
+///
+/// let file = File::open("test/source.mp3").unwrap();
+/// let mut source = BufReader::new(file);
+/// let mut scanner = MpegSyncScanner::new(&mut source, &ParseOptions::default());
+/// let mut aggregation = AggregationState::new();
+/// while let Some(frame_header) = scanner.next_frame().unwrap() {
+///     aggregation.feed_frame(&frame_header);
+/// }
+/// println!("MPEG audio header: {:?}", aggregation.finalize());
+/// ```
+#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct MpegSyncScanner<'r, R> {
+    reader: Reader<'r, R>,
+    options: ParseOptions,
+    started: bool,
+    lead_in_accepted: bool,
+    skip_vbr_header_frame: bool,
+    finished: bool,
+    frame_count: u64,
+    truncated_frame: Option<u64>,
+}
+
+impl<'r, R: Read> MpegSyncScanner<'r, R> {
+    /// Scan the given source
+    #[must_use]
+    pub fn new(source: &'r mut R, options: &ParseOptions) -> Self {
+        let mut reader = Reader::new(source);
+        reader.set_max_bytes(options.max_bytes);
+        Self {
+            reader,
+            options: options.clone(),
+            started: false,
+            lead_in_accepted: options.min_lead_in_frames <= 1,
+            skip_vbr_header_frame: false,
+            finished: false,
+            frame_count: 0,
+            truncated_frame: None,
+        }
+    }
+
+    /// Skip a XING/VBRI header frame at the start instead of reporting it
+    pub(crate) fn skip_vbr_header_frame(&mut self) {
+        self.skip_vbr_header_frame = true;
+    }
+
+    /// Skip metadata blocks between frames instead of finishing at the first one
+    pub(crate) fn continue_after_metadata(&mut self) {
+        self.reader.continue_after_metadata();
+    }
+
+    /// Read the header of the next frame
+    ///
+    /// Returns `None` at the end of the stream, at the end of the audio
+    /// data of a RIFF/WAVE or AIFF-C container, or if the next frame has been
+    /// truncated, see [`Self::truncated_frame()`]. Unrecognized data
+    /// after the last frame is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PositionalError`](crate::PositionalError) on any kind of failure.
+    pub fn next_frame(&mut self) -> PositionalResult<Option<FrameHeader>> {
+        if self.finished {
+            return Ok(None);
+        }
+        if !self.started {
+            self.started = true;
+            let data_end_offset = riff::skip_to_data_chunk(&mut self.reader)?;
+            self.reader.set_end_offset(data_end_offset);
+        }
+        loop {
+            let sync_step = match frame::sync_next_frame(
+                &mut self.reader,
+                &self.options,
+                self.lead_in_accepted,
+            ) {
+                Ok(sync_step) => sync_step,
+                Err(err) => {
+                    if err.is_unexpected_eof() && self.frame_count > 0 {
+                        // Silently ignore all unrecognized data after the last frame
+                        self.finished = true;
+                        return Ok(None);
+                    }
+                    return Err(err);
+                }
+            };
+            match sync_step {
+                SyncStep::Frame(frame_header, _) => {
+                    self.lead_in_accepted = true;
+                    let frame_start_offset =
+                        self.reader.position().byte_offset - u64::from(frame::FRAME_HEADER_SIZE);
+                    if let Some(frame_size) = frame_header.frame_size {
+                        let body_size = frame_size.saturating_sub(frame::FRAME_HEADER_SIZE.into());
+                        if self.skip_vbr_header_frame && self.frame_count == 0 {
+                            // Only the first frame might be a XING/VBRI header frame
+                            self.skip_vbr_header_frame = false;
+                            let mut frame_body = vec![0; usize::from(body_size)];
+                            if !self.reader.try_read_exact_until_eof(&mut frame_body)? {
+                                self.truncated_frame = Some(frame_start_offset);
+                                self.finished = true;
+                                return Ok(None);
+                            }
+                            if frame::is_vbr_header_frame(&frame_header, &frame_body) {
+                                continue;
+                            }
+                        } else if !self.reader.try_skip_exact_until_eof(body_size.into())? {
+                            self.truncated_frame = Some(frame_start_offset);
+                            self.finished = true;
+                            return Ok(None);
+                        }
+                    }
+                    self.reader.add_duration(frame_header.frame_duration());
+                    self.frame_count += 1;
+                    return Ok(Some(frame_header));
+                }
+                SyncStep::LeadInRejected => {
+                    // Continue searching at the next byte
+                }
+                SyncStep::MetadataSkipped => {
+                    if self.frame_count > 0 && !self.reader.continues_after_metadata() {
+                        // No more MPEG frames after a trailing metadata frame expected
+                        self.finished = true;
+                        return Ok(None);
+                    }
+                }
+                SyncStep::Finished => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Number of frames that have been read
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Position after the last frame or metadata block
+    ///
+    /// The duration accounts for all frames that have been read.
+    #[must_use]
+    pub fn position(&self) -> &ReadPosition {
+        self.reader.position()
+    }
+
+    /// Byte offset of the last frame if it has been truncated
+    #[must_use]
+    pub const fn truncated_frame(&self) -> Option<u64> {
+        self.truncated_frame
+    }
+}
+
+/// Frame boundaries within a byte range of a stream
+///
+/// All byte offsets are relative to the start of the stream.
+///
+/// See also: [`crate::Header::scan_range_boundaries()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeScanResult {
+    /// Byte offset of the first frame that is fully contained in the range
+    pub first_full_frame_offset: Option<u64>,
+
+    /// Byte offset right after the last frame that is fully contained in the range
+    pub last_full_frame_end: Option<u64>,
+
+    /// Number of bytes before the first full frame
+    ///
+    /// Usually the tail of a frame that started in the preceding range.
+    /// Covers the whole range if it contains no full frames.
+    pub partial_head_bytes: u64,
+
+    /// Number of bytes after the last full frame
+    ///
+    /// Usually the head of a frame that continues in the following range.
+    pub partial_tail_bytes: u64,
+
+    /// Byte offsets and headers of all full frames
+    pub frames: Vec<(u64, FrameHeader)>,
+}
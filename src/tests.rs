@@ -14,7 +14,7 @@ fn is_supported_file_extension(entry: &DirEntry) -> bool {
     }
     // symlinks are resolved by follow_symlinks = true
     debug_assert!(entry.file_type().is_file());
-    entry.file_name().to_str().map_or(false, |file_name| {
+    entry.file_name().to_str().is_some_and(|file_name| {
         let file_name = file_name.to_lowercase();
         file_name.ends_with(".mp3") || file_name.ends_with(".mp2") || file_name.ends_with(".mp1")
     })
@@ -86,7 +86,7 @@ fn check_header(path_suffix: &str, parse_mode: ParseMode, header: Header) -> Hea
                 assert_eq!(Duration::from_nanos(398_367_346_938), header.total_duration);
             } else {
                 assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
-                assert_eq!(Duration::from_nanos(398_367_332_000), header.total_duration);
+                assert_eq!(Duration::from_micros(398_367_332), header.total_duration);
             }
         }
         "samples.ffmpeg.org/A-codecs/mp1-sample.mp1" => {
@@ -95,7 +95,16 @@ fn check_header(path_suffix: &str, parse_mode: ParseMode, header: Header) -> Hea
         "getID3-testfiles/mp3/VBRI/VBR-10s-44x16x2-q75-19170Hz-random.mp3" => {
             if matches!(parse_mode, ParseMode::PreferVbrHeaders) {
                 assert_eq!(HeaderSource::VbriHeader, header.source);
-                assert_eq!(Duration::from_nanos(10_083_265_306), header.total_duration);
+                // 386 frames reduced by the delay of the VBRI header
+                let vbri_delay = header.vbri_delay.unwrap();
+                assert_eq!(
+                    duration_from_samples(444_672 - u64::from(vbri_delay), 44_100),
+                    header.total_duration
+                );
+                assert_eq!(
+                    Some(Duration::from_nanos(10_083_265_306)),
+                    header.declared_duration()
+                );
             } else {
                 assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
                 assert_eq!(Duration::from_nanos(10_057_142_480), header.total_duration);
@@ -148,3 +157,3581 @@ fn read_all() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+const MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE: usize = 417;
+
+fn header_word(
+    version_bits: u32,
+    layer_bits: u32,
+    bitrate_bits: u32,
+    sample_rate_bits: u32,
+    padding: bool,
+    mode_bits: u32,
+) -> u32 {
+    0xFFE0_0000
+        | (version_bits << 19)
+        | (layer_bits << 17)
+        | (1 << 16) // no CRC
+        | (bitrate_bits << 12)
+        | (sample_rate_bits << 10)
+        | (u32::from(padding) << 9)
+        | (mode_bits << 6)
+}
+
+/// MPEG-1 Layer III, 128 kbps, 44.1 kHz, Stereo
+fn mpeg1_layer3_header_word() -> u32 {
+    header_word(0b11, 0b01, 0b1001, 0b00, false, 0b00)
+}
+
+fn frame_with_payload(header_word: u32, frame_size: usize, payload: &[u8]) -> Vec<u8> {
+    let mut frame = header_word.to_be_bytes().to_vec();
+    frame.extend_from_slice(payload);
+    debug_assert!(frame.len() <= frame_size);
+    frame.resize(frame_size, 0);
+    frame
+}
+
+fn mpeg1_layer3_frames(frame_count: usize) -> Vec<u8> {
+    (0..frame_count)
+        .flat_map(|_| {
+            frame_with_payload(
+                mpeg1_layer3_header_word(),
+                MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+                &[],
+            )
+        })
+        .collect()
+}
+
+fn mpeg1_layer3_xing_frame(magic: [u8; 4], flags: u32, fields: &[u8]) -> Vec<u8> {
+    // Side information of MPEG-1 stereo frames
+    let mut payload = vec![0; 32];
+    payload.extend_from_slice(&magic);
+    payload.extend_from_slice(&flags.to_be_bytes());
+    payload.extend_from_slice(fields);
+    frame_with_payload(
+        mpeg1_layer3_header_word(),
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &payload,
+    )
+}
+
+#[test]
+fn xing_header_with_frames_field_only() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert!(header.from_vbr_header());
+    assert!(!header.from_frame_scan());
+    assert_eq!(Some(1152), header.samples_per_frame);
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(Duration::from_nanos(261_224_489), header.total_duration);
+    assert_eq!(Some(128), header.avg_bitrate_kbps);
+    assert_eq!(417, header.final_position.byte_offset());
+    assert_eq!(Duration::ZERO, header.final_position.duration());
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(Some(header.total_duration), header.declared_duration());
+    assert_eq!(Some(1152), header.vbr_frame_samples);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(11_520, header.total_sample_count);
+    // The samples of the XING header frame are not included
+    assert_eq!(Some(1152), header.vbr_frame_samples);
+    assert_eq!(Duration::from_nanos(261_224_480), header.total_duration);
+    assert_eq!(Some(128_000), header.avg_bitrate_bps);
+    assert_eq!(Some(128), header.avg_bitrate_kbps);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+    assert_eq!(header.total_duration, header.final_position.duration());
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(
+        Some(Duration::from_nanos(261_224_489)),
+        header.declared_duration()
+    );
+    assert_eq!(None, header.reconciled_declared_duration);
+    assert_eq!(None, header.measured_duration);
+    assert_eq!(None, header.measured_frame_count);
+
+    let header = Header::read_from_source(&mut &data[417..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(None, header.vbr_frame_samples);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::Reconcile)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert!(!header.from_vbr_header());
+    assert!(header.from_frame_scan());
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(Some(10), header.measured_frame_count);
+    assert_eq!(Some(11_520), header.measured_sample_count);
+    assert_eq!(
+        Some(Duration::from_nanos(261_224_489)),
+        header.reconciled_declared_duration
+    );
+    assert_eq!(
+        Some(Duration::from_nanos(261_224_480)),
+        header.measured_duration
+    );
+    assert_eq!(header.measured_duration, Some(header.total_duration));
+    assert_eq!(Some(Duration::from_nanos(9)), header.vbr_duration_drift());
+
+    Ok(())
+}
+
+#[test]
+fn xing_header_with_frames_field_at_end_of_frame() -> anyhow::Result<()> {
+    // MPEG-2 Layer III, 8 kbps, 24 kHz, Mono, padded: 25 bytes per frame
+    // that only fit the frame header, the side information, the XING
+    // magic, the flags, and the frames field
+    let header_word = header_word(0b10, 0b01, 0b0001, 0b01, true, 0b11);
+    let frame_size = 25;
+    let mut payload = vec![0; 9];
+    payload.extend_from_slice(b"Xing");
+    // Frames, TOC, and quality flags
+    payload.extend_from_slice(&0b1101u32.to_be_bytes());
+    payload.extend_from_slice(&10u32.to_be_bytes());
+    let mut data = frame_with_payload(header_word, frame_size, &payload);
+    assert_eq!(frame_size, data.len());
+    for _ in 0..10 {
+        data.extend(frame_with_payload(header_word, frame_size, &[]));
+    }
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(5_760, header.total_sample_count);
+    assert_eq!(Duration::from_millis(240), header.total_duration);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(5_760, header.total_sample_count);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn duration_secs_and_millis() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Duration::from_nanos(261_224_480), header.total_duration);
+    assert!((header.duration_secs() - 0.261_224_48).abs() < 1e-12);
+    assert!((header.duration_millis() - 261.224_48).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn vbr_duration_drift() -> anyhow::Result<()> {
+    // The stream has been cut after the VBR header has been written
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &20u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::Reconcile)?;
+    assert_eq!(Some(20), header.declared_frame_count);
+    assert_eq!(Some(10), header.measured_frame_count);
+    assert_eq!(
+        Some(Duration::from_nanos(522_448_979 - 261_224_480)),
+        header.vbr_duration_drift()
+    );
+
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+        assert_eq!(None, header.vbr_duration_drift());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn cbr_claim_violated() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Info", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::Reconcile)?;
+    assert_eq!(Some(false), header.declared_vbr);
+    assert!(!header.cbr_claim_violated);
+
+    // 32 kbps at 44.1 kHz = 104 bytes per frame
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b0001, 0b00, false, 0b00),
+        104,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::Reconcile)?;
+    assert!(header.cbr_claim_violated);
+
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+        assert!(!header.cbr_claim_violated);
+    }
+
+    // Varying bitrates are expected for "Xing" headers
+    data[36..40].copy_from_slice(b"Xing");
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::Reconcile)?;
+    assert_eq!(Some(true), header.declared_vbr);
+    assert!(!header.cbr_claim_violated);
+
+    Ok(())
+}
+
+#[test]
+fn validate_vbr_header_tolerance() -> anyhow::Result<()> {
+    let options = ParseOptions {
+        vbr_header_tolerance_percent: Some(10),
+        ..Default::default()
+    };
+    let xing_data = |total_frames: u32| {
+        let total_bytes = 10 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u32;
+        let mut fields = total_frames.to_be_bytes().to_vec();
+        fields.extend_from_slice(&total_bytes.to_be_bytes());
+        let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0011, &fields);
+        data.extend(mpeg1_layer3_frames(10));
+        data
+    };
+
+    // Plausible
+    let data = xing_data(10);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::PreferVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert!(!header.vbr_header_suspect);
+
+    // Stale frame count, i.e. an average bitrate of 1280 kbps
+    let data = xing_data(1);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::PreferVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert!(header.vbr_header_suspect);
+    assert_eq!(Some(1), header.declared_frame_count);
+    assert_eq!(11_520, header.total_sample_count);
+
+    // Stale frame count with an average bitrate of 256 kbps that
+    // deviates from the estimate based on the first audio frame
+    let data = xing_data(5);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::PreferVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert!(header.vbr_header_suspect);
+    assert_eq!(Some(5), header.declared_frame_count);
+    assert_eq!(11_520, header.total_sample_count);
+
+    // Deviation within the tolerance
+    let data = xing_data(11);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::PreferVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert!(!header.vbr_header_suspect);
+
+    // Corrupt frame count, i.e. an average bitrate below 1 kbps
+    let data = xing_data(100_000);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::Reconcile,
+        &options,
+    )?;
+    assert!(header.vbr_header_suspect);
+    assert_eq!(Some(100_000), header.declared_frame_count);
+    assert_eq!(Some(10), header.measured_frame_count);
+
+    // Disabled by default
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert!(!header.vbr_header_suspect);
+    assert_eq!(100_000 * 1152, header.total_sample_count);
+
+    Ok(())
+}
+
+#[test]
+fn detect_vbr_header_only() -> anyhow::Result<()> {
+    let options = ParseOptions {
+        detect_vbr_header_only: true,
+        ..Default::default()
+    };
+
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert_eq!(0, header.total_sample_count);
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(417, header.final_position.byte_offset());
+
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(1152, header.total_sample_count);
+    assert_eq!(417, header.final_position.byte_offset());
+    assert!(header.partial);
+
+    Ok(())
+}
+
+#[test]
+fn identify_only() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(12);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(!header.partial);
+
+    let options = ParseOptions {
+        identify_only: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert!(header.partial);
+    assert_eq!(Some(Version::Mpeg1), header.version);
+    assert_eq!(10 * 1152, header.total_sample_count);
+    // Sum of the identified frame durations
+    assert_eq!(
+        10 * duration_from_samples(1152, 44_100),
+        header.total_duration
+    );
+    assert_eq!(
+        10 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64,
+        header.final_position.byte_offset()
+    );
+
+    // 128 kbps at 48 kHz = 384 bytes per frame
+    let mut data = mpeg1_layer3_frames(2);
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00),
+        384,
+        &[],
+    ));
+    data.extend(mpeg1_layer3_frames(10));
+    let options = ParseOptions {
+        identify_only: true,
+        identify_frame_count: 3,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert!(header.partial);
+    assert_eq!(3 * 1152, header.total_sample_count);
+    assert_eq!(44100, header.min_sample_rate_hz);
+    assert_eq!(48000, header.max_sample_rate_hz);
+
+    Ok(())
+}
+
+#[test]
+fn duration_certainty() -> anyhow::Result<()> {
+    let identify_only = ParseOptions {
+        identify_only: true,
+        identify_frame_count: 3,
+        ..Default::default()
+    };
+
+    let data = mpeg1_layer3_frames(12);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        DurationCertainty::ExactFromFrameScan,
+        header.duration_certainty()
+    );
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &identify_only,
+    )?;
+    assert_eq!(
+        DurationCertainty::EstimatedFromBitrate,
+        header.duration_certainty()
+    );
+
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &12u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(12));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(
+        DurationCertainty::ExactFromVbrHeader,
+        header.duration_certainty()
+    );
+
+    // 160 kbps at 44.1 kHz = 522 bytes per frame
+    let mut data = mpeg1_layer3_frames(2);
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b1010, 0b00, false, 0b00),
+        522,
+        &[],
+    ));
+    data.extend(mpeg1_layer3_frames(10));
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &identify_only,
+    )?;
+    assert_eq!(
+        DurationCertainty::RequiresFullScan,
+        header.duration_certainty()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lame_tag_with_encoder_delay_and_padding() -> anyhow::Result<()> {
+    let mut fields = 10u32.to_be_bytes().to_vec();
+    let mut lame_tag = [0; 36];
+    lame_tag[..9].copy_from_slice(b"LAME3.100");
+    // Delay = 576 (0x240), padding = 1000 (0x3E8)
+    lame_tag[21..24].copy_from_slice(&[0x24, 0x03, 0xE8]);
+    // Preset V2 = 480 (0x1E0)
+    lame_tag[26..28].copy_from_slice(&[0x01, 0xE0]);
+    fields.extend_from_slice(&lame_tag);
+    let mut data = mpeg1_layer3_xing_frame(*b"Info", 0b0001, &fields);
+    data.extend(mpeg1_layer3_frames(10));
+
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+        assert_eq!(
+            Some(LameInfo {
+                encoder_version: "LAME3.100".to_owned(),
+                encoder_delay: 576,
+                encoder_padding: 1000,
+                preset: Some(LamePreset::Vbr(2)),
+            }),
+            header.lame_info
+        );
+        assert_eq!(Some(576), header.start_skip_samples);
+        assert_eq!(Some(1000), header.end_skip_samples);
+    }
+
+    // No LAME tag
+    let mut data = mpeg1_layer3_xing_frame(*b"Info", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(None, header.lame_info);
+    assert_eq!(None, header.start_skip_samples);
+
+    Ok(())
+}
+
+#[test]
+fn reserved_sample_rate() -> anyhow::Result<()> {
+    // MPEG-1 Layer III, 128 kbps, reserved sample rate
+    let reserved_sample_rate = header_word(0b11, 0b01, 0b1001, 0b11, false, 0b00);
+    assert!(!looks_like_mpeg_audio(&reserved_sample_rate.to_be_bytes()));
+    assert!(
+        Header::scan_candidates(&mut reserved_sample_rate.to_be_bytes().as_slice())?.is_empty()
+    );
+
+    // Skipped as junk between valid frames
+    let frames = mpeg1_layer3_frames(10);
+    let mut data = frames[..5 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE].to_vec();
+    data.extend(frame_with_payload(
+        reserved_sample_rate,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &[],
+    ));
+    data.extend_from_slice(&frames[5 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE..]);
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+        assert_eq!(10 * 1152, header.total_sample_count);
+        assert_eq!(44100, header.min_sample_rate_hz);
+        assert_eq!(44100, header.max_sample_rate_hz);
+    }
+
+    // No valid frames at all
+    let data = frame_with_payload(
+        reserved_sample_rate,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &[],
+    );
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(0, header.total_sample_count);
+    assert_eq!(0, header.max_sample_rate_hz);
+    assert_eq!(None, header.first_frame);
+
+    Ok(())
+}
+
+#[test]
+fn trim_leading_and_trailing_frames() -> anyhow::Result<()> {
+    let mut fields = 10u32.to_be_bytes().to_vec();
+    let mut lame_tag = [0; 36];
+    lame_tag[..9].copy_from_slice(b"LAME3.100");
+    // Delay = 576 (0x240), padding = 1000 (0x3E8)
+    lame_tag[21..24].copy_from_slice(&[0x24, 0x03, 0xE8]);
+    fields.extend_from_slice(&lame_tag);
+    let mut data = mpeg1_layer3_xing_frame(*b"Info", 0b0001, &fields);
+    data.extend(mpeg1_layer3_frames(10));
+
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+        let trimmed = header.trimmed(0, 0).unwrap();
+        assert_eq!(header.total_sample_count, trimmed.total_sample_count);
+        assert_eq!(header.profiles, trimmed.profiles);
+        assert_eq!(header.start_skip_samples, trimmed.start_skip_samples);
+
+        let trimmed = header.trimmed(1, 2).unwrap();
+        assert_eq!(7 * 1152, trimmed.total_sample_count);
+        assert_eq!(
+            duration_from_samples(7 * 1152, 44100),
+            trimmed.total_duration
+        );
+        assert_eq!(7, trimmed.profiles[0].frame_count);
+        assert_eq!(7 * 1152, trimmed.profiles[0].total_samples);
+        assert_eq!(Some(0), trimmed.start_skip_samples);
+        assert_eq!(Some(0), trimmed.end_skip_samples);
+        assert_eq!(
+            header.measured_frame_count.map(|_| 7),
+            trimmed.measured_frame_count
+        );
+        assert_eq!(Some(7), trimmed.declared_frame_count);
+        if matches!(parse_mode, ParseMode::IgnoreVbrHeaders) {
+            // Without the Info frame and the first frame
+            assert_eq!(Some(2 * 417), trimmed.first_frame_offset);
+            // Without the last 2 frames
+            assert_eq!(Some(9 * 417), trimmed.audio_end_offset);
+            assert_eq!(9 * 417, trimmed.final_position.byte_offset());
+            assert_eq!(
+                7 * duration_from_samples(1152, 44100),
+                trimmed.final_position.duration()
+            );
+            assert_eq!([7, 0, 0, 0], trimmed.mode_frame_counts);
+        } else {
+            // The position of the frames is unknown
+            assert_eq!(None, trimmed.first_frame_offset);
+            assert_eq!(None, trimmed.audio_end_offset);
+            assert_eq!(header.final_position, trimmed.final_position);
+            assert_eq!([0; 4], trimmed.mode_frame_counts);
+        }
+
+        let trimmed = header.trimmed(10, 0).unwrap();
+        assert_eq!(0, trimmed.total_sample_count);
+        assert_eq!(Duration::ZERO, trimmed.total_duration);
+        assert_eq!(None, header.trimmed(10, 1));
+    }
+
+    // Mixed profiles
+    let mut data = mpeg1_layer3_frames(5);
+    for _ in 0..5 {
+        // 48 kHz
+        data.extend(frame_with_payload(
+            header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00),
+            384,
+            &[],
+        ));
+    }
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(2, header.profiles.len());
+    assert_eq!(None, header.trimmed(1, 1));
+
+    Ok(())
+}
+
+#[test]
+fn read_untrusted_data_without_panicking() -> anyhow::Result<()> {
+    // Free bitrate with the padding bit set
+    let free_bitrate_padded = header_word(0b11, 0b01, 0b0000, 0b00, true, 0b00);
+    let mut data = free_bitrate_padded.to_be_bytes().to_vec();
+    data.extend_from_slice(&[0; 8]);
+    let options = ParseOptions {
+        require_next_sync: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(None, header.max_frame_size);
+
+    // Pseudo-random junk with a valid frame header at the start
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    for _ in 0..100 {
+        let mut data = mpeg1_layer3_header_word().to_be_bytes().to_vec();
+        for _ in 0..500 {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.push(state as u8);
+        }
+        for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+            let _ = Header::read_from_source_with_options(
+                &mut data.as_slice(),
+                parse_mode,
+                &ParseOptions::default(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn flat_key_value_fields() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let fields = header.to_fields();
+    let field = |key| {
+        fields
+            .iter()
+            .find_map(|(field_key, value)| (*field_key == key).then_some(value.as_str()))
+    };
+    assert_eq!(Some("MpegFrameHeaders"), field("source"));
+    assert_eq!(Some("Mpeg1"), field("version"));
+    assert_eq!(Some("Layer3"), field("layer"));
+    assert_eq!(Some("11520"), field("total_sample_count"));
+    assert_eq!(Some("128"), field("avg_bitrate_kbps"));
+    assert_eq!(Some("10"), field("declared_frame_count"));
+    assert_eq!(Some("true"), field("declared_vbr"));
+    assert_eq!(Some("false"), field("common_flags.original"));
+    assert_eq!(Some("1"), field("profiles.len"));
+    assert_eq!(Some("44100"), field("first_frame.sample_rate_hz"));
+    // Omitted
+    assert_eq!(None, field("truncated_frame"));
+    assert_eq!(None, field("lame_info.encoder_version"));
+    assert_eq!(None, field("min_bitrate_runs.len"));
+    // Unique keys
+    let mut keys = fields.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+    keys.sort_unstable();
+    keys.dedup();
+    assert_eq!(fields.len(), keys.len());
+
+    Ok(())
+}
+
+#[test]
+fn audio_equivalent_with_different_tags() -> anyhow::Result<()> {
+    let audio = mpeg1_layer3_frames(10);
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, b' ');
+    let mut tagged = audio.clone();
+    tagged.extend_from_slice(&id3v1);
+
+    let header = Header::read_from_source(&mut audio.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let tagged_header =
+        Header::read_from_source(&mut tagged.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(header.audio_equivalent(&tagged_header));
+
+    let truncated = mpeg1_layer3_frames(9);
+    let truncated_header =
+        Header::read_from_source(&mut truncated.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(!header.audio_equivalent(&truncated_header));
+
+    Ok(())
+}
+
+#[test]
+fn truncated_trailing_metadata() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_frames(10);
+    let audio_len = data.len() as u64;
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.truncated_metadata);
+
+    // ID3v1 tag that is cut off after 64 of 128 bytes
+    data.extend_from_slice(b"TAG");
+    data.resize(data.len() + 61, b' ');
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(audio_len), header.truncated_metadata);
+    assert_eq!(11_520, header.total_sample_count);
+
+    Ok(())
+}
+
+fn apev2_block(size: u32, item_count: u32, is_header: bool) -> Vec<u8> {
+    let mut block = b"APETAGEX".to_vec();
+    block.extend_from_slice(&2000u32.to_le_bytes());
+    block.extend_from_slice(&size.to_le_bytes());
+    block.extend_from_slice(&item_count.to_le_bytes());
+    // Contains a header
+    let flags = (1 << 31) | (u32::from(is_header) << 29);
+    block.extend_from_slice(&flags.to_le_bytes());
+    block.resize(32, 0);
+    block
+}
+
+#[test]
+fn trailing_apev2_tag() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let items = b"item data without sync words";
+    let size = (items.len() + 32) as u32;
+
+    let mut data = frames.clone();
+    data.extend(apev2_block(size, 1, true));
+    data.extend_from_slice(items);
+    data.extend(apev2_block(size, 1, false));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let apev2_info = header.apev2_info.unwrap();
+    assert!(apev2_info.is_header());
+    assert_eq!(2000, apev2_info.version);
+    assert_eq!(1, apev2_info.item_count);
+    assert_eq!(size, apev2_info.size);
+    assert_eq!(None, header.truncated_metadata);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    // Only the footer is recognized
+    let mut data = frames;
+    data.extend_from_slice(items);
+    data.extend(apev2_block(size, 1, false));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(!header.apev2_info.unwrap().is_header());
+    assert_eq!(None, header.truncated_metadata);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn detect_trailing_tags() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let items = b"item data without sync words";
+    let ape_size = (items.len() + 32) as u32;
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, b' ');
+
+    let mut data = frames.clone();
+    data.extend(apev2_block(ape_size, 1, true));
+    data.extend_from_slice(items);
+    data.extend(apev2_block(ape_size, 1, false));
+    let lyrics3_offset = data.len() as u64;
+    data.extend_from_slice(b"LYRICSBEGININD00002  LYR00005Hello000034LYRICS200");
+    data.extend_from_slice(&id3v1);
+    let header = Header::read_from_slice(&data, ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(
+        vec![
+            MetadataRegion {
+                kind: MetadataKind::ApeV2,
+                byte_offset: frames.len() as u64,
+                byte_len: u64::from(ape_size) + 32,
+            },
+            MetadataRegion {
+                kind: MetadataKind::Lyrics3,
+                byte_offset: lyrics3_offset,
+                byte_len: 49,
+            },
+            MetadataRegion {
+                kind: MetadataKind::Id3v1,
+                byte_offset: data.len() as u64 - 128,
+                byte_len: 128,
+            },
+        ],
+        header.trailing_tags
+    );
+
+    // Lyrics3 v1 tag from a seekable source that doesn't start at the beginning
+    let mut data = b"skip".to_vec();
+    data.extend_from_slice(&frames);
+    data.extend_from_slice(b"LYRICSBEGINHello worldLYRICSEND");
+    data.extend_from_slice(&id3v1);
+    let mut source = std::io::Cursor::new(data.as_slice());
+    source.set_position(4);
+    let header = Header::read_from_seekable_source_with_options(
+        &mut source,
+        ParseMode::IgnoreVbrHeaders,
+        &ParseOptions::default(),
+    )?;
+    assert_eq!(
+        vec![
+            MetadataRegion {
+                kind: MetadataKind::Lyrics3,
+                byte_offset: frames.len() as u64,
+                byte_len: 31,
+            },
+            MetadataRegion {
+                kind: MetadataKind::Id3v1,
+                byte_offset: frames.len() as u64 + 31,
+                byte_len: 128,
+            },
+        ],
+        header.trailing_tags
+    );
+
+    // Not detected for generic sources
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(header.trailing_tags.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn scan_candidates_including_junk() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_header_word().to_be_bytes().to_vec();
+    data.extend(mpeg1_layer3_frames(3));
+
+    let candidates = Header::scan_candidates(&mut data.as_slice())?;
+    let offsets = candidates
+        .iter()
+        .map(|(offset, _)| *offset)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![0, 4, 421, 838], offsets);
+    for (_, frame_header) in candidates {
+        assert_eq!(Version::Mpeg1, frame_header.version);
+        assert_eq!(Layer::Layer3, frame_header.layer);
+        assert_eq!(Some(417), frame_header.frame_size);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn scan_frames_with_sync_scanner() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let items = b"item data without sync words";
+    let size = (items.len() + 32) as u32;
+    // Leading ID3v2 tag without frames
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x0A".to_vec();
+    data.extend([0; 10]);
+    data.extend_from_slice(&frames);
+    data.extend(apev2_block(size, 1, true));
+    data.extend_from_slice(items);
+    data.extend(apev2_block(size, 1, false));
+
+    let mut source = data.as_slice();
+    let mut scanner = MpegSyncScanner::new(&mut source, &ParseOptions::default());
+    let mut aggregation = AggregationState::new();
+    while let Some(frame_header) = scanner.next_frame()? {
+        assert_eq!(
+            Some(MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u16),
+            frame_header.frame_size
+        );
+        aggregation.feed_frame(&frame_header);
+    }
+    assert!(scanner.next_frame()?.is_none());
+    assert_eq!(10, scanner.frame_count());
+    assert_eq!(None, scanner.truncated_frame());
+    assert_eq!(data.len() as u64, scanner.position().byte_offset());
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(header.total_sample_count, aggregation.total_sample_count());
+    assert_eq!(header.total_duration, scanner.position().duration());
+
+    // Truncated final frame
+    let data = &frames[..frames.len() - 100];
+    let mut source = data;
+    let mut scanner = MpegSyncScanner::new(&mut source, &ParseOptions::default());
+    while scanner.next_frame()?.is_some() {}
+    assert_eq!(9, scanner.frame_count());
+    assert_eq!(
+        Some(9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64),
+        scanner.truncated_frame()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn leading_null_bytes() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let mut data = vec![0; 10_000];
+    data.extend_from_slice(&frames);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    // Skipping stops right before the limit is exceeded
+    let options = ParseOptions {
+        max_bytes: Some(5_000),
+        ..Default::default()
+    };
+    let err = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::LimitExceeded(5_000)));
+    assert_eq!(5_000, err.position().byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn skip_zero_bytes_without_reading_ahead() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+
+    // Only a single byte is read ahead if there are no zero bytes
+    let mut source = frames.as_slice();
+    let mut reader = Reader::new(&mut source);
+    assert_eq!(0, reader.skip_zero_bytes()?);
+    assert_eq!(frames.len() - 1, source.len());
+
+    // The chunk size grows from a single byte while only zero bytes are found
+    let mut data = vec![0; 3];
+    data.extend_from_slice(&frames);
+    let mut source = data.as_slice();
+    let mut reader = Reader::new(&mut source);
+    assert_eq!(3, reader.skip_zero_bytes()?);
+    assert_eq!(3, reader.position().byte_offset());
+    // 1 + 2 + 4 bytes
+    assert_eq!(data.len() - 7, source.len());
+
+    Ok(())
+}
+
+#[test]
+fn read_from_slice_skips_in_place() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let mut data = vec![0; 10_000];
+    // ID3v2 tag with 20_000 bytes
+    data.extend_from_slice(b"ID3\x04\x00\x00\x00\x01\x1C\x20");
+    data.resize(data.len() + 20_000, 0xAA);
+    data.extend_from_slice(&frames[..5 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE]);
+    data.extend_from_slice(b"garbage");
+    // Truncated final frame
+    data.extend_from_slice(&frames[5 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE..frames.len() - 1]);
+
+    let expected = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(9 * 1152, expected.total_sample_count);
+    assert!(expected.truncated_frame.is_some());
+    let header = Header::read_from_slice(&data, ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(data.len() as u64), header.stream_byte_len);
+    assert_eq!(
+        expected,
+        Header {
+            stream_byte_len: None,
+            ..header
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trailing_bytes() -> anyhow::Result<()> {
+    let audio_byte_len = 6 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u32;
+    let mut data = mpeg1_layer3_xing_frame(
+        *b"Xing",
+        0b0011,
+        &[5u32.to_be_bytes(), audio_byte_len.to_be_bytes()].concat(),
+    );
+    data.extend(mpeg1_layer3_frames(5));
+    // Appended junk and an ID3v1 tag
+    data.resize(data.len() + 100, 0xAA);
+    data.extend_from_slice(b"TAG");
+    data.resize(data.len() + 125, b' ');
+
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header = Header::read_from_slice(&data, parse_mode)?;
+        assert_eq!(Some(u64::from(audio_byte_len)), header.audio_end_offset);
+        assert_eq!(Some(100), header.trailing_bytes(data.len() as u64));
+    }
+
+    // Without trailing tags all bytes after the audio data are unexplained
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(100 + 128), header.trailing_bytes(data.len() as u64));
+    assert_eq!(None, header.trailing_bytes(100));
+
+    Ok(())
+}
+
+#[test]
+fn capture_error_context() {
+    let mut data = b"skip".to_vec();
+    data.extend(mpeg1_layer3_frames(3));
+    let options = ParseOptions {
+        max_bytes: Some(500),
+        error_context_len: 16,
+        ..Default::default()
+    };
+
+    let mut source = std::io::Cursor::new(data.as_slice());
+    source.set_position(4);
+    let err = Header::read_from_seekable_source_with_options(
+        &mut source,
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::LimitExceeded(500)));
+    let byte_offset = err.position().byte_offset() as usize;
+    assert_eq!(Some(byte_offset as u64 - 16), err.context_byte_offset());
+    assert_eq!(
+        Some(&data[4 + byte_offset - 16..4 + byte_offset + 16]),
+        err.context()
+    );
+
+    // Not supported for sources that are not seekable
+    let err = Header::read_from_source_with_options(
+        &mut &data[4..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(None, err.context());
+}
+
+#[test]
+fn channel_semantics_of_mixed_stereo_modes() -> anyhow::Result<()> {
+    let stereo = header_word(0b11, 0b01, 0b1001, 0b00, false, 0b00);
+    let joint_stereo = header_word(0b11, 0b01, 0b1001, 0b00, false, 0b01);
+    let dual_channel = header_word(0b11, 0b01, 0b1001, 0b00, false, 0b10);
+
+    let mut data = frame_with_payload(stereo, MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE, &[]);
+    data.extend(frame_with_payload(
+        joint_stereo,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.mode);
+    assert_eq!(Some(ChannelSemantics::Stereo), header.channel_semantics);
+    assert_eq!(1, header.program_count);
+
+    let dual_channel_frame =
+        frame_with_payload(dual_channel, MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE, &[]);
+    let header = Header::read_from_source(
+        &mut dual_channel_frame.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+    )?;
+    assert_eq!(
+        Some(ChannelSemantics::DualProgram),
+        header.channel_semantics
+    );
+    assert_eq!(2, header.program_count);
+
+    data.extend(dual_channel_frame);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.channel_semantics);
+    assert_eq!(1, header.program_count);
+    assert_eq!([1, 1, 1, 0], header.mode_frame_counts);
+    assert_eq!(1, header.mode_frame_counts[Mode::DualChannel as usize]);
+    assert_eq!(0, header.mono_frame_count);
+    assert_eq!(3, header.stereo_frame_count);
+
+    let mono = header_word(0b11, 0b01, 0b1001, 0b00, false, 0b11);
+    data.extend(frame_with_payload(
+        mono,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(1, header.mono_frame_count);
+    assert_eq!(3, header.stereo_frame_count);
+
+    Ok(())
+}
+
+#[test]
+fn count_padded_frames() -> anyhow::Result<()> {
+    let padded = header_word(0b11, 0b01, 0b1001, 0b00, true, 0b00);
+    let mut data = mpeg1_layer3_frames(3);
+    for _ in 0..2 {
+        data.extend(frame_with_payload(
+            padded,
+            MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE + 1,
+            &[],
+        ));
+    }
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(data.len() as u64, header.final_position.byte_offset);
+    assert_eq!(2, header.padding_frame_count);
+    assert_eq!(Some(418), header.max_frame_size);
+    assert_eq!(
+        Some(MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u16),
+        header.first_frame.unwrap().frame_size
+    );
+
+    Ok(())
+}
+
+#[test]
+fn frame_size_includes_header_and_padding() -> anyhow::Result<()> {
+    // MPEG-1 Layer I, 288 kbps, 44.1 kHz: 4 * floor(12 * 288000 / 44100) = 312 bytes
+    for (padding, frame_size) in [(false, 312), (true, 316)] {
+        let data = header_word(0b11, 0b11, 0b1001, 0b00, padding, 0b00).to_be_bytes();
+        let candidates = Header::scan_candidates(&mut data.as_slice())?;
+        let [(_, frame_header)] = candidates.as_slice() else {
+            panic!("single frame header");
+        };
+        assert_eq!(padding, frame_header.flags.padding);
+        assert_eq!(Some(frame_size), frame_header.frame_size);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reserved_emphasis() -> anyhow::Result<()> {
+    let reserved_emphasis = mpeg1_layer3_header_word() | 0b10;
+    let data: Vec<_> = (0..10)
+        .flat_map(|_| {
+            frame_with_payload(
+                reserved_emphasis,
+                MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+                &[],
+            )
+        })
+        .collect();
+
+    // Rejected by default
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(0, header.total_sample_count);
+
+    let options = ParseOptions {
+        allow_reserved_emphasis: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(None, header.emphasis);
+    assert_eq!(Some(Mode::Stereo), header.mode);
+    assert_eq!(44100, header.max_sample_rate_hz);
+    assert_eq!(Some(128), header.avg_bitrate_kbps);
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(Duration::from_nanos(261_224_480), header.total_duration);
+
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(Emphasis::None), header.emphasis);
+
+    Ok(())
+}
+
+#[test]
+fn aggregate_common_flags() -> anyhow::Result<()> {
+    // Copyright and private bits
+    let copyright = mpeg1_layer3_header_word() | 0b1_0000_1000;
+    let padded = header_word(0b11, 0b01, 0b1001, 0b00, true, 0b00) | 0b1_0000_1000;
+    let mut data = frame_with_payload(copyright, MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE, &[]);
+    data.extend(frame_with_payload(
+        padded,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE + 1,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        FrameFlags {
+            protection: Some(false),
+            padding: None,
+            private: Some(true),
+            copyright: Some(true),
+            original: Some(false),
+        },
+        header.common_flags
+    );
+    assert_eq!(
+        FrameFlags {
+            protection: false,
+            padding: false,
+            private: true,
+            copyright: true,
+            original: false,
+        },
+        header.first_frame.unwrap().flags
+    );
+
+    Ok(())
+}
+
+#[test]
+fn byte_swapped_stream() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let swapped_data = data
+        .chunks(2)
+        .flat_map(|word| word.iter().rev().copied())
+        .collect::<Vec<_>>();
+    assert_eq!([0xFB, 0xFF], swapped_data[..2]);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let options = ParseOptions {
+        byte_order: ByteOrder::Swapped,
+        ..Default::default()
+    };
+    let swapped_header = Header::read_from_source_with_options(
+        &mut swapped_data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(header, swapped_header);
+
+    Ok(())
+}
+
+#[test]
+fn resync_after_invalid_frame_header() -> anyhow::Result<()> {
+    // Sync word followed by an invalid bitrate
+    let mut data = header_word(0b11, 0b01, 0b1111, 0b00, false, 0b00)
+        .to_be_bytes()
+        .to_vec();
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(Some(128_000), header.avg_bitrate_bps);
+
+    Ok(())
+}
+
+#[test]
+fn require_next_sync() -> anyhow::Result<()> {
+    // Junk that starts with a valid frame header (64 kbps, 208 bytes)
+    let mut data = header_word(0b11, 0b01, 0b0101, 0b00, false, 0b00)
+        .to_be_bytes()
+        .to_vec();
+    data.resize(100, 0);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_ne!(Some(128_000), header.avg_bitrate_bps);
+
+    let options = ParseOptions {
+        require_next_sync: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(128_000), header.avg_bitrate_bps);
+    assert_eq!(11_520, header.total_sample_count);
+
+    Ok(())
+}
+
+#[test]
+fn count_alignment_errors() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(3);
+    let header = Header::read_from_source(&mut frames.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(0, header.alignment_errors);
+
+    let mut data = frames.clone();
+    data.extend_from_slice(b"junk");
+    data.extend_from_slice(&frames);
+    data.extend_from_slice(b"TAG");
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(6 * 1152, header.total_sample_count);
+    assert_eq!(1, header.alignment_errors);
+
+    // The unused remainder of the XING header frame is not checked
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &3u32.to_be_bytes());
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(3 * 1152, header.total_sample_count);
+    assert_eq!(0, header.alignment_errors);
+
+    Ok(())
+}
+
+#[test]
+fn confidence() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(20);
+    // Leading ID3v2 tag without any frames
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x00".to_vec();
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(0, header.junk_byte_count);
+    assert_eq!(100, header.confidence());
+
+    // Junk after every other frame
+    let data = frames
+        .chunks(2 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE)
+        .flat_map(|frames| [frames, b"garbage"].concat())
+        .collect::<Vec<_>>();
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(20 * 1152, header.total_sample_count);
+    assert_eq!(10, header.alignment_errors);
+    assert_eq!(10 * 7, header.junk_byte_count);
+    // 100 - 40 (alignment errors) - 3 (junk bytes)
+    assert_eq!(57, header.confidence());
+
+    let header = Header::read_from_source(&mut &b"garbage"[..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(7, header.junk_byte_count);
+    assert_eq!(0, header.confidence());
+
+    // The unused remainder of the XING header frame is not junk
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &20u32.to_be_bytes());
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(20 * 1152, header.total_sample_count);
+    assert_eq!(0, header.junk_byte_count);
+    assert_eq!(100, header.confidence());
+
+    Ok(())
+}
+
+#[test]
+fn read_from_source_with_prefix() -> anyhow::Result<()> {
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x10".to_vec();
+    data.resize(10 + 16, 0);
+    data.extend(mpeg1_layer3_frames(5));
+    let expected = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    for prefix_len in [0, 4, 10, 100] {
+        let (prefix, mut remainder) = data.split_at(prefix_len);
+        let header = Header::read_from_source_with_prefix(
+            prefix,
+            &mut remainder,
+            ParseMode::IgnoreVbrHeaders,
+        )?;
+        assert_eq!(expected, header);
+        assert_eq!(Some(26), header.first_frame_offset);
+        assert_eq!(data.len() as u64, header.final_position.byte_offset());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn assume_no_metadata() -> anyhow::Result<()> {
+    // Raw stream that starts with junk that looks like an ID3v1 tag
+    let mut data = b"TAG".to_vec();
+    data.extend(mpeg1_layer3_frames(5));
+
+    // The first frame is skipped as part of the tag
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(4 * 1152, header.total_sample_count);
+
+    let options = ParseOptions {
+        assume_no_metadata: true,
+        stop_at_metadata: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(5 * 1152, header.total_sample_count);
+    assert_eq!(3, header.junk_byte_count);
+    assert_eq!(None, header.stopped_at);
+    assert_eq!(Some(3), header.first_frame_offset);
+
+    Ok(())
+}
+
+#[test]
+fn stop_at_metadata() -> anyhow::Result<()> {
+    let options = ParseOptions {
+        stop_at_metadata: true,
+        ..Default::default()
+    };
+    let id3v2_tag = b"ID3\x04\x00\x00\x00\x00\x00\x00";
+    let frames = mpeg1_layer3_frames(3);
+    let data = [&frames[..], id3v2_tag, &frames].concat();
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    // Parsing ends after the metadata block
+    assert_eq!(None, header.stopped_at);
+    assert!(!header.partial);
+    assert_eq!(3 * 1152, header.total_sample_count);
+    assert_eq!(
+        (frames.len() + id3v2_tag.len()) as u64,
+        header.final_position.byte_offset()
+    );
+
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(frames.len() as u64), header.stopped_at);
+    assert!(header.partial);
+    assert_eq!(3 * 1152, header.total_sample_count);
+    assert_eq!(frames.len() as u64, header.final_position.byte_offset());
+
+    // Continue parsing at the metadata block
+    let remainder =
+        Header::read_from_source(&mut &data[frames.len()..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(6 * 1152, header.merge(remainder).total_sample_count);
+
+    // Leading metadata
+    let header = Header::read_from_source_with_options(
+        &mut &data[frames.len()..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(0), header.stopped_at);
+    assert_eq!(0, header.total_sample_count);
+    assert_eq!(0, header.final_position.byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn read_with_progress() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &5u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(5));
+    let mut positions = Vec::new();
+    let header = Header::read_with_progress(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &ParseOptions::default(),
+        |position| positions.push(position.clone()),
+    )?;
+    // Not invoked for the XING header frame
+    assert_eq!(5, positions.len());
+    for (frame_count, position) in (1..).zip(&positions) {
+        assert_eq!(
+            (frame_count + 1) * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64,
+            position.byte_offset()
+        );
+        assert_eq!(
+            duration_from_samples(frame_count * 1152, 44100).as_millis(),
+            position.duration().as_millis()
+        );
+    }
+    assert_eq!(Some(&header.final_position), positions.last());
+
+    Ok(())
+}
+
+#[test]
+fn read_and_verify_duration() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let actual = Duration::from_nanos(261_224_480);
+
+    let header = Header::read_and_verify_duration(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        Duration::from_millis(262),
+        Duration::from_millis(1),
+    )?;
+    assert_eq!(actual, header.total_duration);
+
+    let err = Header::read_and_verify_duration(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        Duration::from_millis(260),
+        Duration::from_millis(1),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.source(),
+        Error::DurationMismatch { expected, actual: mismatch }
+            if *expected == Duration::from_millis(260) && *mismatch == actual
+    ));
+    assert_eq!(data.len() as u64, err.position().byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn collect_diagnostics() -> anyhow::Result<()> {
+    // XING header with a reserved flag and the frames field
+    let xing_frame = mpeg1_layer3_xing_frame(*b"Xing", 0x0100 | 0b0001, &3u32.to_be_bytes());
+    let frames = mpeg1_layer3_frames(3);
+    let data = [
+        &b"garbage..."[..],
+        &xing_frame,
+        &frames[..2 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE],
+        b"garbage",
+        &frames[2 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE..],
+        b"trail",
+    ]
+    .concat();
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(header.diagnostics.is_empty());
+
+    let options = ParseOptions {
+        collect_diagnostics: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(3 * 1152, header.total_sample_count);
+    let last_frame_offset = 10 + 3 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64 + 7;
+    assert_eq!(
+        vec![
+            Diagnostic::ResyncPerformed {
+                byte_offset: 10,
+                skipped_byte_count: 10,
+            },
+            Diagnostic::SuspiciousXingFlags {
+                byte_offset: 10,
+                flags: 0x0101,
+            },
+            Diagnostic::ResyncPerformed {
+                byte_offset: last_frame_offset,
+                skipped_byte_count: 7,
+            },
+            Diagnostic::TrailingDataIgnored {
+                byte_offset: last_frame_offset + MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64,
+                byte_len: 5,
+            },
+        ],
+        header.diagnostics
+    );
+    assert_eq!(header.junk_byte_count, 10 + 7 + 5);
+
+    Ok(())
+}
+
+#[test]
+fn detect_bit_reservoir() -> anyhow::Result<()> {
+    let options = ParseOptions {
+        detect_bit_reservoir: true,
+        ..Default::default()
+    };
+
+    let data = mpeg1_layer3_frames(3);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.uses_bit_reservoir);
+    assert_eq!(None, header.all_frames_independent());
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(false), header.uses_bit_reservoir);
+    assert_eq!(Some(true), header.all_frames_independent());
+
+    // main_data_begin = 3 (9 bits)
+    let mut data = mpeg1_layer3_frames(1);
+    data.extend(frame_with_payload(
+        mpeg1_layer3_header_word(),
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &[0b0000_0001, 0b1000_0000],
+    ));
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(true), header.uses_bit_reservoir);
+    assert_eq!(Some(false), header.all_frames_independent());
+
+    // No effect on Layer II
+    let data = frame_with_payload(
+        header_word(0b11, 0b10, 0b1000, 0b00, false, 0b00),
+        417,
+        &[0xFF],
+    );
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(Layer::Layer2), header.layer);
+    assert_eq!(None, header.uses_bit_reservoir);
+    // Layer II frames are always independent
+    assert_eq!(Some(true), header.all_frames_independent());
+
+    Ok(())
+}
+
+#[test]
+fn sync_alignment() -> anyhow::Result<()> {
+    // Junk with an unaligned sync word
+    let mut data = vec![0x01];
+    data.extend_from_slice(&mpeg1_layer3_header_word().to_be_bytes());
+    data.extend_from_slice(&[0x01; 3]);
+    // MPEG-1 Layer I, 128 kbps, 44.1 kHz = 136 bytes per frame
+    let layer1_frame =
+        frame_with_payload(header_word(0b11, 0b11, 0b0100, 0b00, false, 0b00), 136, &[]);
+    for _ in 0..5 {
+        data.extend_from_slice(&layer1_frame);
+    }
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_ne!(Some(Layer::Layer1), header.layer);
+
+    // The frames start at byte offset 8 and are 8-byte aligned
+    for sync_alignment in [2, 4, 8] {
+        let options = ParseOptions {
+            sync_alignment: NonZeroU8::new(sync_alignment),
+            ..Default::default()
+        };
+        let header = Header::read_from_source_with_options(
+            &mut data.as_slice(),
+            ParseMode::IgnoreVbrHeaders,
+            &options,
+        )?;
+        assert_eq!(Some(Layer::Layer1), header.layer);
+        assert_eq!(Some(384), header.samples_per_frame);
+        assert_eq!(5 * 384, header.total_sample_count);
+        assert_eq!(data.len() as u64, header.final_position.byte_offset());
+    }
+
+    // Aligned frames after a leading ID3v2 tag of 20 bytes
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x0A".to_vec();
+    data.resize(20, 0);
+    data.resize(24, 0x01);
+    for _ in 0..5 {
+        data.extend_from_slice(&layer1_frame);
+    }
+    let options = ParseOptions {
+        sync_alignment: NonZeroU8::new(8),
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(5 * 384, header.total_sample_count);
+
+    Ok(())
+}
+
+#[test]
+fn expected_profile() -> anyhow::Result<()> {
+    // Junk with a sync word of an MPEG-1 Layer I frame
+    let mut data = header_word(0b11, 0b11, 0b0100, 0b00, false, 0b00)
+        .to_be_bytes()
+        .to_vec();
+    data.extend_from_slice(&[0x01; 3]);
+    data.extend(mpeg1_layer3_frames(5));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_ne!(Some(Layer::Layer3), header.layer);
+
+    let options = ParseOptions {
+        expected_profile: Some(FormatProfile {
+            version: Version::Mpeg1,
+            layer: Layer::Layer3,
+            sample_rate_hz: 44100,
+        }),
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(Layer::Layer3), header.layer);
+    assert_eq!(5 * 1152, header.total_sample_count);
+    assert_eq!(7, header.junk_byte_count);
+    assert!(options
+        .expected_profile
+        .unwrap()
+        .matches(header.first_frame.as_ref().unwrap()));
+
+    Ok(())
+}
+
+#[test]
+fn detect_multichannel_extension() -> anyhow::Result<()> {
+    // MPEG-1 Layer II, 192 kbps, 48 kHz, Stereo
+    const FRAME_SIZE: usize = 576;
+    let header_word = header_word(0b11, 0b10, 0b1010, 0b01, false, 0b00);
+    // No bits are allocated in any of the 27 subbands (2 x 88 bits). The
+    // multichannel header follows: center = 1, surround = 2, lfe = 1 (5.1)
+    let mut mc_payload = vec![0; 22];
+    mc_payload.push(0b0011_0100);
+
+    let stereo_data = (0..10)
+        .flat_map(|_| frame_with_payload(header_word, FRAME_SIZE, &[]))
+        .collect::<Vec<_>>();
+    let mc_data = (0..10)
+        .flat_map(|_| frame_with_payload(header_word, FRAME_SIZE, &mc_payload))
+        .collect::<Vec<_>>();
+
+    let options = ParseOptions {
+        detect_multichannel: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut stereo_data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert!(!header.possible_multichannel);
+    assert_eq!(None, header.multichannel_count);
+    let header = Header::read_from_source_with_options(
+        &mut mc_data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert!(header.possible_multichannel);
+    assert_eq!(Some(6), header.multichannel_count);
+    assert_eq!(2, header.max_channel_count);
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(
+        Some(6),
+        header.clone().merge(header.clone()).multichannel_count
+    );
+
+    // Disabled by default
+    let header = Header::read_from_source(&mut mc_data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(!header.possible_multichannel);
+    assert_eq!(None, header.multichannel_count);
+
+    Ok(())
+}
+
+#[test]
+fn capture_raw_vbr_header() -> anyhow::Result<()> {
+    let mut fields = 10u32.to_be_bytes().to_vec();
+    // TOC
+    fields.extend(0..100u8);
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0101, &fields);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(None, header.raw_vbr_header);
+
+    let options = ParseOptions {
+        capture_vbr_header: true,
+        ..Default::default()
+    };
+    for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+        let header =
+            Header::read_from_source_with_options(&mut data.as_slice(), parse_mode, &options)?;
+        // Frame header + side information + XING header + frames + TOC
+        assert_eq!(
+            Some(&data[..4 + 32 + 8 + 4 + 100]),
+            header.raw_vbr_header.as_deref()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn vbri_toc_seek_byte_offsets() -> anyhow::Result<()> {
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u16;
+    let mut fields = Vec::new();
+    // Quality
+    fields.extend_from_slice(&75u16.to_be_bytes());
+    // Size in bytes
+    fields.extend_from_slice(&(10 * u32::from(frame_size)).to_be_bytes());
+    // Number of frames
+    fields.extend_from_slice(&10u32.to_be_bytes());
+    // 3 entries of 2 bytes with a scale of 1 and 4 frames per entry
+    for value in [3, 1, 2, 4] {
+        fields.extend_from_slice(&u16::to_be_bytes(value));
+    }
+    // The final segment contains only 2 frames
+    for frame_count in [4, 4, 2] {
+        fields.extend_from_slice(&(frame_count * frame_size).to_be_bytes());
+    }
+    // Version and delay
+    let mut data = mpeg1_layer3_xing_frame(*b"VBRI", 0x0001_0000, &fields);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::VbriHeader, header.source);
+    assert_eq!(Some(10), header.declared_frame_count);
+    let vbri_toc = header.vbri_toc.unwrap();
+    assert_eq!(4, vbri_toc.frames_per_entry);
+    assert_eq!(
+        vec![
+            4 * u32::from(frame_size),
+            4 * u32::from(frame_size),
+            2 * u32::from(frame_size),
+        ],
+        vbri_toc.entries
+    );
+    let frame_size = u64::from(frame_size);
+    assert_eq!(0, vbri_toc.seek_byte_offset_for_frame(3));
+    assert_eq!(4 * frame_size, vbri_toc.seek_byte_offset_for_frame(4));
+    assert_eq!(8 * frame_size, vbri_toc.seek_byte_offset_for_frame(9));
+    // Beyond the end of the TOC
+    assert_eq!(8 * frame_size, vbri_toc.seek_byte_offset_for_frame(12));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(11_520, header.total_sample_count);
+    assert!(header.vbri_toc.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn vbri_delay() -> anyhow::Result<()> {
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u32;
+    let mut fields = Vec::new();
+    // Quality
+    fields.extend_from_slice(&75u16.to_be_bytes());
+    // Size in bytes
+    fields.extend_from_slice(&(10 * frame_size).to_be_bytes());
+    // Number of frames
+    fields.extend_from_slice(&10u32.to_be_bytes());
+    // Empty TOC
+    for value in [0, 1, 2, 1] {
+        fields.extend_from_slice(&u16::to_be_bytes(value));
+    }
+    // Version 1 and a delay of 576 samples
+    let mut data = mpeg1_layer3_xing_frame(*b"VBRI", 0x0001_0240, &fields);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::VbriHeader, header.source);
+    assert_eq!(Some(576), header.vbri_delay);
+    assert_eq!(11_520 - 576, header.total_sample_count);
+    assert_eq!(
+        duration_from_samples(11_520 - 576, 44100),
+        header.total_duration
+    );
+    // The raw totals
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(
+        Some(duration_from_samples(11_520, 44100)),
+        header.declared_duration()
+    );
+
+    // The delay is not subtracted from the aggregated properties
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(Some(576), header.vbri_delay);
+    assert_eq!(11_520, header.total_sample_count);
+
+    Ok(())
+}
+
+#[test]
+fn vbri_header_at_fixed_offset() -> anyhow::Result<()> {
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, Mono
+    let header_word = header_word(0b11, 0b01, 0b1001, 0b00, false, 0b11);
+    // The VBRI header is located 32 bytes after the frame header
+    // and not immediately after the 17 bytes of side information
+    let mut payload = vec![0; 32];
+    payload.extend_from_slice(b"VBRI");
+    // Version and delay
+    payload.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    // Quality
+    payload.extend_from_slice(&75u16.to_be_bytes());
+    // Size in bytes
+    payload.extend_from_slice(&(10 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u32).to_be_bytes());
+    // Number of frames
+    payload.extend_from_slice(&10u32.to_be_bytes());
+    // No TOC entries
+    for value in [0, 1, 2, 1] {
+        payload.extend_from_slice(&u16::to_be_bytes(value));
+    }
+    let mut data = frame_with_payload(
+        header_word,
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+        &payload,
+    );
+    for _ in 0..10 {
+        data.extend(frame_with_payload(
+            header_word,
+            MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE,
+            &[],
+        ));
+    }
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::VbriHeader, header.source);
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(
+        MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64,
+        header.final_position.byte_offset()
+    );
+
+    // The VBRI header frame is not counted as an audio frame
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(Some(10), header.declared_frame_count);
+    assert_eq!(11_520, header.total_sample_count);
+
+    // Audio frames are not affected by the lookahead
+    let data = &data[MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE..];
+    let header = Header::read_from_source(&mut &data[..], ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+    assert_eq!(None, header.declared_frame_count);
+    assert_eq!(11_520, header.total_sample_count);
+    assert_eq!(0, header.alignment_errors);
+
+    Ok(())
+}
+
+#[test]
+fn recommended_input_buffer_size() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &10u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(417), header.max_frame_size);
+    assert_eq!(417 + 511, header.recommended_input_buffer_size());
+
+    // 320 kbps at 44.1 kHz with padding
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(None, header.max_frame_size);
+    assert_eq!(1045 + 511, header.recommended_input_buffer_size());
+
+    Ok(())
+}
+
+#[test]
+fn max_frame_size_for_valid_sample_rates() {
+    // 160 kbps at 22.05 kHz with padding
+    assert_eq!(
+        Some(523),
+        max_frame_size_for(Version::Mpeg2, Layer::Layer3, 22050, true)
+    );
+    assert_eq!(
+        Some(522),
+        max_frame_size_for(Version::Mpeg2, Layer::Layer3, 22050, false)
+    );
+    assert_eq!(
+        None,
+        max_frame_size_for(Version::Mpeg1, Layer::Layer3, 22050, true)
+    );
+    assert_eq!(
+        None,
+        max_frame_size_for(Version::Mpeg1, Layer::Layer3, 0, true)
+    );
+}
+
+#[test]
+fn aggregate_consistent_layer() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_frames(3);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(Layer::Layer3), header.layer);
+
+    // MPEG-1 Layer II, 128 kbps, 44.1 kHz
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b10, 0b1000, 0b00, false, 0b00),
+        417,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        4,
+        header
+            .profiles
+            .iter()
+            .map(|profile| profile.frame_count)
+            .sum::<u64>()
+    );
+    assert_eq!(None, header.layer);
+
+    Ok(())
+}
+
+#[test]
+fn aggregate_scanned_frame_headers() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(Layer::Layer3), header.layer);
+
+    let mut aggregation = AggregationState::new();
+    for (_, frame_header) in Header::scan_candidates(&mut data.as_slice())? {
+        aggregation.feed_frame(&frame_header);
+    }
+    assert_eq!(10, aggregation.frame_count());
+    assert_eq!(data.len() as u64, aggregation.position().byte_offset());
+    assert_eq!(10, header.lead_in_confidence);
+    assert_eq!(
+        Header {
+            // Not derived from the frame headers
+            lead_in_confidence: 0,
+            first_frame_offset: None,
+            audio_end_offset: None,
+            ..header
+        },
+        aggregation.finalize()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_with_frames() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &5u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(2));
+    data.extend_from_slice(b"junk");
+    data.extend(mpeg1_layer3_frames(3));
+
+    let (header, frames) =
+        Header::read_with_frames(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?,
+        header
+    );
+    // The XING header frame is not listed
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64;
+    assert_eq!(
+        vec![
+            frame_size,
+            2 * frame_size,
+            3 * frame_size + 4,
+            4 * frame_size + 4,
+            5 * frame_size + 4,
+        ],
+        frames
+            .iter()
+            .map(|(byte_offset, _)| *byte_offset)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        header.first_frame.as_ref(),
+        frames.first().map(|(_, frame_header)| frame_header)
+    );
+
+    let (header, frames) =
+        Header::read_with_frames(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(HeaderSource::XingHeader, header.source);
+    assert!(frames.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn ignore_frames_without_samples() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(1);
+    let (_, frame_header) = Header::scan_candidates(&mut data.as_slice())?
+        .pop()
+        .unwrap();
+    let empty_frame_header = FrameHeader {
+        sample_count: 0,
+        ..frame_header.clone()
+    };
+
+    let mut aggregation = AggregationState::new();
+    aggregation.feed_frame(&empty_frame_header);
+    assert_eq!(0, aggregation.frame_count());
+    assert_eq!(0, aggregation.position().byte_offset());
+    aggregation.feed_frame(&frame_header);
+    aggregation.feed_frame(&empty_frame_header);
+    assert_eq!(1, aggregation.frame_count());
+    let header = aggregation.finalize();
+    assert_eq!(1152, header.total_sample_count);
+    assert_eq!(Some(frame_header), header.first_frame);
+
+    Ok(())
+}
+
+#[test]
+fn typed_sample_rates() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(3);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(SampleRate::Hz44100), header.min_sample_rate());
+    assert_eq!(Some(SampleRate::Hz44100), header.max_sample_rate());
+    assert_eq!(Some(Version::Mpeg1), SampleRate::Hz44100.version());
+
+    assert_eq!(None, AggregationState::new().finalize().min_sample_rate());
+
+    for sample_rate_hz in [44100, 48000, 32000, 22050, 24000, 16000, 11025, 12000, 8000] {
+        let sample_rate = SampleRate::from_hz(sample_rate_hz);
+        assert!(sample_rate.version().is_some());
+        assert_eq!(sample_rate_hz, sample_rate.hz());
+    }
+    assert_eq!(SampleRate::Other(64000), SampleRate::from_hz(64000));
+    assert_eq!(None, SampleRate::Other(64000).version());
+
+    Ok(())
+}
+
+#[test]
+fn mpeg1_layer1_frames() -> anyhow::Result<()> {
+    // MPEG-1 Layer I, 32 kbps, 44.1 kHz: 4 * floor(12 * 32000 / 44100) = 32 bytes,
+    // i.e. smaller than the offset of a XING header
+    let small_frame = header_word(0b11, 0b11, 0b0001, 0b00, false, 0b00);
+    // MPEG-1 Layer I, 384 kbps, 44.1 kHz: 4 * floor(12 * 384000 / 44100) = 416 bytes
+    let large_frame = header_word(0b11, 0b11, 0b1100, 0b00, false, 0b00);
+    for (header_word, frame_size) in [(small_frame, 32), (large_frame, 416)] {
+        let frame = frame_with_payload(header_word, frame_size, &[0xAA; 28]);
+        let data = (0..20).flat_map(|_| frame.clone()).collect::<Vec<_>>();
+        for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+            let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+            assert_eq!(Some(Layer::Layer1), header.layer);
+            assert_eq!(Some(384), header.samples_per_frame);
+            assert_eq!(Some(frame_size as u16), header.max_frame_size);
+            assert_eq!(data.len() as u64, header.final_position.byte_offset());
+            assert_eq!(0, header.alignment_errors);
+            assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+            assert_eq!(20 * 384, header.total_sample_count);
+            assert_eq!(
+                duration_from_samples(20 * 384, 44100).as_millis(),
+                header.total_duration.as_millis()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn layer2_frames_with_emphasis() -> anyhow::Result<()> {
+    // MPEG-1 Layer II, 48 kHz, Mono: 144 * bitrate / 48000 bytes, i.e.
+    // the smaller frames could not carry a XING header
+    let mpeg1_frames = [32, 48, 56, 64, 80, 96, 112, 128, 160, 192]
+        .into_iter()
+        .zip(1..)
+        .map(|(bitrate_kbps, bitrate_bits)| {
+            (
+                header_word(0b11, 0b10, bitrate_bits, 0b01, false, 0b11),
+                144 * bitrate_kbps / 48,
+                48000,
+            )
+        });
+    // MPEG-2 Layer II, 8 kbps, 16 kHz, Mono: 144 * 8000 / 16000 = 72 bytes
+    let mpeg2_frame = (
+        header_word(0b10, 0b10, 0b0001, 0b10, false, 0b11),
+        72,
+        16000,
+    );
+    for (header_word, frame_size, sample_rate_hz) in mpeg1_frames.chain([mpeg2_frame]) {
+        // CCITT J.17
+        let header_word = header_word | 0b11;
+        let frame = frame_with_payload(header_word, frame_size, &[0xAA; 64]);
+        let data = (0..20).flat_map(|_| frame.clone()).collect::<Vec<_>>();
+        for parse_mode in [ParseMode::PreferVbrHeaders, ParseMode::IgnoreVbrHeaders] {
+            let header = Header::read_from_source(&mut data.as_slice(), parse_mode)?;
+            assert_eq!(Some(Layer::Layer2), header.layer);
+            assert_eq!(Some(Emphasis::CcittJ17), header.emphasis);
+            assert_eq!(Some(frame_size as u16), header.max_frame_size);
+            assert_eq!(data.len() as u64, header.final_position.byte_offset());
+            assert_eq!(0, header.alignment_errors);
+            assert_eq!(HeaderSource::MpegFrameHeaders, header.source);
+            assert_eq!(20 * 1152, header.total_sample_count);
+            assert_eq!(
+                duration_from_samples(20 * 1152, sample_rate_hz),
+                header.total_duration
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn mpeg1_layer2_bitrate_mode_constraints() -> anyhow::Result<()> {
+    // 32 kbps at 48 kHz = 96 bytes per frame
+    let frames = |mode_bits| {
+        let header_word = header_word(0b11, 0b10, 0b0001, 0b01, false, mode_bits);
+        (0..3)
+            .flat_map(|_| frame_with_payload(header_word, 96, &[]))
+            .collect::<Vec<_>>()
+    };
+
+    // Mono
+    let candidates = Header::scan_candidates(&mut frames(0b11).as_slice())?;
+    assert_eq!(3, candidates.len());
+    assert!(candidates
+        .iter()
+        .all(|(_, frame_header)| frame_header.bitrate_bps == Some(32_000)));
+
+    // Stereo is not allowed at 32 kbps
+    let candidates = Header::scan_candidates(&mut frames(0b00).as_slice())?;
+    assert!(candidates.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn min_bitrate_runs() -> anyhow::Result<()> {
+    // 32 kbps at 44.1 kHz = 104 bytes per frame
+    let low_bitrate_frame =
+        frame_with_payload(header_word(0b11, 0b01, 0b0001, 0b00, false, 0b00), 104, &[]);
+    let high_bitrate_frame = mpeg1_layer3_frames(1);
+    let mut data = Vec::new();
+    for (frame, count) in [
+        (&high_bitrate_frame, 3),
+        (&low_bitrate_frame, 4),
+        (&high_bitrate_frame, 2),
+        (&low_bitrate_frame, 1),
+        (&high_bitrate_frame, 1),
+        (&low_bitrate_frame, 2),
+    ] {
+        for _ in 0..count {
+            data.extend_from_slice(frame);
+        }
+    }
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert!(header.min_bitrate_runs.is_empty());
+
+    let options = ParseOptions {
+        min_bitrate_run_frames: Some(2),
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(
+        vec![(3 * 1152, 4 * 1152), (11 * 1152, 2 * 1152)],
+        header.min_bitrate_runs
+    );
+
+    Ok(())
+}
+
+#[test]
+fn bitrate_coefficient_of_variation() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(0.0), header.bitrate_cv);
+
+    // 32 kbps at 44.1 kHz = 104 bytes per frame
+    let low_bitrate_frame =
+        frame_with_payload(header_word(0b11, 0b01, 0b0001, 0b00, false, 0b00), 104, &[]);
+    let high_bitrate_frame = mpeg1_layer3_frames(1);
+    let mut data = Vec::new();
+    for _ in 0..5 {
+        data.extend_from_slice(&low_bitrate_frame);
+        data.extend_from_slice(&high_bitrate_frame);
+    }
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    // Mean = 80 kbps, standard deviation = 48 kbps
+    let bitrate_cv = header.bitrate_cv.unwrap();
+    assert!((bitrate_cv - 0.6).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn bitrate_cv_of_merged_constant_bitrate() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let (head, tail) = data.split_at(3 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE);
+    let head = Header::read_from_source(&mut &head[..], ParseMode::IgnoreVbrHeaders)?;
+    let tail = Header::read_from_source(&mut &tail[..], ParseMode::IgnoreVbrHeaders)?;
+    let merged = head.merge(tail);
+    // Exactly 0 without any rounding errors
+    assert_eq!(Some(0.0), merged.bitrate_cv);
+    assert_eq!(Some(false), merged.is_vbr());
+
+    Ok(())
+}
+
+#[test]
+fn bitrate_monotonic() -> anyhow::Result<()> {
+    // MPEG-1 Layer III at 44.1 kHz from 32 to 128 kbps
+    let frame = |bitrate_bits: u32, bitrate_bps: usize| {
+        frame_with_payload(
+            header_word(0b11, 0b01, bitrate_bits, 0b00, false, 0b00),
+            144 * bitrate_bps / 44100,
+            &[],
+        )
+    };
+    let bitrates = [
+        (0b0001, 32_000),
+        (0b0010, 40_000),
+        (0b0011, 48_000),
+        (0b0100, 56_000),
+        (0b0101, 64_000),
+        (0b0110, 80_000),
+        (0b0111, 96_000),
+        (0b1000, 112_000),
+        (0b1001, 128_000),
+    ];
+    let options = ParseOptions {
+        detect_bitrate_monotonic: true,
+        ..Default::default()
+    };
+    let read_header = |data: &[u8], options: &ParseOptions| {
+        Header::read_from_source_with_options(&mut &data[..], ParseMode::IgnoreVbrHeaders, options)
+    };
+
+    let ramp = bitrates
+        .iter()
+        .flat_map(|(bitrate_bits, bitrate_bps)| {
+            std::iter::repeat_n(frame(*bitrate_bits, *bitrate_bps), 3).flatten()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(Some(true), read_header(&ramp, &options)?.bitrate_monotonic);
+    assert_eq!(
+        None,
+        read_header(&ramp, &ParseOptions::default())?.bitrate_monotonic
+    );
+
+    let ramp_down = bitrates
+        .iter()
+        .rev()
+        .flat_map(|(bitrate_bits, bitrate_bps)| frame(*bitrate_bits, *bitrate_bps))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        Some(true),
+        read_header(&ramp_down, &options)?.bitrate_monotonic
+    );
+
+    let oscillating = bitrates
+        .iter()
+        .flat_map(|(bitrate_bits, bitrate_bps)| {
+            let mut frames = frame(*bitrate_bits, *bitrate_bps);
+            frames.extend(mpeg1_layer3_frames(1));
+            frames
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        Some(false),
+        read_header(&oscillating, &options)?.bitrate_monotonic
+    );
+
+    let constant = mpeg1_layer3_frames(10);
+    assert_eq!(
+        Some(true),
+        read_header(&constant, &options)?.bitrate_monotonic
+    );
+
+    let single = mpeg1_layer3_frames(1);
+    assert_eq!(None, read_header(&single, &options)?.bitrate_monotonic);
+
+    // Merging never reports a monotonic bitrate
+    let merged = |head: &[u8], tail: &[u8]| -> anyhow::Result<Option<bool>> {
+        Ok(read_header(head, &options)?
+            .merge(read_header(tail, &options)?)
+            .bitrate_monotonic)
+    };
+    assert_eq!(None, merged(&ramp, &ramp_down)?);
+    assert_eq!(None, merged(&ramp, &single)?);
+    assert_eq!(None, merged(&single, &single)?);
+    assert_eq!(Some(false), merged(&ramp, &oscillating)?);
+    assert_eq!(Some(false), merged(&oscillating, &single)?);
+
+    Ok(())
+}
+
+#[test]
+fn bitrate_timeline() -> anyhow::Result<()> {
+    // 24 ms per frame at 48 kHz: 96 bytes at 32 kbps and 384 bytes at 128 kbps
+    let low_bitrate_frame =
+        frame_with_payload(header_word(0b11, 0b01, 0b0001, 0b01, false, 0b00), 96, &[]);
+    let high_bitrate_frame =
+        frame_with_payload(header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00), 384, &[]);
+    let mut data = Vec::new();
+    for _ in 0..2 {
+        data.extend_from_slice(&low_bitrate_frame);
+        data.extend_from_slice(&high_bitrate_frame);
+    }
+    for _ in 0..3 {
+        data.extend_from_slice(&high_bitrate_frame);
+    }
+
+    let timeline = Header::bitrate_timeline(&mut data.as_slice(), Duration::from_millis(48))?;
+    assert_eq!(
+        vec![
+            (Duration::ZERO, 80_000),
+            (Duration::from_millis(48), 80_000),
+            (Duration::from_millis(96), 128_000),
+            (Duration::from_millis(144), 128_000),
+        ],
+        timeline
+    );
+
+    // Buckets without frames are omitted
+    let timeline = Header::bitrate_timeline(&mut data.as_slice(), Duration::from_millis(20))?;
+    assert_eq!(
+        vec![
+            (Duration::ZERO, 32_000),
+            (Duration::from_millis(20), 128_000),
+            (Duration::from_millis(40), 32_000),
+            (Duration::from_millis(60), 128_000),
+            (Duration::from_millis(80), 128_000),
+            (Duration::from_millis(120), 128_000),
+            (Duration::from_millis(140), 128_000),
+        ],
+        timeline
+    );
+
+    // The XING header frame is skipped
+    let mut xing_payload = vec![0; 32];
+    xing_payload.extend_from_slice(b"Xing");
+    xing_payload.extend_from_slice(&0b0001u32.to_be_bytes());
+    xing_payload.extend_from_slice(&7u32.to_be_bytes());
+    let mut xing_data = frame_with_payload(
+        header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00),
+        384,
+        &xing_payload,
+    );
+    xing_data.extend_from_slice(&data);
+    let timeline = Header::bitrate_timeline(&mut xing_data.as_slice(), Duration::from_millis(48))?;
+    assert_eq!(
+        Header::bitrate_timeline(&mut data.as_slice(), Duration::from_millis(48))?,
+        timeline
+    );
+
+    Ok(())
+}
+
+#[test]
+fn audio_properties() -> anyhow::Result<()> {
+    fn properties(
+        properties: &impl AudioProperties,
+    ) -> (Duration, Option<u32>, Option<u8>, Option<u32>) {
+        (
+            properties.duration(),
+            properties.sample_rate(),
+            properties.channels(),
+            properties.bitrate(),
+        )
+    }
+
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        (header.total_duration, Some(44100), Some(2), Some(128_000)),
+        properties(&header)
+    );
+
+    let header = AggregationState::new().finalize();
+    assert_eq!((Duration::ZERO, None, None, None), properties(&header));
+
+    Ok(())
+}
+
+#[test]
+fn variable_bitrate_from_available_evidence() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+
+    let header = Header::read_from_source(&mut frames.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(false), header.is_vbr());
+
+    // A single frame is insufficient
+    let header = Header::read_from_source(
+        &mut mpeg1_layer3_frames(1).as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+    )?;
+    assert_eq!(None, header.is_vbr());
+
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &10u32.to_be_bytes());
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(Some(true), header.declared_vbr);
+    assert_eq!(Some(true), header.is_vbr());
+    // The bitrates of the scanned frames take precedence
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(true), header.declared_vbr);
+    assert_eq!(Some(false), header.is_vbr());
+
+    let mut data = mpeg1_layer3_xing_frame(*b"Info", 0b0001, &10u32.to_be_bytes());
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(Some(false), header.declared_vbr);
+    assert_eq!(Some(false), header.is_vbr());
+
+    // 32 kbps at 44.1 kHz = 104 bytes per frame
+    let mut data = frame_with_payload(header_word(0b11, 0b01, 0b0001, 0b00, false, 0b00), 104, &[]);
+    data.extend_from_slice(&frames);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(true), header.is_vbr());
+
+    Ok(())
+}
+
+#[test]
+fn cbr_duration_from_bytes() -> anyhow::Result<()> {
+    // Only the first frames have been read
+    let header = Header::read_from_source(
+        &mut mpeg1_layer3_frames(3).as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+    )?;
+    assert_eq!(
+        Some(duration_from_samples(10 * 1152, 44100)),
+        header.cbr_duration_from_bytes(10 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64)
+    );
+
+    // 417 or 418 bytes per frame with padding
+    assert_eq!(
+        Some(duration_from_samples(1000 * 1152, 44100)),
+        header.cbr_duration_from_bytes(417_960)
+    );
+
+    let mut data = mpeg1_layer3_xing_frame(*b"Xing", 0b0001, &3u32.to_be_bytes());
+    data.extend(mpeg1_layer3_frames(3));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::PreferVbrHeaders)?;
+    assert_eq!(None, header.cbr_duration_from_bytes(417_960));
+
+    Ok(())
+}
+
+#[test]
+fn exact_duration_rational() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    // 11520 samples / 44100 Hz = 12_800_000_000 ns / 49
+    assert_eq!(Some((12_800_000_000, 49)), header.exact_duration_rational());
+    let (numerator, denominator) = header.exact_duration_rational().unwrap();
+    assert_eq!(
+        duration_from_samples(header.total_sample_count, 44100),
+        Duration::from_nanos(numerator / denominator)
+    );
+
+    // Unknown sample rate
+    let header = AggregationState::new().finalize();
+    assert_eq!(None, header.exact_duration_rational());
+
+    Ok(())
+}
+
+#[test]
+fn stream_length() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_frames(10);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        StreamLength {
+            samples: header.total_sample_count,
+            duration: header.total_duration,
+            sample_rate_basis: Some(44100),
+        },
+        header.length()
+    );
+
+    // Mixed sample rates: MPEG-1 Layer III, 128 kbps, 48 kHz
+    let frame = frame_with_payload(header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00), 384, &[]);
+    data.extend_from_slice(&frame);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let length = header.length();
+    assert_eq!(11 * 1152, length.samples);
+    assert_eq!(header.total_duration, length.duration);
+    assert_eq!(None, length.sample_rate_basis);
+
+    Ok(())
+}
+
+#[test]
+fn stream_byte_len() -> anyhow::Result<()> {
+    let mut data = mpeg1_layer3_frames(10);
+    data.extend(apev2_block(32, 0, false));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.stream_byte_len);
+
+    let header = Header::read_from_slice(&data, ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(data.len() as u64), header.stream_byte_len);
+
+    let path = std::env::temp_dir().join(format!(
+        "mpeg-audio-header-stream-byte-len-{}.mp3",
+        std::process::id()
+    ));
+    std::fs::write(&path, &data)?;
+    let header = Header::read_from_path(&path, ParseMode::PreferVbrHeaders);
+    std::fs::remove_file(&path)?;
+    assert_eq!(Some(data.len() as u64), header?.stream_byte_len);
+
+    Ok(())
+}
+
+#[test]
+fn merge_headers_of_split_stream() -> anyhow::Result<()> {
+    // 32 kbps at 44.1 kHz = 104 bytes per frame
+    let low_bitrate_frame =
+        frame_with_payload(header_word(0b11, 0b01, 0b0001, 0b00, false, 0b00), 104, &[]);
+    let high_bitrate_frame = mpeg1_layer3_frames(1);
+    let mut data = Vec::new();
+    for (frame, count) in [
+        (&high_bitrate_frame, 3),
+        (&low_bitrate_frame, 4),
+        (&high_bitrate_frame, 2),
+        (&low_bitrate_frame, 1),
+    ] {
+        for _ in 0..count {
+            data.extend_from_slice(frame);
+        }
+    }
+    let options = ParseOptions {
+        min_bitrate_run_frames: Some(2),
+        ..Default::default()
+    };
+    let whole = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+
+    // Split within the first run of low bitrate frames
+    let split_offset = 3 * 417 + 2 * 104;
+    let (head, tail) = data.split_at(split_offset);
+    let head = Header::read_from_source_with_options(
+        &mut &head[..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    let tail = Header::read_from_source_with_options(
+        &mut &tail[..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    let merged = head.merge(tail);
+
+    assert!((whole.bitrate_cv.unwrap() - merged.bitrate_cv.unwrap()).abs() < 1e-9);
+    assert_eq!(
+        Header {
+            bitrate_cv: None,
+            ..whole
+        },
+        Header {
+            bitrate_cv: None,
+            ..merged
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn merge_headers_without_frames() -> anyhow::Result<()> {
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64;
+    let frames = mpeg1_layer3_frames(3);
+    let head = Header::read_from_source(&mut frames.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+
+    // Only a truncated frame in the second part
+    let truncated_frame = &mpeg1_layer3_frames(1)[..200];
+    let tail = Header::read_from_source(&mut &truncated_frame[..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(0, tail.total_sample_count);
+    assert_eq!(Some(0), tail.truncated_frame);
+    let merged = head.clone().merge(tail);
+    assert_eq!(3 * 1152, merged.total_sample_count);
+    assert_eq!(Some(3 * frame_size), merged.truncated_frame);
+    assert_eq!(head.partial, merged.partial);
+
+    // Only a partial first part
+    let partial_head = Header {
+        partial: true,
+        alignment_errors: 1,
+        ..head.clone()
+    };
+    let tail = Header::read_from_source(&mut &truncated_frame[..], ParseMode::IgnoreVbrHeaders)?;
+    let merged = partial_head.merge(tail);
+    assert!(merged.partial);
+    assert_eq!(1, merged.alignment_errors);
+
+    // Only a truncated frame in the first part
+    let tail = Header::read_from_source(&mut frames.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let head = Header::read_from_source(&mut &truncated_frame[..], ParseMode::IgnoreVbrHeaders)?;
+    let merged = head.merge(tail);
+    assert_eq!(3 * 1152, merged.total_sample_count);
+    assert_eq!(Some(0), merged.truncated_frame);
+
+    // Stopped at a metadata block in either part
+    let options = ParseOptions {
+        stop_at_metadata: true,
+        ..Default::default()
+    };
+    let id3v2_tag = b"ID3\x04\x00\x00\x00\x00\x00\x00";
+    let frames_head =
+        Header::read_from_source(&mut frames.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let stopped = Header::read_from_source_with_options(
+        &mut &id3v2_tag[..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(Some(0), stopped.stopped_at);
+    let merged = frames_head.clone().merge(stopped.clone());
+    assert_eq!(Some(3 * frame_size), merged.stopped_at);
+    assert!(merged.partial);
+    let merged = stopped.merge(frames_head);
+    assert_eq!(Some(0), merged.stopped_at);
+    assert!(merged.partial);
+
+    // Failed reading after a leading metadata block
+    let options = ParseOptions {
+        tolerate_io_errors: true,
+        ..Default::default()
+    };
+    let leading_tag = Header::read_from_source(&mut &id3v2_tag[..], ParseMode::IgnoreVbrHeaders)?;
+    let failed = Header::read_from_source_with_options(
+        &mut frames.as_slice().chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    let merged = leading_tag.merge(failed);
+    assert_eq!(
+        Some(id3v2_tag.len() as u64 + 3 * frame_size),
+        merged.read_error.map(|err| err.byte_offset)
+    );
+    assert!(merged.partial);
+
+    Ok(())
+}
+
+#[test]
+fn max_bytes_exceeded_by_oversized_metadata() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let max_bytes = frames.len() as u64 + 1;
+    // ID3v2 tag with a declared size of 256 MiB that is followed
+    // by more bytes than permitted
+    let mut data = b"ID3\x04\x00\x00\x7F\x7F\x7F\x7F".to_vec();
+    data.extend(mpeg1_layer3_frames(20));
+
+    let options = ParseOptions {
+        max_bytes: Some(max_bytes),
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut frames.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+
+    let err = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::LimitExceeded(limit) if *limit == max_bytes));
+    assert_eq!(max_bytes, err.position().byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn max_bytes_at_end_of_stream() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(10);
+
+    // The stream ends exactly at the limit
+    let options = ParseOptions {
+        max_bytes: Some(data.len() as u64),
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(None, header.truncated_frame);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    // The last byte of the stream is beyond the limit
+    let options = ParseOptions {
+        max_bytes: Some(data.len() as u64 - 1),
+        ..Default::default()
+    };
+    let err = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err.source(), Error::LimitExceeded(max_bytes) if *max_bytes == data.len() as u64 - 1)
+    );
+    assert_eq!(data.len() as u64 - 1, err.position().byte_offset());
+
+    Ok(())
+}
+
+/// Non-seekable source that returns at most 3 bytes per read
+///
+/// Every other read is interrupted, like a slow pipe.
+struct ShortReads<'a>(&'a [u8], bool);
+
+impl Read for ShortReads<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.1 = !self.1;
+        if self.1 {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+        let num_bytes = buf.len().min(self.0.len()).min(3);
+        buf[..num_bytes].copy_from_slice(&self.0[..num_bytes]);
+        self.0 = &self.0[num_bytes..];
+        Ok(num_bytes)
+    }
+}
+
+#[test]
+fn skip_metadata_with_short_and_interrupted_reads() -> anyhow::Result<()> {
+    // ID3v2 tag with 1000 bytes of (syncsafe) content
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x07\x68".to_vec();
+    data.resize(data.len() + 1000, 0xFF);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(
+        &mut ShortReads(data.as_slice(), false),
+        ParseMode::IgnoreVbrHeaders,
+    )?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    Ok(())
+}
+
+#[test]
+fn swap_bytes_in_whole_words() -> anyhow::Result<()> {
+    let data = (0..=100).collect::<Vec<u8>>();
+    let mut expected = data
+        .chunks_exact(2)
+        .flat_map(|word| [word[1], word[0]])
+        .collect::<Vec<_>>();
+    // A trailing odd byte is not swapped
+    expected.push(100);
+
+    // A single read fills the whole buffer
+    let mut source = data.as_slice();
+    let mut buf = [0; 100];
+    assert_eq!(100, SwappedBytes::new(&mut source).read(&mut buf)?);
+    assert_eq!(expected[..100], buf);
+
+    // Odd buffer sizes carry the second byte of the last word
+    let mut source = data.as_slice();
+    let mut swapped = SwappedBytes::new(&mut source);
+    let mut buf = [0; 3];
+    assert_eq!(3, swapped.read(&mut buf)?);
+    assert_eq!(expected[..3], buf);
+    assert_eq!(3, swapped.read(&mut buf)?);
+    assert_eq!(expected[3..6], buf);
+
+    // Short and interrupted reads
+    let mut source = ShortReads(&data, false);
+    let mut swapped_data = Vec::new();
+    SwappedBytes::new(&mut source).read_to_end(&mut swapped_data)?;
+    assert_eq!(expected, swapped_data);
+
+    Ok(())
+}
+
+#[test]
+fn chained_sources() -> anyhow::Result<()> {
+    // ID3v2 tag with 1000 bytes of (syncsafe) content
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x07\x68".to_vec();
+    data.resize(data.len() + 1000, 0xFF);
+    data.extend(mpeg1_layer3_frames(10));
+    let expected_header =
+        Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+
+    for split_offset in (0..data.len()).step_by(13) {
+        let (data_a, data_b) = data.split_at(split_offset);
+        let header = Header::read_from_source(
+            &mut ShortReads(data_a, false).chain(data_b),
+            ParseMode::IgnoreVbrHeaders,
+        )?;
+        assert_eq!(expected_header, header);
+    }
+
+    // The position is accurate if skipping fails at the boundary,
+    // i.e. after the buffered bytes of the first chunk
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x4E\x10".to_vec();
+    data.resize(data.len() + 10_000, 0xFF);
+    let err = Header::read_from_source(
+        &mut data[..6_000].chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::IoError(_)));
+    assert_eq!(6_000, err.position().byte_offset());
+
+    Ok(())
+}
+
+/// Source that always fails
+struct FailingRead;
+
+impl Read for FailingRead {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+#[test]
+fn tolerate_io_errors() -> anyhow::Result<()> {
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64;
+    let data = mpeg1_layer3_frames(10);
+    let options = ParseOptions {
+        tolerate_io_errors: true,
+        ..Default::default()
+    };
+
+    let err = Header::read_from_source(
+        &mut data[..5 * 417 + 100].chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::IoError(_)));
+
+    // Failing within a frame
+    let header = Header::read_from_source_with_options(
+        &mut data[..5 * 417 + 100].chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(5 * 1152, header.total_sample_count);
+    assert_eq!(Some(5 * frame_size), header.truncated_frame);
+    assert!(header.partial);
+    let read_error = header.read_error.unwrap();
+    assert_eq!(5 * frame_size + 100, read_error.byte_offset);
+    assert_eq!(std::io::ErrorKind::BrokenPipe, read_error.kind);
+
+    // Failing between frames
+    let header = Header::read_from_source_with_options(
+        &mut data[..5 * 417].chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(5 * 1152, header.total_sample_count);
+    assert_eq!(None, header.truncated_frame);
+    assert!(header.partial);
+    assert_eq!(
+        Some(5 * frame_size),
+        header.read_error.map(|err| err.byte_offset)
+    );
+
+    // Not tolerated before the first audio frame
+    let err = Header::read_from_source_with_options(
+        &mut data[..100].chain(FailingRead),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )
+    .unwrap_err();
+    assert!(matches!(err.source(), Error::IoError(_)));
+
+    Ok(())
+}
+
+#[test]
+fn scan_range_boundaries() -> anyhow::Result<()> {
+    let frame_size = MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64;
+    let data = mpeg1_layer3_frames(10);
+
+    let result = Header::scan_range_boundaries(&mut Cursor::new(&data), 100..2000)?;
+    assert_eq!(Some(frame_size), result.first_full_frame_offset);
+    assert_eq!(Some(4 * frame_size), result.last_full_frame_end);
+    assert_eq!(frame_size - 100, result.partial_head_bytes);
+    assert_eq!(2000 - 4 * frame_size, result.partial_tail_bytes);
+    assert_eq!(
+        vec![frame_size, 2 * frame_size, 3 * frame_size],
+        result
+            .frames
+            .iter()
+            .map(|(frame_offset, _)| *frame_offset)
+            .collect::<Vec<_>>()
+    );
+
+    // Stitching the results of adjacent ranges
+    let mut frame_count = 0;
+    for start in (0..data.len() as u64).step_by(1000) {
+        let result = Header::scan_range_boundaries(&mut Cursor::new(&data), start..start + 1000)?;
+        frame_count += result.frames.len();
+        if result.partial_tail_bytes > 0 {
+            frame_count += 1;
+        }
+    }
+    assert_eq!(10, frame_count);
+
+    let result = Header::scan_range_boundaries(&mut Cursor::new(&data), 0..data.len() as u64)?;
+    assert_eq!(10, result.frames.len());
+    assert_eq!(0, result.partial_head_bytes);
+    assert_eq!(0, result.partial_tail_bytes);
+
+    // Within a single frame
+    let result = Header::scan_range_boundaries(&mut Cursor::new(&data), 10..400)?;
+    assert_eq!(None, result.first_full_frame_offset);
+    assert_eq!(None, result.last_full_frame_end);
+    assert_eq!(390, result.partial_head_bytes);
+    assert_eq!(0, result.partial_tail_bytes);
+    assert!(result.frames.is_empty());
+
+    // Metadata block between the frames
+    let id3v2_tag = b"ID3\x04\x00\x00\x00\x00\x00\x10";
+    let mut data = mpeg1_layer3_frames(3);
+    data.extend_from_slice(id3v2_tag);
+    data.resize(data.len() + 16, 0);
+    data.extend(mpeg1_layer3_frames(3));
+    let result = Header::scan_range_boundaries(&mut Cursor::new(&data), 0..data.len() as u64)?;
+    assert_eq!(6, result.frames.len());
+    assert_eq!(3 * frame_size + 26, result.frames[3].0);
+    assert_eq!(Some(data.len() as u64), result.last_full_frame_end);
+    assert_eq!(0, result.partial_head_bytes);
+    assert_eq!(0, result.partial_tail_bytes);
+
+    // Free format frame at the end
+    let mut data = mpeg1_layer3_frames(2);
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b0000, 0b00, false, 0b00),
+        300,
+        &[],
+    ));
+    let result = Header::scan_range_boundaries(&mut Cursor::new(&data), 0..data.len() as u64)?;
+    assert_eq!(3, result.frames.len());
+    assert_eq!(2 * frame_size, result.frames[2].0);
+    assert_eq!(None, result.frames[2].1.frame_size);
+    assert_eq!(Some(data.len() as u64), result.last_full_frame_end);
+    assert_eq!(0, result.partial_tail_bytes);
+
+    Ok(())
+}
+
+#[test]
+fn min_lead_in_frames() -> anyhow::Result<()> {
+    // Spurious sync word followed by 2 frames, junk, and 5 frames
+    let mut data = vec![0xFF, 0xFB, 0x90, 0x00];
+    data.extend(mpeg1_layer3_frames(2));
+    data.extend([0; 100]);
+    data.extend(mpeg1_layer3_frames(5));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(1, header.lead_in_confidence);
+
+    let options = ParseOptions {
+        min_lead_in_frames: 3,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(5, header.lead_in_confidence);
+    assert_eq!(5 * 1152, header.total_sample_count);
+    let spurious = data;
+    assert_eq!(
+        Some((4 + 2 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE + 100) as u64),
+        header.first_frame_offset
+    );
+
+    // Captured mid-stream, starting with the fragment of a frame
+    let frames = mpeg1_layer3_frames(10);
+    let data = &frames[300..];
+    let header = Header::read_from_source_with_options(
+        &mut &data[..],
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(9 * 1152, header.total_sample_count);
+    assert_eq!(
+        Some((MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE - 300) as u64),
+        header.first_frame_offset
+    );
+
+    // The scanner rejects the spurious sync word as well
+    let mut source = spurious.as_slice();
+    let mut scanner = MpegSyncScanner::new(&mut source, &ParseOptions::default());
+    while scanner.next_frame()?.is_some() {}
+    // The spurious frame overlaps with the first of the 2 frames
+    assert_eq!(1 + 1 + 5, scanner.frame_count());
+    let mut source = spurious.as_slice();
+    let mut scanner = MpegSyncScanner::new(&mut source, &options);
+    while scanner.next_frame()?.is_some() {}
+    assert_eq!(5, scanner.frame_count());
+
+    Ok(())
+}
+
+#[test]
+fn profiles_of_spliced_stream() -> anyhow::Result<()> {
+    // 128 kbps at 48 kHz = 384 bytes per frame
+    let frame_48000hz =
+        frame_with_payload(header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00), 384, &[]);
+    let mut data = mpeg1_layer3_frames(3);
+    for _ in 0..2 {
+        data.extend_from_slice(&frame_48000hz);
+    }
+    data.extend(mpeg1_layer3_frames(1));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let first_frame = header.first_frame.as_ref().expect("first frame");
+    assert_eq!(44100, first_frame.sample_rate_hz);
+    assert_eq!(Some(128_000), first_frame.bitrate_bps);
+    assert_eq!(
+        Some(MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u16),
+        first_frame.frame_size
+    );
+    assert_eq!(
+        vec![
+            ProfileSummary {
+                version: Version::Mpeg1,
+                layer: Layer::Layer3,
+                sample_rate_hz: 44100,
+                frame_count: 4,
+                total_samples: 4 * 1152,
+            },
+            ProfileSummary {
+                version: Version::Mpeg1,
+                layer: Layer::Layer3,
+                sample_rate_hz: 48000,
+                frame_count: 2,
+                total_samples: 2 * 1152,
+            },
+        ],
+        header.profiles
+    );
+    assert_eq!(
+        Duration::from_millis(24),
+        header.profiles[1].frame_duration()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn nominal_frame_duration() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(2);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    // 1152 samples at 44.1 kHz
+    let frame_duration = Duration::from_nanos(26_122_448);
+    assert_eq!(Some(frame_duration), header.nominal_frame_duration());
+    for (_, frame_header) in Header::scan_candidates(&mut data.as_slice())? {
+        assert_eq!(frame_duration, frame_header.frame_duration());
+    }
+
+    // Inconsistent sample rates
+    let mut data = data;
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00),
+        384,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.nominal_frame_duration());
+
+    Ok(())
+}
+
+#[test]
+fn encode_frame_header_word() -> anyhow::Result<()> {
+    let mut decoded_count = 0;
+    for version_bits in [0b00, 0b10, 0b11] {
+        for layer_bits in 0b01..=0b11 {
+            for bitrate_bits in 0b0000..0b1111 {
+                for sample_rate_bits in 0b00..0b11 {
+                    for mode_bits in 0b00..=0b11 {
+                        // All other bits, including the inverted protection bit
+                        let other_bits = [
+                            0x0000_0000,
+                            0x0001_0000,
+                            0x0000_0300,
+                            0x0000_003F,
+                            0x0001_031D,
+                        ][decoded_count % 5];
+                        let word = header_word(
+                            version_bits,
+                            layer_bits,
+                            bitrate_bits,
+                            sample_rate_bits,
+                            false,
+                            mode_bits,
+                        ) & !0x0001_0000
+                            | other_bits;
+                        let candidates =
+                            Header::scan_candidates(&mut word.to_be_bytes().as_slice())?;
+                        let Some((_, frame_header)) = candidates.first() else {
+                            // Disallowed combination of bitrate and mode or reserved emphasis
+                            continue;
+                        };
+                        assert_eq!(Some(word), frame_header.to_header_word());
+                        decoded_count += 1;
+                    }
+                }
+            }
+        }
+    }
+    assert!(decoded_count > 1000);
+
+    let data = mpeg1_layer3_frames(1);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    let frame_header = header.first_frame.unwrap();
+    assert_eq!(
+        Some(mpeg1_layer3_header_word()),
+        frame_header.to_header_word()
+    );
+    assert_eq!(
+        None,
+        FrameHeader {
+            bitrate_bps: Some(100_000),
+            ..frame_header.clone()
+        }
+        .to_header_word()
+    );
+    assert_eq!(
+        None,
+        FrameHeader {
+            version: Version::Mpeg2,
+            ..frame_header
+        }
+        .to_header_word()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn convert_between_samples_and_frame_indexes() -> anyhow::Result<()> {
+    let data = mpeg1_layer3_frames(2);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(0), header.frame_index_for_sample(1151));
+    assert_eq!(Some(1), header.frame_index_for_sample(1152));
+    assert_eq!(Some(2304), header.sample_for_frame_index(2));
+    assert_eq!(None, header.sample_for_frame_index(u64::MAX));
+
+    // MPEG-1 Layer I, 128 kbps, 44.1 kHz = 136 bytes per frame
+    let data = frame_with_payload(header_word(0b11, 0b11, 0b0100, 0b00, false, 0b00), 136, &[]);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(Some(2), header.frame_index_for_sample(768));
+    assert_eq!(Some(768), header.sample_for_frame_index(2));
+
+    // Inconsistent sample rates
+    let mut data = mpeg1_layer3_frames(2);
+    data.extend(frame_with_payload(
+        header_word(0b11, 0b01, 0b1001, 0b01, false, 0b00),
+        384,
+        &[],
+    ));
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.frame_index_for_sample(0));
+    assert_eq!(None, header.sample_for_frame_index(0));
+
+    Ok(())
+}
+
+fn id3v24_frame(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut frame = id.to_vec();
+    // Synchronization safe size, less than 128 bytes
+    assert!(body.len() < 128);
+    frame.extend([0, 0, 0, body.len() as u8, 0, 0]);
+    frame.extend_from_slice(body);
+    frame
+}
+
+#[test]
+fn parse_mllt_seek_points() -> anyhow::Result<()> {
+    let mut mllt = vec![0, 1];
+    // 417 bytes and 26 ms between references
+    mllt.extend([0x00, 0x01, 0xA1, 0x00, 0x00, 0x1A]);
+    // 4 + 4 bits of deviation per reference
+    mllt.extend([4, 4, 0x12, 0x30]);
+    let mut frames = id3v24_frame(*b"TIT2", b"\x03Title");
+    frames.extend(id3v24_frame(*b"MLLT", &mllt));
+    // Padding
+    frames.resize(frames.len() + 10, 0);
+
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00".to_vec();
+    data.push(frames.len() as u8);
+    data.extend(frames);
+    data.extend(mpeg1_layer3_frames(10));
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, header.mllt_seek_points);
+
+    let options = ParseOptions {
+        parse_mllt: true,
+        ..Default::default()
+    };
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(
+        Some(vec![
+            (Duration::from_millis(28), 418),
+            (Duration::from_millis(54), 838),
+        ]),
+        header.mllt_seek_points
+    );
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(data.len() as u64, header.final_position.byte_offset());
+
+    // Oversized frames are skipped instead of buffered
+    mllt.resize((1 << 20) + 1, 0);
+    let mut frames = b"MLLT".to_vec();
+    // Synchronization safe size
+    frames.extend([0x00, 0x40, 0x00, 0x01, 0, 0]);
+    frames.extend(mllt);
+    let mut data = b"ID3\x04\x00\x00\x00\x40\x00\x0B".to_vec();
+    data.extend(frames);
+    data.extend(mpeg1_layer3_frames(10));
+    let header = Header::read_from_source_with_options(
+        &mut data.as_slice(),
+        ParseMode::IgnoreVbrHeaders,
+        &options,
+    )?;
+    assert_eq!(None, header.mllt_seek_points);
+    assert_eq!(None, header.truncated_metadata);
+    assert_eq!(10 * 1152, header.total_sample_count);
+
+    Ok(())
+}
+
+#[cfg(feature = "id3-lite")]
+#[test]
+fn extract_title_and_artist_of_leading_tag() -> anyhow::Result<()> {
+    let mut frames = id3v24_frame(*b"TALB", b"\x03Album");
+    // ISO-8859-1
+    frames.extend(id3v24_frame(*b"TIT2", b"\x00Caf\xE9\x00"));
+    // UTF-16 with BOM (little endian), multiple values
+    frames.extend(id3v24_frame(
+        *b"TPE1",
+        b"\x01\xFF\xFEA\x00r\x00t\x00\x00\x00B\x00",
+    ));
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00".to_vec();
+    data.push(frames.len() as u8);
+    data.extend(frames);
+    data.extend(mpeg1_layer3_frames(10));
+    // Trailing tag
+    let trailing_frame = id3v24_frame(*b"TIT2", b"\x03Trailing");
+    data.extend(b"ID3\x04\x00\x00\x00\x00\x00");
+    data.push(trailing_frame.len() as u8);
+    data.extend(trailing_frame);
+
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(
+        Some(BasicTags {
+            title: Some("Café".to_owned()),
+            artist: Some("Art".to_owned()),
+        }),
+        header.basic_tags
+    );
+    assert_eq!(10 * 1152, header.total_sample_count);
+
+    Ok(())
+}
+
+fn riff_chunk(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend((body.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+fn wave_file(format_tag: u16, data: &[u8], trailing_chunks: &[u8]) -> Vec<u8> {
+    let mut fmt = format_tag.to_le_bytes().to_vec();
+    fmt.resize(30, 0);
+    let mut chunks = b"WAVE".to_vec();
+    chunks.extend(riff_chunk(*b"fmt ", &fmt));
+    chunks.extend(riff_chunk(*b"data", data));
+    chunks.extend_from_slice(trailing_chunks);
+    riff_chunk(*b"RIFF", &chunks)
+}
+
+#[test]
+fn mpeg_audio_in_wave_file() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    // Trailing chunk that contains more MPEG frames
+    let trailing_chunks = riff_chunk(*b"junk", &mpeg1_layer3_frames(2));
+
+    let data = wave_file(0x0055, &frames, &trailing_chunks);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(None, header.truncated_frame);
+    let data_end_offset = (data.len() - trailing_chunks.len()) as u64;
+    assert_eq!(data_end_offset, header.final_position.byte_offset());
+
+    // The last frame exceeds the data chunk
+    let data = wave_file(0x0055, &frames[..frames.len() - 100], &trailing_chunks);
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(9 * 1152, header.total_sample_count);
+    let data_start_offset = (data.len() - trailing_chunks.len() - (frames.len() - 100)) as u64;
+    assert_eq!(
+        Some(data_start_offset + 9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64),
+        header.truncated_frame
+    );
+
+    // PCM
+    let data = wave_file(0x0001, &frames, &[]);
+    let err =
+        Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders).unwrap_err();
+    assert!(matches!(err.source(), Error::UnsupportedFormat(_)));
+
+    Ok(())
+}
+
+fn aiff_chunk(id: [u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend((body.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+fn aifc_file(compression_type: [u8; 4], data: &[u8], trailing_chunks: &[u8]) -> Vec<u8> {
+    // Channels, sample frames, sample size, and sample rate
+    let mut comm = vec![0; 18];
+    comm.extend_from_slice(&compression_type);
+    // Empty compression name (Pascal string)
+    comm.extend_from_slice(&[0, 0]);
+    // Offset 4 and block size 0
+    let mut ssnd = vec![0, 0, 0, 4, 0, 0, 0, 0];
+    ssnd.extend_from_slice(b"skip");
+    ssnd.extend_from_slice(data);
+    let mut chunks = b"AIFC".to_vec();
+    chunks.extend(aiff_chunk(*b"FVER", &[0xA2, 0x80, 0x51, 0x40]));
+    chunks.extend(aiff_chunk(*b"COMM", &comm));
+    chunks.extend(aiff_chunk(*b"SSND", &ssnd));
+    chunks.extend_from_slice(trailing_chunks);
+    aiff_chunk(*b"FORM", &chunks)
+}
+
+#[test]
+fn mpeg_audio_in_aifc_file() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    // Trailing chunk that contains more MPEG frames
+    let trailing_chunks = aiff_chunk(*b"APPL", &mpeg1_layer3_frames(2));
+
+    for compression_type in [*b".mp3", *b"ms\x00\x55"] {
+        let data = aifc_file(compression_type, &frames, &trailing_chunks);
+        let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+        assert_eq!(10 * 1152, header.total_sample_count);
+        assert_eq!(0, header.junk_byte_count);
+        assert_eq!(None, header.truncated_frame);
+        assert_eq!(
+            (data.len() - frames.len() - trailing_chunks.len()) as u64,
+            header.first_frame_offset.unwrap()
+        );
+        let data_end_offset = (data.len() - trailing_chunks.len()) as u64;
+        assert_eq!(data_end_offset, header.final_position.byte_offset());
+    }
+
+    // PCM
+    let data = aifc_file(*b"NONE", &frames, &[]);
+    let err =
+        Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders).unwrap_err();
+    assert!(matches!(err.source(), Error::UnsupportedFormat(_)));
+
+    // Uncompressed AIFF
+    let mut data = aifc_file(*b".mp3", &frames, &[]);
+    data[8..12].copy_from_slice(b"AIFF");
+    let err =
+        Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders).unwrap_err();
+    assert!(matches!(err.source(), Error::UnsupportedFormat(_)));
+
+    Ok(())
+}
+
+#[test]
+fn truncated_final_frame() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let complete_frames = &frames[..9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE];
+    let complete_header =
+        Header::read_from_source(&mut &complete_frames[..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(None, complete_header.truncated_frame);
+
+    // The size of the last frame exceeds the end of the stream
+    let data = &frames[..frames.len() - 100];
+    for parse_mode in [ParseMode::IgnoreVbrHeaders, ParseMode::PreferVbrHeaders] {
+        let header = Header::read_from_source(&mut &data[..], parse_mode)?;
+        assert_eq!(9 * 1152, header.total_sample_count);
+        assert_eq!(
+            Some(9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64),
+            header.truncated_frame
+        );
+        assert_eq!(complete_header.total_duration, header.total_duration);
+        assert_eq!(header.total_duration, header.final_position.duration());
+        assert_eq!(data.len() as u64, header.final_position.byte_offset());
+        assert_eq!(0, header.alignment_errors);
+    }
+
+    // Only the header word of the last frame is available
+    let data = &frames[..9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE + 4];
+    let header = Header::read_from_source(&mut &data[..], ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(9 * 1152, header.total_sample_count);
+    assert_eq!(
+        Some(9 * MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE as u64),
+        header.truncated_frame
+    );
+
+    Ok(())
+}
+
+fn icy_stream(audio: &[u8], metaint: usize, metadata: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for (index, chunk) in audio.chunks(metaint).enumerate() {
+        data.extend_from_slice(chunk);
+        if chunk.len() < metaint {
+            break;
+        }
+        // Metadata is only sent with the first block
+        if index == 0 {
+            let block_len = metadata.len().div_ceil(16);
+            data.push(block_len as u8);
+            data.extend_from_slice(metadata);
+            data.resize(data.len() + block_len * 16 - metadata.len(), 0);
+        } else {
+            data.push(0);
+        }
+    }
+    data
+}
+
+#[test]
+fn strip_interleaved_icy_metadata() -> anyhow::Result<()> {
+    let frames = mpeg1_layer3_frames(10);
+    let data = icy_stream(
+        &frames,
+        1000,
+        b"StreamTitle='Artist - It's a Title';StreamUrl='';",
+    );
+
+    let mut source = IcyReader::new(data.as_slice(), 1000);
+    let header = Header::read_from_source(&mut source, ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(None, header.truncated_frame);
+    assert_eq!(frames.len() as u64, header.final_position.byte_offset());
+    assert_eq!(Some("Artist - It's a Title"), source.stream_title());
+
+    assert_eq!(0, header.alignment_errors);
+
+    // The interleaved metadata breaks the alignment of frames
+    let header = Header::read_from_source(&mut data.as_slice(), ParseMode::IgnoreVbrHeaders)?;
+    assert_ne!(0, header.alignment_errors);
+
+    // Pass through all bytes
+    let mut source = IcyReader::new(frames.as_slice(), 0);
+    let header = Header::read_from_source(&mut source, ParseMode::IgnoreVbrHeaders)?;
+    assert_eq!(10 * 1152, header.total_sample_count);
+    assert_eq!(None, source.stream_title());
+
+    Ok(())
+}
+
+#[test]
+fn looks_like_mpeg_audio_prefix() {
+    let frames = mpeg1_layer3_frames(2);
+    assert!(looks_like_mpeg_audio(&frames[..4]));
+    assert!(looks_like_mpeg_audio(&frames));
+
+    // Leading ID3v2 tag
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x0A".to_vec();
+    data.extend([0; 10]);
+    data.extend_from_slice(&frames);
+    assert!(looks_like_mpeg_audio(&data));
+    assert!(!looks_like_mpeg_audio(&data[..20]));
+
+    // The next frame is missing
+    let mut data = frames[..MPEG1_LAYER3_128KBPS_44100HZ_FRAME_SIZE].to_vec();
+    data.extend([0; 4]);
+    assert!(!looks_like_mpeg_audio(&data));
+
+    assert!(!looks_like_mpeg_audio(&[]));
+    assert!(!looks_like_mpeg_audio(b"RIFF\x00\x00\x00\x00WAVE"));
+}
+
+#[test]
+fn duration_from_samples_matches_previous_calculation() {
+    for sample_rate_hz in [8000, 11025, 22050, 44100, 48000] {
+        for sample_count in [0, 1, 576, 1152, 11_520, 1_234_567, 987_654_321] {
+            let sample_rate = u64::from(sample_rate_hz);
+            let seconds = sample_count / sample_rate;
+            let nanoseconds =
+                (sample_count * 1_000_000_000) / sample_rate - 1_000_000_000 * seconds;
+            assert_eq!(
+                Duration::new(seconds, nanoseconds as u32),
+                duration_from_samples(sample_count, sample_rate_hz)
+            );
+        }
+    }
+    assert_eq!(
+        Duration::from_nanos(261_224_489),
+        duration_from_samples(11_520, 44100)
+    );
+    // No overflow for very long streams
+    assert_eq!(
+        Duration::from_secs(u64::MAX / 48000),
+        duration_from_samples(u64::MAX / 48000 * 48000, 48000)
+    );
+}
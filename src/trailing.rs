@@ -0,0 +1,176 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Metadata blocks at the end of a stream
+//!
+//! Trailing tags are detected by reading backwards from the end of
+//! a seekable stream without scanning the MPEG frames. The following
+//! blocks are recognized in any order:
+//!
+//! - ID3 v1 tags: <https://id3.org/ID3v1>
+//! - Lyrics3 v1/v2 tags: <https://id3.org/Lyrics3v2>
+//! - APE tags with a footer: <https://wiki.hydrogenaud.io/index.php?title=APE_Tags_Header>
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::apev2::{self, APEV2_HEADER_SIZE};
+
+const ID3V1_SIZE: u64 = 128;
+
+const LYRICS3_BEGIN: &[u8; 11] = b"LYRICSBEGIN";
+
+const LYRICS3V1_END: &[u8; 9] = b"LYRICSEND";
+
+/// Maximum size of the lyrics of a Lyrics3 v1 tag
+const LYRICS3V1_MAX_LYRICS_SIZE: u64 = 5100;
+
+const LYRICS3V2_END: &[u8; 9] = b"LYRICS200";
+
+/// The decimal size that precedes the end marker of a Lyrics3 v2 tag
+const LYRICS3V2_SIZE_FIELD_SIZE: u64 = 6;
+
+/// Kind of a metadata block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetadataKind {
+    /// ID3 v1 tag
+    Id3v1,
+
+    /// Lyrics3 tag, either version 1 or 2
+    Lyrics3,
+
+    /// APE tag, including the optional header
+    ApeV2,
+}
+
+/// Location of a metadata block within the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataRegion {
+    /// Kind of the metadata block
+    pub kind: MetadataKind,
+
+    /// Byte offset of the first byte
+    pub byte_offset: u64,
+
+    /// Size in bytes
+    pub byte_len: u64,
+}
+
+impl MetadataRegion {
+    /// Byte offset right after the last byte
+    #[must_use]
+    pub const fn end_offset(&self) -> u64 {
+        self.byte_offset + self.byte_len
+    }
+}
+
+/// Detect all metadata blocks at the end of a seekable stream
+///
+/// The stream starts at `start_offset` of the `source`. The offsets
+/// of the returned regions are relative to the start of the stream
+/// and ordered by ascending offset. Leaves the `source` at an
+/// unspecified position.
+pub(crate) fn read_trailing_tags(
+    source: &mut (impl Read + Seek),
+    start_offset: u64,
+) -> io::Result<Vec<MetadataRegion>> {
+    let mut end_offset = source.seek(SeekFrom::End(0))?.saturating_sub(start_offset);
+    let mut regions = Vec::new();
+    while let Some(region) = read_trailing_tag(source, start_offset, end_offset)? {
+        debug_assert_eq!(end_offset, region.end_offset());
+        end_offset = region.byte_offset;
+        regions.push(region);
+    }
+    regions.reverse();
+    Ok(regions)
+}
+
+/// Detect the metadata block that ends at `end_offset`
+fn read_trailing_tag(
+    source: &mut (impl Read + Seek),
+    start_offset: u64,
+    end_offset: u64,
+) -> io::Result<Option<MetadataRegion>> {
+    let mut read_at = |byte_offset: u64, buf: &mut [u8]| {
+        source.seek(SeekFrom::Start(start_offset + byte_offset))?;
+        source.read_exact(buf)
+    };
+    let region = |kind, byte_len: u64| {
+        Some(MetadataRegion {
+            kind,
+            byte_offset: end_offset - byte_len,
+            byte_len,
+        })
+    };
+
+    if end_offset >= ID3V1_SIZE {
+        let mut magic = [0; 3];
+        read_at(end_offset - ID3V1_SIZE, &mut magic)?;
+        if &magic == b"TAG" {
+            return Ok(region(MetadataKind::Id3v1, ID3V1_SIZE));
+        }
+    }
+
+    if end_offset >= APEV2_HEADER_SIZE.into() {
+        let mut footer = [0; APEV2_HEADER_SIZE as usize];
+        read_at(end_offset - u64::from(APEV2_HEADER_SIZE), &mut footer)?;
+        if let Some(apev2_info) =
+            apev2::parse_apev2_block(&footer).filter(|apev2_info| !apev2_info.is_header())
+        {
+            let header_size = if apev2_info.has_header() {
+                u64::from(APEV2_HEADER_SIZE)
+            } else {
+                0
+            };
+            let byte_len = u64::from(apev2_info.size) + header_size;
+            // The size includes the footer
+            if (u64::from(APEV2_HEADER_SIZE)..=end_offset).contains(&byte_len) {
+                return Ok(region(MetadataKind::ApeV2, byte_len));
+            }
+        }
+    }
+
+    let end_marker_len = LYRICS3V2_END.len() as u64;
+    if end_offset >= end_marker_len {
+        let mut end_marker = [0; LYRICS3V2_END.len()];
+        read_at(end_offset - end_marker_len, &mut end_marker)?;
+        if &end_marker == LYRICS3V2_END {
+            let mut size_field = [0; LYRICS3V2_SIZE_FIELD_SIZE as usize];
+            let size_field_offset = end_offset - end_marker_len;
+            if size_field_offset >= LYRICS3V2_SIZE_FIELD_SIZE {
+                read_at(
+                    size_field_offset - LYRICS3V2_SIZE_FIELD_SIZE,
+                    &mut size_field,
+                )?;
+                let lyrics_size = std::str::from_utf8(&size_field)
+                    .ok()
+                    .and_then(|size| size.parse::<u64>().ok());
+                if let Some(byte_len) = lyrics_size
+                    .map(|size| size + LYRICS3V2_SIZE_FIELD_SIZE + end_marker_len)
+                    .filter(|byte_len| *byte_len <= end_offset)
+                {
+                    let mut begin_marker = [0; LYRICS3_BEGIN.len()];
+                    read_at(end_offset - byte_len, &mut begin_marker)?;
+                    if &begin_marker == LYRICS3_BEGIN {
+                        return Ok(region(MetadataKind::Lyrics3, byte_len));
+                    }
+                }
+            }
+        } else if &end_marker == LYRICS3V1_END {
+            // The start of the lyrics is only marked at the beginning
+            let max_len = (LYRICS3_BEGIN.len() as u64 + LYRICS3V1_MAX_LYRICS_SIZE + end_marker_len)
+                .min(end_offset);
+            let mut tag = vec![0; max_len as usize];
+            read_at(end_offset - max_len, &mut tag)?;
+            if let Some(begin) = tag
+                .windows(LYRICS3_BEGIN.len())
+                .rposition(|window| window == LYRICS3_BEGIN)
+            {
+                return Ok(region(MetadataKind::Lyrics3, max_len - begin as u64));
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: The mpeg-audio-header authors
+// SPDX-License-Identifier: MPL-2.0
+
+//! Table of contents (TOC) of the VBRI header
+//!
+//! The VBRI header is written by the Fraunhofer encoder instead
+//! of a XING header. Each entry of the TOC contains the size of
+//! a segment of consecutive frames.
+//!
+//! <https://www.codeproject.com/Articles/8295/MPEG-Audio-Frame-Header#VBRIHeader>
+
+/// Table of contents of the VBRI header
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VbriToc {
+    /// Scale factor of the entries
+    pub scale: u16,
+
+    /// Number of frames per entry
+    ///
+    /// All segments contain this number of frames except for the
+    /// final segment that might be shorter.
+    pub frames_per_entry: u16,
+
+    /// Unscaled sizes of the segments
+    ///
+    /// Multiply by [`Self::scale`] to get the size in bytes.
+    pub entries: Vec<u32>,
+}
+
+impl VbriToc {
+    /// Byte offset of the segment that contains the given frame
+    ///
+    /// The offset is relative to the start of the first audio frame
+    /// after the VBRI header frame, i.e. the sum of the sizes of all
+    /// preceding segments. Frames beyond the end of the TOC are
+    /// located in the final segment.
+    ///
+    /// Returns 0 if the TOC is empty or if the number of frames
+    /// per entry is 0.
+    #[must_use]
+    pub fn seek_byte_offset_for_frame(&self, frame_index: u32) -> u64 {
+        let Some(last_segment_index) = self.entries.len().checked_sub(1) else {
+            return 0;
+        };
+        let Some(segment_index) = frame_index.checked_div(self.frames_per_entry.into()) else {
+            return 0;
+        };
+        let segment_index = (segment_index as usize).min(last_segment_index);
+        self.entries[..segment_index]
+            .iter()
+            .map(|entry| u64::from(*entry) * u64::from(self.scale))
+            .sum()
+    }
+}
+
+/// Parse the entries of the TOC
+///
+/// Returns `None` if the size of the entries is not between 1 and 4 bytes.
+pub(crate) fn parse_vbri_toc(
+    scale: u16,
+    frames_per_entry: u16,
+    entry_size: u16,
+    toc: &[u8],
+) -> Option<VbriToc> {
+    if !(1..=4).contains(&entry_size) {
+        return None;
+    }
+    let entries = toc
+        .chunks_exact(entry_size.into())
+        .map(|entry| {
+            entry
+                .iter()
+                .fold(0u32, |value, byte| (value << 8) | u32::from(*byte))
+        })
+        .collect();
+    Some(VbriToc {
+        scale,
+        frames_per_entry,
+        entries,
+    })
+}
@@ -1,7 +1,12 @@
 // SPDX-FileCopyrightText: The mpeg-audio-header authors
 // SPDX-License-Identifier: MPL-2.0
 
-use mpeg_audio_header::{Header, HeaderSource, Layer, Mode, Version};
+use mpeg_audio_header::{
+    AggregationState, ApeV2Info, AudioProperties, ByteOrder, ChannelSemantics, Diagnostic,
+    DurationCertainty, Emphasis, FormatProfile, FrameFlags, FrameHeader, Header, HeaderSource,
+    LameInfo, LamePreset, Layer, MetadataKind, MetadataRegion, Mode, ParseOptions, ProfileSummary,
+    RangeScanResult, ReadError, SampleRate, StreamLength, VbriToc, Version,
+};
 
 #[test]
 fn public_api() {
@@ -10,14 +15,169 @@ fn public_api() {
         source: HeaderSource::MpegFrameHeaders,
         layer: Some(Layer::Layer1),
         mode: Some(Mode::DualChannel),
+        channel_semantics: Some(ChannelSemantics::DualProgram),
+        emphasis: Some(Emphasis::Ms50_15),
+        common_flags: FrameFlags {
+            protection: Some(false),
+            padding: None,
+            private: Some(false),
+            copyright: Some(true),
+            original: Some(true),
+        },
         version: Some(Version::Mpeg1),
+        max_frame_size: None,
         avg_bitrate_bps: None,
+        avg_bitrate_kbps: None,
+        bitrate_cv: None,
+        uses_bit_reservoir: None,
+        bitrate_monotonic: None,
         min_channel_count: Default::default(),
         max_channel_count: Default::default(),
+        program_count: Default::default(),
+        mode_frame_counts: Default::default(),
+        mono_frame_count: 0,
+        stereo_frame_count: 0,
+        padding_frame_count: 0,
+        possible_multichannel: false,
+        multichannel_count: None,
         min_sample_rate_hz: Default::default(),
         max_sample_rate_hz: Default::default(),
         avg_sample_rate_hz: None,
         total_duration: Default::default(),
+        reconciled_declared_duration: None,
+        measured_duration: None,
+        measured_frame_count: None,
+        measured_sample_count: None,
         total_sample_count: Default::default(),
+        samples_per_frame: Some(384),
+        truncated_metadata: None,
+        truncated_frame: None,
+        alignment_errors: 0,
+        junk_byte_count: 0,
+        partial: false,
+        stopped_at: None,
+        read_error: Some(ReadError {
+            byte_offset: 0,
+            kind: std::io::ErrorKind::ConnectionReset,
+            message: String::new(),
+        }),
+        final_position: Default::default(),
+        stream_byte_len: None,
+        raw_vbr_header: None,
+        declared_frame_count: None,
+        declared_vbr: None,
+        vbr_header_suspect: false,
+        cbr_claim_violated: false,
+        vbr_frame_samples: None,
+        vbri_toc: Some(VbriToc {
+            scale: 1,
+            frames_per_entry: 1,
+            entries: vec![417],
+        }),
+        vbri_delay: None,
+        lame_info: Some(LameInfo {
+            encoder_version: "LAME3.100".to_owned(),
+            encoder_delay: 576,
+            encoder_padding: 0,
+            preset: Some(LamePreset::Vbr(2)),
+        }),
+        start_skip_samples: None,
+        end_skip_samples: None,
+        min_bitrate_runs: Vec::new(),
+        lead_in_confidence: 0,
+        first_frame_offset: None,
+        audio_end_offset: None,
+        profiles: vec![ProfileSummary {
+            version: Version::Mpeg1,
+            layer: Layer::Layer1,
+            sample_rate_hz: 44100,
+            frame_count: 0,
+            total_samples: 0,
+        }],
+        mllt_seek_points: None,
+        apev2_info: Some(ApeV2Info {
+            version: 2000,
+            item_count: 0,
+            flags: 0,
+            size: 32,
+        }),
+        trailing_tags: vec![MetadataRegion {
+            kind: MetadataKind::Id3v1,
+            byte_offset: 0,
+            byte_len: 128,
+        }],
+        diagnostics: vec![Diagnostic::ResyncPerformed {
+            byte_offset: 0,
+            skipped_byte_count: 1,
+        }],
+        first_frame: None,
+        #[cfg(feature = "id3-lite")]
+        basic_tags: Some(mpeg_audio_header::BasicTags {
+            title: None,
+            artist: None,
+        }),
     };
+    let _properties: &dyn AudioProperties = &_header;
+    let _frame_header = FrameHeader {
+        version: Version::Mpeg1,
+        layer: Layer::Layer3,
+        mode: Mode::Stereo,
+        mode_extension: 0,
+        flags: FrameFlags {
+            protection: false,
+            padding: false,
+            private: false,
+            copyright: false,
+            original: true,
+        },
+        emphasis: Emphasis::None,
+        sample_count: 1152,
+        sample_rate_hz: 44100,
+        bitrate_bps: Some(128_000),
+        frame_size: Some(417),
+    };
+    let _range_scan_result = RangeScanResult {
+        first_full_frame_offset: None,
+        last_full_frame_end: None,
+        partial_head_bytes: 0,
+        partial_tail_bytes: 0,
+        frames: Vec::new(),
+    };
+    let _sample_rate = SampleRate::Other(64000);
+    let _certainty = DurationCertainty::RequiresFullScan;
+    let _length = StreamLength {
+        samples: 0,
+        duration: Default::default(),
+        sample_rate_basis: None,
+    };
+    let _options = ParseOptions {
+        byte_order: ByteOrder::Normal,
+        require_next_sync: false,
+        detect_multichannel: false,
+        capture_vbr_header: false,
+        min_bitrate_run_frames: None,
+        max_bytes: None,
+        min_lead_in_frames: 0,
+        parse_mllt: false,
+        detect_vbr_header_only: false,
+        allow_reserved_emphasis: false,
+        identify_only: false,
+        stop_at_metadata: false,
+        assume_no_metadata: false,
+        identify_frame_count: ParseOptions::DEFAULT_IDENTIFY_FRAME_COUNT,
+        detect_bit_reservoir: false,
+        detect_bitrate_monotonic: false,
+        sync_alignment: None,
+        expected_profile: Some(FormatProfile {
+            version: Version::Mpeg1,
+            layer: Layer::Layer3,
+            sample_rate_hz: 44100,
+        }),
+        vbr_header_tolerance_percent: None,
+        error_context_len: 0,
+        collect_diagnostics: false,
+        tolerate_io_errors: false,
+    };
+
+    let _header = AggregationState::new().finalize();
 }